@@ -0,0 +1,310 @@
+// draw the interactive charts of the report (Content::Chart), without any dependency to be viewed offline
+// each div.chart contains the json of the chart in a script tag
+// hover : show the nearest point, drag : zoom on the selected rectangle, double click : reset the zoom, click on a legend : hide or show the serie
+(function () {
+    "use strict";
+
+    var MARGIN = { left : 80, right : 20, top : 15, bottom : 50 };
+    var HEIGHT = 450;
+    // the distance in pixels to show the tooltip of a point
+    var HOVER_DISTANCE = 12;
+    // the smallest rectangle in pixels to zoom
+    var MIN_ZOOM_SIZE = 5;
+
+    // get about count ticks between min and max, at round values
+    function getTicks(min, max, count) {
+        var span = max - min;
+        if (!(span > 0)) {
+            return [min];
+        }
+        var step = Math.pow(10, Math.floor(Math.log10(span / count)));
+        var error = span / count / step;
+        if (error >= 7.5) {
+            step *= 10;
+        } else if (error >= 3.5) {
+            step *= 5;
+        } else if (error >= 1.5) {
+            step *= 2;
+        }
+        var ticks = [];
+        for (var value = Math.ceil(min / step) * step; value <= max + step * 1e-9; value += step) {
+            ticks.push(Math.abs(value) < step * 1e-9 ? 0 : value);
+        }
+        return ticks;
+    }
+
+    function formatValue(value) {
+        var absolute = Math.abs(value);
+        if (value !== 0 && (absolute >= 1e6 || absolute < 1e-3)) {
+            return value.toExponential(2);
+        }
+        return parseFloat(value.toPrecision(6)).toString();
+    }
+
+    function setupChart(container) {
+        var source = container.querySelector("script[type='application/json']");
+        var chart = JSON.parse(source.textContent);
+
+        var title = document.createElement("div");
+        title.className = "chart-title";
+        title.textContent = chart.title;
+        var area = document.createElement("div");
+        area.className = "chart-area";
+        var canvas = document.createElement("canvas");
+        var tooltip = document.createElement("div");
+        tooltip.className = "chart-tooltip";
+        var legend = document.createElement("div");
+        legend.className = "chart-legend";
+        area.appendChild(canvas);
+        area.appendChild(tooltip);
+        container.appendChild(title);
+        container.appendChild(area);
+        container.appendChild(legend);
+
+        var context = canvas.getContext("2d");
+        var width = 800;
+        var hidden = {};
+        var view = { x : chart.x_range.slice(), y : chart.y_range.slice() };
+        // the rectangle selected to zoom, in pixels
+        var selection = null;
+
+        function plotWidth() {
+            return width - MARGIN.left - MARGIN.right;
+        }
+
+        function plotHeight() {
+            return HEIGHT - MARGIN.top - MARGIN.bottom;
+        }
+
+        function toPixel(x, y) {
+            return [
+                MARGIN.left + (x - view.x[0]) / (view.x[1] - view.x[0]) * plotWidth(),
+                MARGIN.top + (view.y[1] - y) / (view.y[1] - view.y[0]) * plotHeight()
+            ];
+        }
+
+        function fromPixel(px, py) {
+            return [
+                view.x[0] + (px - MARGIN.left) / plotWidth() * (view.x[1] - view.x[0]),
+                view.y[1] - (py - MARGIN.top) / plotHeight() * (view.y[1] - view.y[0])
+            ];
+        }
+
+        function drawAxes() {
+            context.strokeStyle = "#ddd";
+            context.fillStyle = "#000";
+            context.font = "12px Arial, sans-serif";
+            context.lineWidth = 1;
+
+            context.textAlign = "center";
+            context.textBaseline = "top";
+            getTicks(view.x[0], view.x[1], 8).forEach(function (x) {
+                var px = Math.round(toPixel(x, 0)[0]) + 0.5;
+                context.beginPath();
+                context.moveTo(px, MARGIN.top);
+                context.lineTo(px, MARGIN.top + plotHeight());
+                context.stroke();
+                context.fillText(formatValue(x), px, MARGIN.top + plotHeight() + 6);
+            });
+            context.textAlign = "right";
+            context.textBaseline = "middle";
+            getTicks(view.y[0], view.y[1], 6).forEach(function (y) {
+                var py = Math.round(toPixel(0, y)[1]) + 0.5;
+                context.beginPath();
+                context.moveTo(MARGIN.left, py);
+                context.lineTo(MARGIN.left + plotWidth(), py);
+                context.stroke();
+                context.fillText(formatValue(y), MARGIN.left - 6, py);
+            });
+
+            context.strokeStyle = "#000";
+            context.strokeRect(MARGIN.left + 0.5, MARGIN.top + 0.5, plotWidth(), plotHeight());
+
+            context.textAlign = "center";
+            context.textBaseline = "bottom";
+            context.fillText(chart.x_label, MARGIN.left + plotWidth() / 2, HEIGHT - 4);
+            context.save();
+            context.translate(14, MARGIN.top + plotHeight() / 2);
+            context.rotate(-Math.PI / 2);
+            context.textBaseline = "middle";
+            context.fillText(chart.y_label, 0, 0);
+            context.restore();
+        }
+
+        function drawSeries() {
+            context.save();
+            context.beginPath();
+            context.rect(MARGIN.left, MARGIN.top, plotWidth(), plotHeight());
+            context.clip();
+            chart.series.forEach(function (serie) {
+                if (hidden[serie.legend]) {
+                    return;
+                }
+                context.strokeStyle = serie.color;
+                context.fillStyle = serie.color;
+                context.lineWidth = 1.5;
+                if (chart.kind === "Line") {
+                    context.beginPath();
+                    serie.points.forEach(function (point, index) {
+                        var pixel = toPixel(point[0], point[1]);
+                        if (index === 0) {
+                            context.moveTo(pixel[0], pixel[1]);
+                        } else {
+                            context.lineTo(pixel[0], pixel[1]);
+                        }
+                    });
+                    context.stroke();
+                } else {
+                    serie.points.forEach(function (point) {
+                        var pixel = toPixel(point[0], point[1]);
+                        context.beginPath();
+                        context.arc(pixel[0], pixel[1], 2.5, 0, 2 * Math.PI);
+                        context.fill();
+                    });
+                }
+            });
+            context.restore();
+        }
+
+        function draw() {
+            context.clearRect(0, 0, width, HEIGHT);
+            context.fillStyle = "#fff";
+            context.fillRect(0, 0, width, HEIGHT);
+            drawAxes();
+            drawSeries();
+            if (selection) {
+                context.fillStyle = "rgba(5, 168, 250, 0.15)";
+                context.strokeStyle = "rgba(5, 168, 250, 0.8)";
+                var x = Math.min(selection.start[0], selection.end[0]);
+                var y = Math.min(selection.start[1], selection.end[1]);
+                var w = Math.abs(selection.end[0] - selection.start[0]);
+                var h = Math.abs(selection.end[1] - selection.start[1]);
+                context.fillRect(x, y, w, h);
+                context.strokeRect(x, y, w, h);
+            }
+        }
+
+        function resize() {
+            var ratio = window.devicePixelRatio || 1;
+            width = Math.max(area.clientWidth, 300);
+            canvas.width = width * ratio;
+            canvas.height = HEIGHT * ratio;
+            canvas.style.width = width + "px";
+            canvas.style.height = HEIGHT + "px";
+            context.setTransform(ratio, 0, 0, ratio, 0, 0);
+            draw();
+        }
+
+        // get the nearest visible point to the pixel, null if it's too far
+        function getNearest(px, py) {
+            var nearest = null;
+            var best = HOVER_DISTANCE * HOVER_DISTANCE;
+            chart.series.forEach(function (serie) {
+                if (hidden[serie.legend]) {
+                    return;
+                }
+                serie.points.forEach(function (point) {
+                    var pixel = toPixel(point[0], point[1]);
+                    var distance = (pixel[0] - px) * (pixel[0] - px) + (pixel[1] - py) * (pixel[1] - py);
+                    if (distance <= best) {
+                        best = distance;
+                        nearest = { serie : serie, point : point, pixel : pixel };
+                    }
+                });
+            });
+            return nearest;
+        }
+
+        function getMouse(event) {
+            var bounds = canvas.getBoundingClientRect();
+            return [event.clientX - bounds.left, event.clientY - bounds.top];
+        }
+
+        function isInPlot(mouse) {
+            return mouse[0] >= MARGIN.left && mouse[0] <= MARGIN.left + plotWidth()
+                && mouse[1] >= MARGIN.top && mouse[1] <= MARGIN.top + plotHeight();
+        }
+
+        function showTooltip(mouse) {
+            var nearest = isInPlot(mouse) ? getNearest(mouse[0], mouse[1]) : null;
+            if (!nearest) {
+                tooltip.style.display = "none";
+                return;
+            }
+            tooltip.textContent = "";
+            var name = document.createElement("b");
+            name.textContent = nearest.serie.legend;
+            name.style.color = nearest.serie.color;
+            tooltip.appendChild(name);
+            tooltip.appendChild(document.createElement("br"));
+            tooltip.appendChild(document.createTextNode(chart.x_label + " : " + formatValue(nearest.point[0])));
+            tooltip.appendChild(document.createElement("br"));
+            tooltip.appendChild(document.createTextNode(chart.y_label + " : " + formatValue(nearest.point[1])));
+            tooltip.style.display = "block";
+            tooltip.style.left = (nearest.pixel[0] + 10) + "px";
+            tooltip.style.top = (nearest.pixel[1] + 10) + "px";
+        }
+
+        canvas.addEventListener("mousedown", function (event) {
+            var mouse = getMouse(event);
+            if (isInPlot(mouse)) {
+                selection = { start : mouse, end : mouse };
+            }
+        });
+        canvas.addEventListener("mousemove", function (event) {
+            var mouse = getMouse(event);
+            if (selection) {
+                selection.end = [
+                    Math.min(Math.max(mouse[0], MARGIN.left), MARGIN.left + plotWidth()),
+                    Math.min(Math.max(mouse[1], MARGIN.top), MARGIN.top + plotHeight())
+                ];
+                tooltip.style.display = "none";
+                draw();
+            } else {
+                showTooltip(mouse);
+            }
+        });
+        window.addEventListener("mouseup", function () {
+            if (!selection) {
+                return;
+            }
+            var start = selection.start;
+            var end = selection.end;
+            selection = null;
+            if (Math.abs(end[0] - start[0]) >= MIN_ZOOM_SIZE && Math.abs(end[1] - start[1]) >= MIN_ZOOM_SIZE) {
+                var first = fromPixel(Math.min(start[0], end[0]), Math.max(start[1], end[1]));
+                var last = fromPixel(Math.max(start[0], end[0]), Math.min(start[1], end[1]));
+                view = { x : [first[0], last[0]], y : [first[1], last[1]] };
+            }
+            draw();
+        });
+        canvas.addEventListener("dblclick", function () {
+            view = { x : chart.x_range.slice(), y : chart.y_range.slice() };
+            draw();
+        });
+        canvas.addEventListener("mouseleave", function () {
+            tooltip.style.display = "none";
+        });
+
+        chart.series.forEach(function (serie) {
+            var item = document.createElement("span");
+            item.className = "chart-legend-item";
+            var swatch = document.createElement("span");
+            swatch.className = "chart-legend-color";
+            swatch.style.background = serie.color;
+            item.appendChild(swatch);
+            item.appendChild(document.createTextNode(serie.legend));
+            item.addEventListener("click", function () {
+                hidden[serie.legend] = !hidden[serie.legend];
+                item.classList.toggle("chart-legend-hidden", hidden[serie.legend]);
+                draw();
+            });
+            legend.appendChild(item);
+        });
+
+        window.addEventListener("resize", resize);
+        resize();
+    }
+
+    Array.prototype.forEach.call(document.querySelectorAll("div.chart"), setupChart);
+})();
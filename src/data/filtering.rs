@@ -93,6 +93,10 @@ where
     {
         self.filters.iter().all(|f| f.apply(sample))
     }
+
+    pub fn get_filters(&self) -> &Vec<Filter<Key>> {
+        &self.filters
+    }
 }
 
 
@@ -121,6 +125,46 @@ where
 // -----------------------------------------------------------------------------
 
 
+/// Define a declarative condition of a filter, which can be translated to another language (e.g. a SQL WHERE clause)
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCondition {
+    /// min <= value <= max, a bound at None is not checked
+    NumberRange(Option<f32>, Option<f32>),
+    /// the value is one of the given numbers
+    NumberIn(Vec<f32>),
+    /// the value is one of the given strings
+    StrIn(Vec<String>),
+}
+
+impl FilterCondition {
+    pub fn apply_number(&self, value : f32) -> bool {
+        match self {
+            FilterCondition::NumberRange(min, max) => {
+                let above_min = match min {
+                    Some(min) => value >= *min,
+                    None => true,
+                };
+                let below_max = match max {
+                    Some(max) => value <= *max,
+                    None => true,
+                };
+                above_min && below_max
+            },
+            FilterCondition::NumberIn(values) => values.contains(&value),
+            FilterCondition::StrIn(_) => panic!("Cannot apply a string condition to a number"),
+        }
+    }
+
+    pub fn apply_str(&self, value : &str) -> bool {
+        match self {
+            FilterCondition::StrIn(values) => values.iter().any(|v| v == value),
+            _ => panic!("Cannot apply a numeric condition to a string"),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
 /// Define a filter for a particular key
 pub struct Filter<Key> 
 where 
@@ -129,6 +173,8 @@ where
     key : Key,
     filter_number : Option<Box<dyn Fn(f32) -> bool>>,
     filter_str : Option<Box<dyn Fn(&str) -> bool>>,
+    /// the conditions equivalent to the filter (all must be true), None if the filter use a custom function
+    conditions : Option<Vec<FilterCondition>>,
 }
 
 impl<Key> Debug for Filter<Key>
@@ -158,6 +204,7 @@ where
             key,
             filter_number : Some(Box::new(|_| true)),
             filter_str : None,
+            conditions : Some(Vec::new()),
         }
     }
 
@@ -170,6 +217,7 @@ where
             key,
             filter_number : None,
             filter_str : Some(Box::new(|_| true)),
+            conditions : Some(Vec::new()),
         }
     }
 
@@ -184,6 +232,7 @@ where
             key,
             filter_number : Some(Box::new(filter_fn)),
             filter_str : None,
+            conditions : None,
         }
     }
 
@@ -198,6 +247,49 @@ where
             key,
             filter_number : None,
             filter_str : Some(Box::new(filter_fn)),
+            conditions : None,
+        }
+    }
+
+    /// Create a new declarative filter for number, keeping the values between min and max (included)
+    pub fn new_number_range(key : Key, min : Option<f32>, max : Option<f32>) -> Self {
+        Self::new_number_condition(key, FilterCondition::NumberRange(min, max))
+    }
+
+    /// Create a new declarative filter for number, keeping the values in the given list
+    pub fn new_number_in(key : Key, values : Vec<f32>) -> Self {
+        Self::new_number_condition(key, FilterCondition::NumberIn(values))
+    }
+
+    /// Create a new declarative filter for str, keeping the values in the given list
+    pub fn new_str_in(key : Key, values : Vec<String>) -> Self {
+        if !key.is_string() {
+            panic!("Cannot create a string filter with a numeric key");
+        }
+        let condition = FilterCondition::StrIn(values);
+        Self {
+            key,
+            filter_number : None,
+            filter_str : Some({
+                let condition = condition.clone();
+                Box::new(move |value| condition.apply_str(value))
+            }),
+            conditions : Some(vec![condition]),
+        }
+    }
+
+    fn new_number_condition(key : Key, condition : FilterCondition) -> Self {
+        if !key.is_numeric() {
+            panic!("Cannot create a numeric filter with a string key");
+        }
+        Self {
+            key,
+            filter_number : Some({
+                let condition = condition.clone();
+                Box::new(move |value| condition.apply_number(value))
+            }),
+            filter_str : None,
+            conditions : Some(vec![condition]),
         }
     }
 
@@ -213,6 +305,22 @@ where
         self.filter_str.as_ref().unwrap()
     }
 
+    /// Get the declarative conditions of the filter (all must be true), None if the filter use a custom function
+    pub fn get_conditions(&self) -> Option<&Vec<FilterCondition>> {
+        self.conditions.as_ref()
+    }
+
+    /// combine the conditions of two filters, the result is declarative only if both are
+    fn combine_conditions(c1 : Option<Vec<FilterCondition>>, c2 : Option<Vec<FilterCondition>>) -> Option<Vec<FilterCondition>> {
+        match (c1, c2) {
+            (Some(mut c1), Some(c2)) => {
+                c1.extend(c2);
+                Some(c1)
+            },
+            _ => None,
+        }
+    }
+
     pub fn combine_ref(&mut self, other : Self) -> &Self {
         if self.key != other.key {
             panic!("Cannot combine filters with different keys");
@@ -234,6 +342,8 @@ where
             (None, None) => None,
         };
 
+        self.conditions = Self::combine_conditions(self.conditions.take(), other.conditions);
+
         self
     }
 
@@ -259,6 +369,8 @@ where
             (None, None) => None,
         };
 
+        self.conditions = Self::combine_conditions(self.conditions, other.conditions);
+
         self
     }
 
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 
+use crate::stat::correlation::{CorrelationMatrix, CorrelationMethod};
 use crate::stat::stats_serie::StatsSerie;
 
 use super::filtered_serie::{FilteredSerie, FilteredSerieIterator};
@@ -12,6 +13,9 @@ use super::sample::Sample;
 
 type Point = (f32, f32);
 
+/// the values indexed by category, then by legend
+pub type ValuesByCategory = HashMap<String, HashMap<String, Vec<f32>>>;
+
 
 /// Trait for a plottable serie
 pub trait Plottable<S, K>
@@ -96,6 +100,75 @@ where
             (sort_key, stats_map)
         }).collect()
     }
+
+    /// Collect the values of the y key per category and per legend, on the filtered samples
+    /// The category is the value of category_key (string or numeric), the legend is "All" if legend_key is None
+    /// If the y_key is None, each sample count for 1 to allow aggregation
+    /// return category -> legend -> values
+    fn collect_by_category(
+        &self,
+        category_key : K,
+        y_key : Option<K>,
+        legend_key : Option<K>,
+        filters : Option<&Filters<K>>
+    ) -> ValuesByCategory {
+        if let Some(legend_key) = legend_key.as_ref() {
+            if legend_key.is_numeric() {
+                panic!("legend_key must be a string key");
+            }
+        }
+        if y_key.is_some_and(|k| !k.is_numeric()) {
+            panic!("y_key must be a numeric key");
+        }
+
+        let mut values_by_category = ValuesByCategory::new();
+        for sample in FilteredSerie::new(self.into_iter(), filters).into_iter() {
+            let category = if category_key.is_numeric() {
+                sample.get_numeric_value(&category_key).to_string()
+            } else {
+                sample.get_string_value(&category_key)
+            };
+            let legend = match legend_key.as_ref() {
+                Some(legend_key) => sample.get_string_value(legend_key),
+                None => "All".to_string(),
+            };
+            let value = match y_key.as_ref() {
+                Some(y_key) => sample.get_numeric_value(y_key),
+                None => 1.0,
+            };
+            values_by_category.entry(category).or_default().entry(legend).or_default().push(value);
+        }
+        values_by_category
+    }
+
+    /// Collect the unique values of the key (string or numeric), on the filtered samples
+    fn collect_unique_values(&self, key : &K, filters : Option<&Filters<K>>) -> HashSet<String> {
+        FilteredSerie::new(self.into_iter(), filters).into_iter()
+            .map(|sample| if key.is_numeric() {
+                sample.get_numeric_value(key).to_string()
+            } else {
+                sample.get_string_value(key)
+            })
+            .collect()
+    }
+
+    /// Compute the correlation between each pair of the numeric keys, on the filtered samples
+    /// The labels of the matrix are the display names of the keys
+    fn collect_correlation_matrix(&self, keys : &[K], method : CorrelationMethod, filters : Option<&Filters<K>>) -> CorrelationMatrix {
+        if let Some(key) = keys.iter().find(|k| !k.is_numeric()) {
+            panic!("correlation key {} must be numeric", key.get_display_name());
+        }
+
+        let mut series = vec![Vec::new(); keys.len()];
+        for sample in FilteredSerie::new(self.into_iter(), filters).into_iter() {
+            for (serie, key) in series.iter_mut().zip(keys.iter()) {
+                serie.push(sample.get_numeric_value(key));
+            }
+        }
+
+        let labels = keys.iter().map(|k| k.get_display_name()).collect();
+        CorrelationMatrix::new(labels, &series, method)
+    }
 }
 
 
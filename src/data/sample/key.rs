@@ -1,10 +1,59 @@
 use std::fmt::Display;
 
+use crate::plotter::utils::{format_bytes, format_duration, format_number};
+
+
+/// the unit of a numeric key, to format its values on the axes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Unit {
+    /// the values are formatted as plain numbers
+    #[default]
+    None,
+    /// a duration, formatted as "1h 2m 3s"
+    Seconds,
+    Milliseconds,
+    /// a size, formatted as "1.5 KiB"
+    Bytes,
+    Kilobytes,
+    Megabytes,
+}
+
+impl Unit {
+    /// format a value of this unit
+    pub fn format(&self, value : f64) -> String {
+        if value < 0.0 && *self != Unit::None {
+            return format!("-{}", self.format(-value));
+        }
+        match self {
+            Unit::None => format_number(value),
+            Unit::Seconds => format_seconds(value),
+            Unit::Milliseconds => format_seconds(value / 1000.0),
+            Unit::Bytes => format_bytes(value),
+            Unit::Kilobytes => format_bytes(value * 1024.0),
+            Unit::Megabytes => format_bytes(value * 1024.0 * 1024.0),
+        }
+    }
+}
+
+/// format a duration, in milliseconds below one second (e.g. "2.5ms" instead of "0.0025s")
+fn format_seconds(seconds : f64) -> String {
+    if seconds > 0.0 && seconds < 1.0 {
+        return format!("{}ms", format_number(seconds * 1000.0));
+    }
+    format_duration(seconds)
+}
+
+
 /// Define a trait for a key of a serie
 pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
     /// get the display name of the serie
     fn get_display_name(&self) -> String;
 
+    /// get the unit of the values of the serie, used to format the axes
+    fn get_unit(&self) -> Unit {
+        Unit::None
+    }
+
     /// if the serie is numeric
     fn is_numeric(&self) -> bool;
 
@@ -13,20 +62,27 @@ pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
 
     /// if the serie is an object
     fn is_object(&self) -> bool;
+
+    /// get all the keys (in the order of declaration)
+    /// NOTE : implemented by generate_plot_key, empty by default (the keys can't be stored with the SqliteWriter)
+    fn get_all_keys() -> Vec<Self> {
+        Vec::new()
+    }
 }
 
 
 /// Define a fully initialized key for a serie
+/// A numeric key can have a unit (a variant of Unit) after its type, to format its values on the axes
 /// Warn : must be called only once by file
 /// Need the dependances :
 /// ```
 /// use serde_derive::{Deserialize, Serialize};
 /// use std::fmt::{Display, Formatter};
 /// use plot_helper::generate_plot_key;
-/// use plot_helper::data::sample::key::SerieKey;
+/// use plot_helper::data::sample::key::{SerieKey, Unit};
 /// generate_plot_key!(
 ///     MultiLineQueryKey[
-///         ParsingTime { "parsing time (s)", Numeric},
+///         ParsingTime { "parsing time (s)", Numeric, Seconds },
 ///         ParsingMaxMemory { "parsing max memory (Mb)", Numeric }
 ///     ],
 ///     SingleLineQueryKey[
@@ -36,12 +92,21 @@ pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
 /// );
 /// assert_eq!(MultiLineQueryKey::ParsingTime.get_display_name(), "parsing time (s)");
 /// assert_eq!(MultiLineQueryKey::ParsingTime.is_numeric(), true);
+/// assert_eq!(MultiLineQueryKey::ParsingTime.get_unit(), Unit::Seconds);
+/// assert_eq!(SingleLineQueryKey::ParsingTime.get_unit(), Unit::None);
+/// assert_eq!(SingleLineQueryKey::get_all_keys(), vec![SingleLineQueryKey::ParsingTime, SingleLineQueryKey::File]);
 /// ```
 #[macro_export]
 macro_rules! generate_plot_key {
+    (@unit) => {
+        $crate::data::sample::key::Unit::None
+    };
+    (@unit $unit:ident) => {
+        $crate::data::sample::key::Unit::$unit
+    };
     ($($key_name:ident [ 
         $($variant:ident {
-             $description:literal, $key_type:ident
+             $description:literal, $key_type:ident $(, $unit:ident)?
         }),* 
     ]),+) => {
         /// Define the type of the key
@@ -69,6 +134,11 @@ macro_rules! generate_plot_key {
                         $($key_name::$variant => $description.to_string()),*
                     }
                 }
+                fn get_unit(&self) -> $crate::data::sample::key::Unit {
+                    match self {
+                        $($key_name::$variant => $crate::generate_plot_key!(@unit $($unit)?)),*
+                    }
+                }
                 fn is_numeric(&self) -> bool {
                     match self {
                         $($key_name::$variant => KeyType::$key_type == KeyType::Numeric),*
@@ -84,6 +154,9 @@ macro_rules! generate_plot_key {
                         $($key_name::$variant => KeyType::$key_type == KeyType::Object),*
                     }
                 }
+                fn get_all_keys() -> Vec<Self> {
+                    vec![$($key_name::$variant),*]
+                }
             }
 
             impl Display for $key_name {
@@ -115,7 +188,7 @@ mod tests {
     #[test]
     fn test_generate_plot_key() {
         generate_plot_key!(TestKey[
-            A { "A", Numeric },
+            A { "A", Numeric, Milliseconds },
             B { "B", String },
             C { "C", Object }
         ]);
@@ -125,6 +198,7 @@ mod tests {
         assert_eq!(key.is_numeric(), true);
         assert_eq!(key.is_string(), false);
         assert_eq!(key.is_object(), false);
+        assert_eq!(key.get_unit(), Unit::Milliseconds);
 
         let key = TestKey::B;
         assert_eq!(key.get_display_name(), "B");
@@ -138,6 +212,22 @@ mod tests {
         assert_eq!(key.is_numeric(), false);
         assert_eq!(key.is_string(), false);
         assert_eq!(key.is_object(), true);
+
+        assert_eq!(TestKey::get_all_keys(), vec![TestKey::A, TestKey::B, TestKey::C]);
+        assert_eq!(TestKey::C.get_unit(), Unit::None);
+    }
+
+    #[test]
+    fn test_unit_format() {
+        assert_eq!(Unit::None.format(1500.0), "1500");
+        assert_eq!(Unit::Seconds.format(90.0), "1m 30s");
+        assert_eq!(Unit::Milliseconds.format(2500.0), "2.5s");
+        assert_eq!(Unit::Bytes.format(512.0), "512 B");
+        assert_eq!(Unit::Kilobytes.format(1536.0), "1.5 MiB");
+        assert_eq!(Unit::Seconds.format(-2.0), "-2s");
+        assert_eq!(Unit::Milliseconds.format(2.0), "2ms");
+        assert_eq!(Unit::Seconds.format(0.25), "250ms");
+        assert_eq!(Unit::Seconds.format(0.0), "0s");
     }
 
 
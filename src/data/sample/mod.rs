@@ -5,6 +5,8 @@ pub mod file_sample;
 
 #[cfg(feature = "sqlite")]
 pub mod sqlite_sample;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_stored_sample;
 
 /// Define a sample linked to a key
 pub trait Sample<Key>
@@ -1,4 +1,4 @@
-use sqlite::Statement;
+use sqlite::{Statement, Value};
 
 use super::key::SerieKey;
 use super::Sample;
@@ -6,24 +6,71 @@ use super::Sample;
 
 
 
+/// Define a parameter of a query (`?`, `?NNN`, `:name`, `@name` or `$name` in the query)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SqliteParameter {
+    /// the index of the parameter (start at 1)
+    Index(usize),
+    /// the name of the parameter, with its prefix (e.g. ":name")
+    Name(String),
+}
+
+impl From<usize> for SqliteParameter {
+    fn from(index : usize) -> Self {
+        SqliteParameter::Index(index)
+    }
+}
 
+impl From<&str> for SqliteParameter {
+    fn from(name : &str) -> Self {
+        SqliteParameter::Name(name.to_string())
+    }
+}
 
+impl SqliteParameter {
+    /// Bind the value to the parameter in the statement
+    pub fn bind(&self, stmt : &mut Statement, value : &Value) -> Result<(), sqlite::Error> {
+        match self {
+            SqliteParameter::Index(index) => stmt.bind((*index, value)),
+            SqliteParameter::Name(name) => stmt.bind((name.as_str(), value)),
+        }
+    }
+}
+
+
+/// Quote an identifier (table or column name) to use it in a query
+pub(crate) fn quote_sqlite_identifier(identifier : &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
 
 
 /// Define a sample linked to a key from a sqlite row
 pub trait SqliteSample<K, Sub> : Sample<K>
-where 
+where
     Self : Clone + Sized + Send + Sync,
     K : SerieKey,
 {
-    /// Load samples from a satement. The statement is garantea to be without error, and the information 
+    /// Load samples from a satement. The statement is garantea to be without error, and the information
     /// can be extracted from the statement like this:
     /// ```
     /// let value = row.read::<i64, _>(0);
     /// ```
     fn new_from_row(row : &Statement) -> Result<Vec<Self>, Box<dyn std::error::Error>>;
-    
+
     /// Get the select query to get the samples from the database
+    /// NOTE : prefer to use parameters for the values, see get_sqlite_query_parameters
     /// - args: the substitution to apply to the query
     fn get_sqlite_select_query(substitution : &Sub) -> String;
+
+    /// Get the values to bind to the parameters of the select query
+    /// - args: the substitution used to build the query
+    fn get_sqlite_query_parameters(_substitution : &Sub) -> Vec<(SqliteParameter, Value)> {
+        Vec::new()
+    }
+
+    /// Get the column of the select query corresponding to the key, None if the key isn't a column.
+    /// The declarative filters on a key with a column are applied directly in the query.
+    fn get_sqlite_column_name(_key : &K) -> Option<String> {
+        None
+    }
 }
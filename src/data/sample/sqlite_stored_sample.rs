@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use sqlite::{Statement, Value};
+
+use crate::stat::stats_serie::MetricName;
+
+use super::key::SerieKey;
+use super::sqlite_sample::{quote_sqlite_identifier, SqliteParameter, SqliteSample};
+use super::Sample;
+
+
+/// the column storing the metric of the aggregated rows
+pub const SQLITE_METRIC_COLUMN : &str = "metric";
+/// the column storing the id of the run of the rows (NULL if the writer has no run id)
+pub const SQLITE_RUN_ID_COLUMN : &str = "run_id";
+
+/// Get the column storing the value of the key
+pub fn get_sqlite_key_column_name<K : SerieKey>(key : &K) -> String {
+    key.get_display_name()
+}
+
+/// Get the keys which can be stored in a table (the numeric and string ones)
+/// return an error if there is none (e.g. the key doesn't implement get_all_keys)
+pub fn get_sqlite_storable_keys<K : SerieKey>() -> Result<Vec<K>, Box<dyn std::error::Error>> {
+    let keys = K::get_all_keys().into_iter().filter(|k| k.is_numeric() || k.is_string()).collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Err("No numeric or string key to store, the key must implement get_all_keys".into());
+    }
+    Ok(keys)
+}
+
+
+/// Define the table to read the stored samples from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SqliteStoredTable {
+    table : String,
+    metric : Option<MetricName>,
+    /// if Some, only the rows of this run are read
+    run_id : Option<String>,
+}
+
+impl SqliteStoredTable {
+    /// Read the samples of a table written with insert_samples
+    pub fn new_samples(table : &str) -> Self {
+        SqliteStoredTable {
+            table : table.to_string(),
+            metric : None,
+            run_id : None,
+        }
+    }
+
+    /// Read the aggregated samples of a table written with insert_stats, for only one metric
+    pub fn new_stats(table : &str, metric : MetricName) -> Self {
+        SqliteStoredTable {
+            table : table.to_string(),
+            metric : Some(metric),
+            run_id : None,
+        }
+    }
+
+    /// Read only the rows written by a SqliteWriter with this run id
+    pub fn with_run_id(mut self, run_id : &str) -> Self {
+        self.run_id = Some(run_id.to_string());
+        self
+    }
+
+    pub fn get_table(&self) -> &String {
+        &self.table
+    }
+
+    pub fn get_metric(&self) -> Option<MetricName> {
+        self.metric
+    }
+
+    pub fn get_run_id(&self) -> Option<&String> {
+        self.run_id.as_ref()
+    }
+}
+
+
+/// A sample read back from a table written by the SqliteWriter, with a value for each storable key.
+/// A missing value (NULL) is read as NaN for a numeric key and as an empty string for a string key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqliteStoredSample<K>
+where
+    K : SerieKey
+{
+    numeric_values : HashMap<K, f32>,
+    string_values : HashMap<K, String>,
+    run_id : Option<String>,
+}
+
+impl<K> SqliteStoredSample<K>
+where
+    K : SerieKey
+{
+    /// get the id of the run which wrote the sample, None if the writer had no run id
+    pub fn get_run_id(&self) -> Option<&String> {
+        self.run_id.as_ref()
+    }
+}
+
+impl<K> Sample<K> for SqliteStoredSample<K>
+where
+    K : SerieKey
+{
+    fn get_numeric_value(&self, key : &K) -> f32 {
+        *self.numeric_values.get(key).unwrap_or_else(|| panic!("The key {} isn't a stored numeric key", key))
+    }
+
+    fn get_string_value(&self, key : &K) -> String {
+        self.string_values.get(key).unwrap_or_else(|| panic!("The key {} isn't a stored string key", key)).clone()
+    }
+}
+
+impl<K> SqliteSample<K, SqliteStoredTable> for SqliteStoredSample<K>
+where
+    K : SerieKey
+{
+    fn new_from_row(row : &Statement) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let mut numeric_values = HashMap::new();
+        let mut string_values = HashMap::new();
+        for key in get_sqlite_storable_keys::<K>()? {
+            let column = get_sqlite_key_column_name(&key);
+            if key.is_numeric() {
+                let value = row.read::<Option<f64>, _>(column.as_str())?;
+                numeric_values.insert(key, value.map(|v| v as f32).unwrap_or(f32::NAN));
+            } else {
+                let value = row.read::<Option<String>, _>(column.as_str())?;
+                string_values.insert(key, value.unwrap_or_default());
+            }
+        }
+
+        Ok(vec![SqliteStoredSample {
+            numeric_values,
+            string_values,
+            run_id : row.read::<Option<String>, _>(SQLITE_RUN_ID_COLUMN)?,
+        }])
+    }
+
+    fn get_sqlite_select_query(substitution : &SqliteStoredTable) -> String {
+        let table = quote_sqlite_identifier(&substitution.table);
+        let mut conditions = Vec::new();
+        if substitution.metric.is_some() {
+            conditions.push(format!("{} = :metric", quote_sqlite_identifier(SQLITE_METRIC_COLUMN)));
+        }
+        if substitution.run_id.is_some() {
+            conditions.push(format!("{} = :run_id", quote_sqlite_identifier(SQLITE_RUN_ID_COLUMN)));
+        }
+        if conditions.is_empty() {
+            return format!("SELECT * FROM {}", table);
+        }
+        format!("SELECT * FROM {} WHERE {}", table, conditions.join(" AND "))
+    }
+
+    fn get_sqlite_query_parameters(substitution : &SqliteStoredTable) -> Vec<(SqliteParameter, Value)> {
+        let mut parameters = Vec::new();
+        if let Some(metric) = substitution.metric {
+            parameters.push((":metric".into(), Value::String(metric.get_name())));
+        }
+        if let Some(run_id) = substitution.run_id.as_ref() {
+            parameters.push((":run_id".into(), Value::String(run_id.clone())));
+        }
+        parameters
+    }
+
+    fn get_sqlite_column_name(key : &K) -> Option<String> {
+        if key.is_numeric() || key.is_string() {
+            Some(get_sqlite_key_column_name(key))
+        } else {
+            None
+        }
+    }
+}
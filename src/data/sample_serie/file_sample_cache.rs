@@ -0,0 +1,366 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+
+
+type LoadResult<S> = Result<Vec<S>, Box<dyn std::error::Error>>;
+type SerializeFn<S> = fn(&FileCacheKey, &[S]) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+type DeserializeFn<S> = fn(&[u8]) -> Result<(FileCacheKey, Vec<S>), Box<dyn std::error::Error>>;
+
+/// Identify a version of a file : the cache entry is invalidated when the file is modified
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileCacheKey {
+    path : String,
+    modified_nanos : u128,
+    size : u64,
+}
+
+impl FileCacheKey {
+    /// Create the key of the file from its metadata (path, modification time and size)
+    pub fn new_from_path(path : &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let metadata = fs::metadata(path)?;
+        let modified_nanos = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos();
+        Ok(FileCacheKey {
+            path : path.to_string(),
+            modified_nanos,
+            size : metadata.len(),
+        })
+    }
+
+    /// get the name of the file storing this key in the disk cache
+    fn get_disk_file_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        format!("{:016x}.bin", hasher.finish())
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// A least recently used cache of the parsed files, kept in memory
+struct MemoryLru<S> {
+    capacity : usize,
+    entries : HashMap<FileCacheKey, Arc<Vec<S>>>,
+    /// the keys from the least to the most recently used
+    order : VecDeque<FileCacheKey>,
+}
+
+impl<S> MemoryLru<S> {
+    fn new(capacity : usize) -> Self {
+        MemoryLru {
+            capacity,
+            entries : HashMap::new(),
+            order : VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, key : &FileCacheKey) -> Option<Arc<Vec<S>>> {
+        let samples = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(samples)
+    }
+
+    fn insert(&mut self, key : FileCacheKey, samples : Arc<Vec<S>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        // an older version of the same file is useless now
+        self.entries.retain(|k, _| k.path != key.path);
+        self.order.retain(|k| k.path != key.path);
+
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => { self.entries.remove(&oldest); },
+                None => break,
+            }
+        }
+
+        self.order.push_back(key.clone());
+        self.entries.insert(key, samples);
+    }
+
+    /// move the key at the end of the order (most recently used)
+    fn touch(&mut self, key : &FileCacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(position).unwrap();
+            self.order.push_back(key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// An entry of the disk cache, the key is stored to detect the collisions of the file names
+#[derive(Deserialize)]
+struct DiskEntry<S> {
+    key : FileCacheKey,
+    samples : Vec<S>,
+}
+
+/// The borrowed version of DiskEntry, with the same binary representation
+#[derive(Serialize)]
+struct DiskEntryRef<'a, S> {
+    key : &'a FileCacheKey,
+    samples : &'a [S],
+}
+
+/// A binary cache of the parsed files, stored in a directory
+/// NOTE : the (de)serialization functions are monomorphized at creation to avoid requiring serde on every sample
+struct DiskCache<S> {
+    dir : PathBuf,
+    serialize : SerializeFn<S>,
+    deserialize : DeserializeFn<S>,
+}
+
+impl<S> DiskCache<S>
+where
+    S : Serialize + DeserializeOwned
+{
+    fn new(dir : &Path) -> Self {
+        DiskCache {
+            dir : dir.to_path_buf(),
+            serialize : |key, samples| {
+                Ok(bincode::serialize(&DiskEntryRef { key, samples })?)
+            },
+            deserialize : |bytes| {
+                let entry : DiskEntry<S> = bincode::deserialize(bytes)?;
+                Ok((entry.key, entry.samples))
+            },
+        }
+    }
+}
+
+impl<S> DiskCache<S> {
+    /// load the samples of the key, return None if the entry is missing or invalid
+    fn load(&self, key : &FileCacheKey) -> Option<Vec<S>> {
+        let bytes = fs::read(self.dir.join(key.get_disk_file_name())).ok()?;
+        match (self.deserialize)(&bytes) {
+            Ok((stored_key, samples)) if &stored_key == key => Some(samples),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("Invalid cache entry for the file {} : {}", key.path, e);
+                None
+            },
+        }
+    }
+
+    /// store the samples of the key, a failure only prevent the next runs to use the cache
+    fn store(&self, key : &FileCacheKey, samples : &[S]) {
+        let result = (self.serialize)(key, samples)
+            .and_then(|bytes| Ok(fs::write(self.dir.join(key.get_disk_file_name()), bytes)?));
+        if let Err(e) = result {
+            log::warn!("Cannot write the cache entry for the file {} : {}", key.path, e);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// A cache of the samples parsed from files, used by FileSampleSerie to avoid parsing the same file multiple times.
+/// The samples are kept in a memory LRU (capacity in number of files), and optionally in a binary cache on disk
+/// to be reused between runs. An entry is invalidated when the path, the modification time or the size of the file change.
+pub struct FileSampleCache<S> {
+    memory : Mutex<MemoryLru<S>>,
+    disk : Option<DiskCache<S>>,
+}
+
+impl<S> Debug for FileSampleCache<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nb_entries = self.memory.lock().map(|m| m.entries.len()).unwrap_or(0);
+        let disk_dir = self.disk.as_ref().map(|d| d.dir.to_string_lossy().to_string());
+        write!(f, "FileSampleCache({} files in memory, disk : {:?})", nb_entries, disk_dir)
+    }
+}
+
+impl<S> FileSampleCache<S>
+where
+    S : Clone
+{
+    /// Create a new cache in memory only
+    /// - args: memory_capacity: the maximum number of files kept in memory
+    pub fn new(memory_capacity : usize) -> Self {
+        FileSampleCache {
+            memory : Mutex::new(MemoryLru::new(memory_capacity)),
+            disk : None,
+        }
+    }
+
+    /// Get the samples of the file from the cache, or load them with the loader and update the cache
+    pub fn get_or_load<F>(&self, path : &str, loader : F) -> LoadResult<S>
+    where
+        F : FnOnce(&str) -> LoadResult<S>
+    {
+        let key = match FileCacheKey::new_from_path(path) {
+            Ok(key) => key,
+            Err(_) => return loader(path), // without metadata, the file can't be cached
+        };
+
+        if let Some(samples) = self.memory.lock().unwrap().get(&key) {
+            return Ok(samples.as_ref().clone());
+        }
+
+        let samples = match self.disk.as_ref().and_then(|disk| disk.load(&key)) {
+            Some(samples) => samples,
+            None => {
+                let samples = loader(path)?;
+                if let Some(disk) = self.disk.as_ref() {
+                    disk.store(&key, &samples);
+                }
+                samples
+            },
+        };
+
+        self.memory.lock().unwrap().insert(key, Arc::new(samples.clone()));
+        Ok(samples)
+    }
+
+    /// Get the number of files currently kept in memory
+    pub fn nb_files_in_memory(&self) -> usize {
+        self.memory.lock().unwrap().entries.len()
+    }
+
+    /// Remove all the files kept in memory (the disk cache is kept)
+    pub fn clear_memory(&self) {
+        self.memory.lock().unwrap().clear();
+    }
+}
+
+impl<S> FileSampleCache<S>
+where
+    S : Clone + Serialize + DeserializeOwned
+{
+    /// Create a new cache in memory, backed by a binary cache on disk
+    /// - args: memory_capacity: the maximum number of files kept in memory
+    /// - args: dir_path: the directory of the disk cache (created if it doesn't exist)
+    pub fn new_with_disk_cache(memory_capacity : usize, dir_path : &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = Path::new(dir_path);
+        fs::create_dir_all(dir)?;
+        Ok(FileSampleCache {
+            memory : Mutex::new(MemoryLru::new(memory_capacity)),
+            disk : Some(DiskCache::new(dir)),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn get_test_dir(name : &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("plot_helper_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(dir : &Path, name : &str, content : &str) -> String {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn parse(path : &str) -> LoadResult<u32> {
+        Ok(fs::read_to_string(path)?.split(',').map(|v| v.trim().parse::<u32>().unwrap()).collect())
+    }
+
+    #[test]
+    fn test_memory_cache_skip_parsing() {
+        let dir = get_test_dir("memory");
+        let path = write_file(&dir, "a.csv", "1,2,3");
+        let cache = FileSampleCache::new(4);
+        let nb_loads = Cell::new(0);
+
+        for _ in 0..3 {
+            let samples = cache.get_or_load(&path, |p| { nb_loads.set(nb_loads.get() + 1); parse(p) }).unwrap();
+            assert_eq!(samples, vec![1, 2, 3]);
+        }
+        assert_eq!(nb_loads.get(), 1);
+        assert_eq!(cache.nb_files_in_memory(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory_cache_evict_least_recently_used() {
+        let dir = get_test_dir("lru");
+        let paths = ["a", "b", "c"].iter().map(|n| write_file(&dir, n, "1")).collect::<Vec<_>>();
+        let cache = FileSampleCache::new(2);
+        let nb_loads = Cell::new(0);
+        let load = |path : &str| cache.get_or_load(path, |p| { nb_loads.set(nb_loads.get() + 1); parse(p) }).unwrap();
+
+        load(&paths[0]);
+        load(&paths[1]);
+        load(&paths[0]); // a is now the most recently used
+        load(&paths[2]); // evict b
+        assert_eq!(nb_loads.get(), 3);
+        load(&paths[0]);
+        assert_eq!(nb_loads.get(), 3);
+        load(&paths[1]);
+        assert_eq!(nb_loads.get(), 4);
+        assert_eq!(cache.nb_files_in_memory(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_invalidated_when_file_change() {
+        let dir = get_test_dir("invalidate");
+        let path = write_file(&dir, "a.csv", "1,2");
+        let cache = FileSampleCache::new(4);
+
+        assert_eq!(cache.get_or_load(&path, parse).unwrap(), vec![1, 2]);
+        write_file(&dir, "a.csv", "1,2,3"); // the size change
+        assert_eq!(cache.get_or_load(&path, parse).unwrap(), vec![1, 2, 3]);
+        assert_eq!(cache.nb_files_in_memory(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_cache_reused_between_caches() {
+        let dir = get_test_dir("disk");
+        let path = write_file(&dir, "a.csv", "4,5,6");
+        let cache_dir = dir.join("cache");
+        let nb_loads = Cell::new(0);
+
+        for _ in 0..2 { // simulate two runs
+            let cache = FileSampleCache::new_with_disk_cache(4, cache_dir.to_str().unwrap()).unwrap();
+            let samples = cache.get_or_load(&path, |p| { nb_loads.set(nb_loads.get() + 1); parse(p) }).unwrap();
+            assert_eq!(samples, vec![4, 5, 6]);
+        }
+        assert_eq!(nb_loads.get(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_cache_ignore_corrupted_entry() {
+        let dir = get_test_dir("corrupted");
+        let path = write_file(&dir, "a.csv", "7");
+        let cache_dir = dir.join("cache");
+        let key = FileCacheKey::new_from_path(&path).unwrap();
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join(key.get_disk_file_name()), b"not a cache entry").unwrap();
+
+        let cache = FileSampleCache::new_with_disk_cache(4, cache_dir.to_str().unwrap()).unwrap();
+        assert_eq!(cache.get_or_load(&path, parse).unwrap(), vec![7]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,12 @@
 use std::collections::VecDeque;
+use std::sync::Arc;
 
 use crate::data::plottable::Plottable;
 use crate::data::resetable::Resetable;
 use crate::data::sample::file_sample::FileSample;
 use crate::data::sample::key::SerieKey;
 
+use super::file_sample_cache::FileSampleCache;
 
 
 /// represent a serie of Sample, linked to a sample and a key
@@ -15,6 +17,7 @@ where
     K : SerieKey
 {
     paths : Vec<String>,
+    cache : Option<Arc<FileSampleCache<S>>>,
 
     _key : std::marker::PhantomData<K>,
     _sample : std::marker::PhantomData<S>,
@@ -29,11 +32,28 @@ where
     pub fn new(paths : Vec<String>) -> Self {
         FileSampleSerie {
             paths,
+            cache : None,
             _key : std::marker::PhantomData,
             _sample : std::marker::PhantomData,
         }
     }
 
+    /// Create a new serie of Sample, the parsed files are stored in the cache to avoid parsing them at each iteration
+    /// NOTE : the cache can be shared between multiple series (and multiple plots)
+    pub fn new_with_cache(paths : Vec<String>, cache : Arc<FileSampleCache<S>>) -> Self {
+        FileSampleSerie {
+            paths,
+            cache : Some(cache),
+            _key : std::marker::PhantomData,
+            _sample : std::marker::PhantomData,
+        }
+    }
+
+    /// Get the cache shared by the serie, None if the serie was created with new (without cache)
+    pub fn get_cache(&self) -> Option<&Arc<FileSampleCache<S>>> {
+        self.cache.as_ref()
+    }
+
     /// Get the number of files in the serie
     pub fn nb_files(&self) -> usize {
         self.paths.len()
@@ -49,7 +69,7 @@ where
     type IntoIter = FileSampleSerieIntoIterator<'a, S, K>;
 
     fn into_iter(self) -> Self::IntoIter {
-        FileSampleSerieIntoIterator::new(&self.paths)
+        FileSampleSerieIntoIterator::new(&self.paths, self.cache.as_deref())
     }
 }
 
@@ -69,6 +89,7 @@ where
     K : SerieKey
 {
     paths : &'a Vec<String>,
+    cache : Option<&'a FileSampleCache<S>>,
     next_index : usize,
 
     current_sample : VecDeque<S>,
@@ -82,9 +103,10 @@ where
     K : SerieKey
 {
     /// Create a new iterator over a serie of Sample
-    fn new(paths : &'a Vec<String>) -> Self {
+    fn new(paths : &'a Vec<String>, cache : Option<&'a FileSampleCache<S>>) -> Self {
         FileSampleSerieIntoIterator {
             paths,
+            cache,
             next_index : 0,
             current_sample : VecDeque::new(),
             _key : std::marker::PhantomData,
@@ -122,8 +144,13 @@ where
                 return None;
             }
 
-            let sample = VecDeque::from(S::new_from_file_path(&self.paths[self.next_index])
-                .expect(format!("Error while loading file {}", &self.paths[self.next_index]).as_str()));
+            let path = &self.paths[self.next_index];
+            let samples = match self.cache {
+                Some(cache) => cache.get_or_load(path, S::new_from_file_path),
+                None => S::new_from_file_path(path),
+            };
+            let sample = VecDeque::from(samples
+                .expect(format!("Error while loading file {}", path).as_str()));
 
             self.current_sample = sample;
             self.next_index += 1;
@@ -11,6 +11,8 @@ use super::file_sample_serie::FileSampleSerie;
 use super::sqlite_sample_serie::SqliteSampleSerie;
 #[cfg(feature = "sqlite")]
 use crate::data::sample::sqlite_sample::SqliteSample;
+#[cfg(feature = "sqlite")]
+use super::sqlite_writer::SqliteWriter;
 
 
 
@@ -43,6 +45,13 @@ where
     pub fn nb_samples(&self) -> usize {
         self.samples.len()
     }
+
+    /// Append the samples to a table of the database (created if it doesn't exist)
+    /// NOTE : the samples can be read back with a SqliteSampleSerie of SqliteStoredSample
+    #[cfg(feature = "sqlite")]
+    pub fn save_to_sqlite(&self, conn : &sqlite::Connection, table : &str) -> Result<usize, Box<dyn std::error::Error>> {
+        SqliteWriter::new(conn).insert_samples(table, self)
+    }
 }
 
 impl<'a, S, K> IntoIterator for &'a MemorySampleSerie<S, K>
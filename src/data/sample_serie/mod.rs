@@ -1,7 +1,10 @@
 
 
 pub mod file_sample_serie;
+pub mod file_sample_cache;
 #[cfg(feature = "sqlite")]
 pub mod sqlite_sample_serie;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_writer;
 
 pub mod memory_sample_serie;
\ No newline at end of file
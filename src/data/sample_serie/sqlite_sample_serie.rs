@@ -1,10 +1,12 @@
 use std::collections::VecDeque;
 
-use sqlite::{Connection, State, Statement};
+use sqlite::{Connection, State, Statement, Value};
 
-use crate::data::plottable::Plottable;
+use crate::data::filtered_serie::{FilteredSerie, FilteredSerieIterator};
+use crate::data::filtering::{FilterCondition, Filters};
+use crate::data::plottable::{Plottable, PlottableIterator};
 use crate::data::sample::key::SerieKey;
-use crate::data::sample::sqlite_sample::SqliteSample;
+use crate::data::sample::sqlite_sample::{quote_sqlite_identifier, SqliteParameter, SqliteSample};
 
 /// represent a serie of Sample, linked to a sample and a key
 #[derive(Clone)]
@@ -40,6 +42,14 @@ where
     pub fn get_connection(&self) -> &'a Connection {
         self.conn
     }
+
+    /// Create an iterator over the serie, return an error if the query can't be prepared
+    /// -args: filters: the filters to apply in the query (only the declarative ones on a key with a column)
+    /// NOTE : the filters must still be applied on the samples, as some of them may not be in the query
+    /// and the numeric ones select a few more rows (the values are compared as f32 on the samples)
+    pub fn try_iter(&self, filters : Option<&Filters<K>>) -> Result<SqliteSampleSerieIntoIterator<'a, S, K, Sub>, Box<dyn std::error::Error>> {
+        SqliteSampleSerieIntoIterator::new(self.conn, &self.sub, filters)
+    }
 }
 
 impl<'a, 'it, S, K, Sub> IntoIterator for &'it SqliteSampleSerie<'a, S, K, Sub>
@@ -50,16 +60,144 @@ where
     type Item = S;
     type IntoIter = SqliteSampleSerieIntoIterator<'a, S, K, Sub>;
 
+    /// NOTE : panic if the query can't be prepared or a row can't be read, see try_iter and try_next to get the errors instead
     fn into_iter(self) -> Self::IntoIter {
-        SqliteSampleSerieIntoIterator::new(self.conn, &self.sub)
+        self.try_iter(None).expect("Error while preparing statement")
     }
 }
 
-impl<'a, S, K, Sub> Plottable<S, K> for SqliteSampleSerie<'a, S, K, Sub>
+impl<'conn, S, K, Sub> Plottable<S, K> for SqliteSampleSerie<'conn, S, K, Sub>
 where
     S : SqliteSample<K, Sub>,
     K : SerieKey
-{}
+{
+    /// Same as the default implementation, but the declarative filters are applied in the query
+    /// NOTE : panic if the query can't be prepared or a row can't be read, see try_iter and try_next to get the errors instead
+    fn into_iter_with_filter<'a>(&'a self, serie_keys : (K, Option<K>), legend_key : Option<K>, filters : Option<&'a Filters<K>>)
+    -> PlottableIterator<S, K, FilteredSerieIterator<'a, S, K, SqliteSampleSerieIntoIterator<'conn, S, K, Sub>>>
+    {
+        let iter = self.try_iter(filters).expect("Error while preparing statement");
+        let filtered_serie = FilteredSerie::new(iter, filters);
+        PlottableIterator::new(filtered_serie.into_iter(), serie_keys, legend_key)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// get the next f32 after the value, toward +inf if up, toward -inf otherwise
+/// NOTE : the infinite and NaN values are returned unchanged
+fn next_f32(value : f32, up : bool) -> f32 {
+    if !value.is_finite() {
+        return value;
+    }
+    if value == 0.0 {
+        let smallest = f32::from_bits(1);
+        return if up { smallest } else { -smallest };
+    }
+    let bits = value.to_bits();
+    if (value > 0.0) == up {
+        f32::from_bits(bits + 1)
+    } else {
+        f32::from_bits(bits - 1)
+    }
+}
+
+/// A WHERE clause built from the declarative filters, with its named parameters
+/// NOTE : the samples read the columns as f32, so the numeric bounds are widened by one f32 step :
+/// the rows whose value rounds to the bound are kept, and the exact comparison is done by the filters on the samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqliteWhereClause {
+    sql : String,
+    parameters : Vec<(SqliteParameter, Value)>,
+}
+
+impl SqliteWhereClause {
+    /// Build the clause from the filters, None if no filter can be applied in the query
+    /// -args: get_column_name: the column corresponding to a key, None if the key isn't a column
+    pub fn new_from_filters<K, F>(filters : &Filters<K>, get_column_name : F) -> Option<Self>
+    where
+        K : SerieKey,
+        F : Fn(&K) -> Option<String>
+    {
+        let mut conditions_sql = Vec::new();
+        let mut parameters = Vec::new();
+
+        for filter in filters.get_filters() {
+            let (Some(conditions), Some(column)) = (filter.get_conditions(), get_column_name(filter.get_key())) else {
+                continue;
+            };
+            let column = quote_sqlite_identifier(&column);
+
+            for condition in conditions {
+                // add a named parameter, and return its name
+                let mut add_parameter = |value : Value| {
+                    let name = format!(":plot_helper_filter_{}", parameters.len());
+                    parameters.push((SqliteParameter::Name(name.clone()), value));
+                    name
+                };
+
+                let condition_sql = match condition {
+                    FilterCondition::NumberRange(min, max) => {
+                        let mut bounds = Vec::new();
+                        if let Some(min) = min {
+                            bounds.push(format!("{} >= {}", column, add_parameter(Value::Float(next_f32(*min, false) as f64))));
+                        }
+                        if let Some(max) = max {
+                            bounds.push(format!("{} <= {}", column, add_parameter(Value::Float(next_f32(*max, true) as f64))));
+                        }
+                        if bounds.is_empty() {
+                            continue;
+                        }
+                        bounds.join(" AND ")
+                    },
+                    FilterCondition::NumberIn(values) => {
+                        if values.is_empty() {
+                            "0".to_string()
+                        } else {
+                            values.iter()
+                                .map(|v| {
+                                    let min = add_parameter(Value::Float(next_f32(*v, false) as f64));
+                                    let max = add_parameter(Value::Float(next_f32(*v, true) as f64));
+                                    format!("{} BETWEEN {} AND {}", column, min, max)
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" OR ")
+                        }
+                    },
+                    FilterCondition::StrIn(values) => {
+                        let names = values.iter()
+                            .map(|v| add_parameter(Value::String(v.clone())))
+                            .collect::<Vec<_>>();
+                        format!("{} IN ({})", column, names.join(", "))
+                    },
+                };
+                conditions_sql.push(format!("({})", condition_sql));
+            }
+        }
+
+        if conditions_sql.is_empty() {
+            return None;
+        }
+
+        Some(SqliteWhereClause {
+            sql : conditions_sql.join(" AND "),
+            parameters,
+        })
+    }
+
+    /// Wrap the select query to apply the clause on its rows
+    pub fn apply_to_query(&self, query : &str) -> String {
+        format!("SELECT * FROM ({}) WHERE {}", query.trim().trim_end_matches(';'), self.sql)
+    }
+
+    pub fn get_sql(&self) -> &String {
+        &self.sql
+    }
+
+    pub fn get_parameters(&self) -> &Vec<(SqliteParameter, Value)> {
+        &self.parameters
+    }
+}
 
 // -----------------------------------------------------------------------------
 
@@ -84,17 +222,51 @@ where
 {
     /// Create a new iterator over a serie of Sample
     /// -args: sub: the arg to pass to the construction of the query
-    pub fn new(conn : &'a Connection, sub : &Sub) -> Self {
-        let query = S::get_sqlite_select_query(sub);
-        let stmt = conn.prepare(query).expect("Error while preparing statement");
+    /// -args: filters: the filters to apply in the query (only the declarative ones on a key with a column)
+    pub fn new(conn : &'a Connection, sub : &Sub, filters : Option<&Filters<K>>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut query = S::get_sqlite_select_query(sub);
+        let mut parameters = S::get_sqlite_query_parameters(sub);
 
+        let where_clause = filters.and_then(|filters| SqliteWhereClause::new_from_filters(filters, S::get_sqlite_column_name));
+        if let Some(where_clause) = where_clause {
+            query = where_clause.apply_to_query(&query);
+            parameters.extend(where_clause.parameters);
+        }
 
-        SqliteSampleSerieIntoIterator {
+        let mut stmt = conn.prepare(&query)
+            .map_err(|e| format!("Error while preparing the query {} : {}", query, e))?;
+        for (parameter, value) in parameters.iter() {
+            parameter.bind(&mut stmt, value)
+                .map_err(|e| format!("Error while binding the parameter {:?} of the query {} : {}", parameter, query, e))?;
+        }
+
+        Ok(SqliteSampleSerieIntoIterator {
             stmt,
             current_sample : VecDeque::new(),
             _key : std::marker::PhantomData,
             _sub : std::marker::PhantomData,
+        })
+    }
+}
+
+impl<'a, S, K, Sub> SqliteSampleSerieIntoIterator<'a, S, K, Sub>
+where
+    S : SqliteSample<K, Sub>,
+    K : SerieKey
+{
+    /// Get the next sample, return an error if the next row can't be read or converted to samples
+    pub fn try_next(&mut self) -> Result<Option<S>, Box<dyn std::error::Error>> {
+        // a row can give several samples, or none
+        while self.current_sample.is_empty() {
+            match self.stmt.next().map_err(|e| format!("Error while reading sample : {}", e))? {
+                State::Row => {
+                    let samples = S::new_from_row(&self.stmt).map_err(|e| format!("Error while reading sample : {}", e))?;
+                    self.current_sample = samples.into_iter().collect();
+                },
+                State::Done => return Ok(None),
+            }
         }
+        Ok(self.current_sample.pop_front())
     }
 }
 
@@ -105,29 +277,172 @@ where
 {
     type Item = S;
 
+    /// NOTE : panic if a row can't be read, see try_next to get the error instead
     fn next(&mut self) -> Option<Self::Item> {
-        // If we have no more sample to read, we try the load the next file
-        if self.current_sample.is_empty() {
-            // We try to load the next sample
-            let state = self.stmt.next();
-
-            match state {
-                Ok(State::Row) => {
-                    let sample = S::new_from_row(&self.stmt).expect("Error while reading sample");
-                    self.current_sample = sample.into_iter().collect();
-                },
-                Ok(State::Done) => return None,
-                Err(e) => panic!("Error while reading sample: {:?}", e),
+        self.try_next().unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::{Display, Formatter};
+    use serde_derive::{Deserialize, Serialize};
+    use crate::data::filtering::Filter;
+    use crate::data::sample::Sample;
+    use crate::generate_plot_key;
+
+    generate_plot_key!(RunKey[
+        Time { "time (s)", Numeric },
+        Parser { "parser", String }
+    ]);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct RunSample {
+        time : f32,
+        parser : String,
+    }
+
+    impl Sample<RunKey> for RunSample {
+        fn get_numeric_value(&self, key : &RunKey) -> f32 {
+            match key {
+                RunKey::Time => self.time,
+                _ => panic!("not a numeric key"),
+            }
+        }
+
+        fn get_string_value(&self, key : &RunKey) -> String {
+            match key {
+                RunKey::Parser => self.parser.clone(),
+                _ => panic!("not a string key"),
+            }
+        }
+    }
+
+    /// the substitution is the table name and the minimum time
+    impl SqliteSample<RunKey, (String, f64)> for RunSample {
+        fn new_from_row(row : &Statement) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+            Ok(vec![RunSample {
+                time : row.read::<f64, _>("time")? as f32,
+                parser : row.read::<String, _>("parser")?,
+            }])
+        }
+
+        fn get_sqlite_select_query(substitution : &(String, f64)) -> String {
+            format!("SELECT time, parser FROM {} WHERE time >= :min_time ORDER BY time", substitution.0)
+        }
+
+        fn get_sqlite_query_parameters(substitution : &(String, f64)) -> Vec<(SqliteParameter, Value)> {
+            vec![(":min_time".into(), Value::Float(substitution.1))]
+        }
+
+        fn get_sqlite_column_name(key : &RunKey) -> Option<String> {
+            match key {
+                RunKey::Time => Some("time".to_string()),
+                RunKey::Parser => Some("parser".to_string()),
             }
+        }
+    }
+
+    fn get_connection() -> Connection {
+        let conn = sqlite::open(":memory:").unwrap();
+        conn.execute("
+            CREATE TABLE runs (time REAL, parser TEXT);
+            INSERT INTO runs VALUES (1.0, 'a'), (2.0, 'b'), (3.0, 'a'), (4.0, 'b');
+        ").unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_query_parameters() {
+        let conn = get_connection();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("runs".to_string(), 2.0));
+        let times = serie.into_iter().map(|s| s.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![2.0, 3.0, 4.0]);
+    }
 
+    #[test]
+    fn test_invalid_query_return_error() {
+        let conn = get_connection();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("missing_table".to_string(), 0.0));
+        assert!(serie.try_iter(None).is_err());
+    }
+
+    #[test]
+    fn test_filters_pushed_in_query() {
+        let conn = get_connection();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("runs".to_string(), 0.0));
+        let filters = Filters::new(vec![
+            Filter::new_number_range(RunKey::Time, Some(1.5), None),
+            Filter::new_str_in(RunKey::Parser, vec!["a".to_string()]),
+        ]);
+
+        // the query alone return only the filtered samples
+        let samples = serie.try_iter(Some(&filters)).unwrap().collect::<Vec<_>>();
+        assert_eq!(samples, vec![RunSample { time : 3.0, parser : "a".to_string() }]);
+
+        let points = serie.into_iter_with_filter((RunKey::Time, None), Some(RunKey::Parser), Some(&filters)).collect::<Vec<_>>();
+        assert_eq!(points, vec![("a".to_string(), (3.0, 1.0))]);
+    }
 
-            self.next()
-               
-        }else {
-            // If we have a sample, we return the next point
-            let sample = self.current_sample.pop_front().expect("Error while reading sample");
-            
-            Some(sample)
+    #[test]
+    fn test_custom_filters_not_pushed_in_query() {
+        let filters = Filters::new(vec![
+            Filter::new_number(RunKey::Time, |t| t > 1.0),
+        ]);
+        assert_eq!(SqliteWhereClause::new_from_filters(&filters, RunSample::get_sqlite_column_name), None);
+
+        // applied on the samples instead
+        let conn = get_connection();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("runs".to_string(), 0.0));
+        let points = serie.into_iter_with_filter((RunKey::Time, None), None, Some(&filters)).count();
+        assert_eq!(points, 3);
+    }
+
+    #[test]
+    fn test_float_filters_match_samples() {
+        // the REAL values are not representable as f32, the samples see them rounded
+        let conn = sqlite::open(":memory:").unwrap();
+        conn.execute("
+            CREATE TABLE runs (time REAL, parser TEXT);
+            INSERT INTO runs VALUES (0.1, 'a'), (0.2, 'b'), (0.3, 'a'), (0.7, 'b');
+        ").unwrap();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("runs".to_string(), 0.0));
+        let samples = serie.into_iter().collect::<Vec<_>>();
+
+        let all_filters = vec![
+            Filter::new_number_in(RunKey::Time, vec![0.1, 0.3]),
+            Filter::new_number_range(RunKey::Time, Some(0.1), None),
+            Filter::new_number_range(RunKey::Time, None, Some(0.3)),
+            Filter::new_number_range(RunKey::Time, Some(0.2), Some(0.7)),
+            Filter::new_number_in(RunKey::Time, vec![]),
+        ];
+        for filter in all_filters {
+            let filters = Filters::new(vec![filter]);
+            let expected = samples.iter().filter(|s| filters.apply(*s)).cloned().collect::<Vec<_>>();
+
+            // the query keeps at least the filtered samples, the filters on the samples give the same result as in memory
+            let queried = serie.try_iter(Some(&filters)).unwrap().collect::<Vec<_>>();
+            assert!(expected.iter().all(|s| queried.contains(s)));
+            let filtered = queried.into_iter().filter(|s| filters.apply(s)).collect::<Vec<_>>();
+            assert_eq!(filtered, expected);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_next_f32() {
+        assert!(next_f32(0.1, true) > 0.1 && next_f32(0.1, false) < 0.1);
+        assert_eq!(next_f32(next_f32(-2.5, true), false), -2.5);
+        assert!(next_f32(0.0, true) > 0.0 && next_f32(0.0, false) < 0.0);
+        assert_eq!(next_f32(f32::INFINITY, false), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_try_next_return_error() {
+        let conn = get_connection();
+        conn.execute("CREATE TABLE texts (time REAL, parser TEXT); INSERT INTO texts VALUES (1.0, NULL);").unwrap();
+        let serie : SqliteSampleSerie<RunSample, RunKey, _> = SqliteSampleSerie::new(&conn, ("texts".to_string(), 0.0));
+        let mut iter = serie.try_iter(None).unwrap();
+        assert!(iter.try_next().is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use sqlite::{Connection, Statement, Value};
+
+use crate::data::sample::key::SerieKey;
+use crate::data::sample::sqlite_sample::quote_sqlite_identifier;
+use crate::data::sample::sqlite_stored_sample::{get_sqlite_key_column_name, get_sqlite_storable_keys, SQLITE_METRIC_COLUMN, SQLITE_RUN_ID_COLUMN};
+use crate::data::sample::Sample;
+use crate::stat::stats_serie::{MetricName, StatsSerie};
+
+
+/// the metrics stored for each aggregated serie
+const STORED_METRICS : [MetricName; 4] = [MetricName::Mean, MetricName::Median, MetricName::Additive, MetricName::NbValues];
+
+
+/// Write samples and aggregated stats in SQLite tables, to read them later with a SqliteSampleSerie of SqliteStoredSample.
+/// The schema of the tables is derived from the key : one column per numeric (REAL) or string (TEXT) key, named by its display name.
+/// The tables also have a "run_id" column, set with the run id of the writer (NULL without it).
+/// NOTE : the rows are appended, so the same table can accumulate the results of multiple runs, distinguished by their run id
+pub struct SqliteWriter<'a, K>
+where
+    K : SerieKey
+{
+    conn : &'a Connection,
+    run_id : Option<String>,
+    _key : std::marker::PhantomData<K>,
+}
+
+impl<'a, K> SqliteWriter<'a, K>
+where
+    K : SerieKey
+{
+    pub fn new(conn : &'a Connection) -> Self {
+        SqliteWriter {
+            conn,
+            run_id : None,
+            _key : std::marker::PhantomData,
+        }
+    }
+
+    /// write the run id in the rows, to read them back with SqliteStoredTable::with_run_id
+    pub fn with_run_id(mut self, run_id : &str) -> Self {
+        self.run_id = Some(run_id.to_string());
+        self
+    }
+
+    /// get the value of the run id column
+    fn get_run_id_value(&self) -> Value {
+        match self.run_id.as_ref() {
+            Some(run_id) => Value::String(run_id.clone()),
+            None => Value::Null,
+        }
+    }
+
+    /// Insert the samples in the table (created if it doesn't exist), in one transaction
+    /// return the number of inserted rows
+    pub fn insert_samples<S, I>(&self, table : &str, samples : I) -> Result<usize, Box<dyn std::error::Error>>
+    where
+        S : Sample<K>,
+        I : IntoIterator<Item = S>
+    {
+        let keys = get_sqlite_storable_keys::<K>()?;
+        self.create_table(table, false)?;
+
+        self.in_transaction(|| {
+            let mut stmt = self.prepare_insert(table, &keys, false)?;
+            let mut nb_rows = 0;
+            for sample in samples {
+                let mut values = keys.iter().map(|key| {
+                    if key.is_numeric() {
+                        Value::Float(sample.get_numeric_value(key) as f64)
+                    } else {
+                        Value::String(sample.get_string_value(key))
+                    }
+                }).collect::<Vec<_>>();
+                values.push(self.get_run_id_value());
+                Self::execute_insert(&mut stmt, &values)?;
+                nb_rows += 1;
+            }
+            Ok(nb_rows)
+        })
+    }
+
+    /// Insert the result of collect_stats_sorted_by_unique_values in the table (created if it doesn't exist), in one transaction.
+    /// For each sort value, a row is inserted per metric : the column of the sort key contains the sort value,
+    /// the columns of the stats keys contain the value of the metric, and the others are NULL.
+    /// return the number of inserted rows
+    pub fn insert_stats(&self, table : &str, sort_value_key : &K, stats : &HashMap<String, HashMap<K, StatsSerie>>) -> Result<usize, Box<dyn std::error::Error>> {
+        let keys = get_sqlite_storable_keys::<K>()?;
+        self.create_table(table, true)?;
+
+        // sort to insert the rows in a deterministic order
+        let mut sort_values = stats.keys().collect::<Vec<_>>();
+        sort_values.sort();
+
+        self.in_transaction(|| {
+            let mut stmt = self.prepare_insert(table, &keys, true)?;
+            let mut nb_rows = 0;
+            for sort_value in sort_values.iter() {
+                let stats_by_key = &stats[*sort_value];
+                for metric in STORED_METRICS.iter() {
+                    let mut values = keys.iter().map(|key| {
+                        if key == sort_value_key {
+                            Self::get_sort_value(key, sort_value)
+                        } else if let Some(stats_serie) = stats_by_key.get(key) {
+                            Value::Float(stats_serie.get_stats(*metric).value)
+                        } else {
+                            Value::Null
+                        }
+                    }).collect::<Vec<_>>();
+                    values.push(self.get_run_id_value());
+                    values.push(Value::String(metric.get_name()));
+                    Self::execute_insert(&mut stmt, &values)?;
+                    nb_rows += 1;
+                }
+            }
+            Ok(nb_rows)
+        })
+    }
+
+    /// convert the sort value (stringified by collect_stats_sorted_by_unique_values) to the type of its key
+    fn get_sort_value(key : &K, sort_value : &str) -> Value {
+        if key.is_numeric() {
+            match sort_value.parse::<f64>() {
+                Ok(value) => Value::Float(value),
+                Err(_) => Value::Null,
+            }
+        } else {
+            Value::String(sort_value.to_string())
+        }
+    }
+
+    /// create the table if it doesn't exist
+    fn create_table(&self, table : &str, with_metric : bool) -> Result<(), Box<dyn std::error::Error>> {
+        let mut columns = get_sqlite_storable_keys::<K>()?.iter().map(|key| {
+            let column_type = if key.is_numeric() { "REAL" } else { "TEXT" };
+            format!("{} {}", quote_sqlite_identifier(&get_sqlite_key_column_name(key)), column_type)
+        }).collect::<Vec<_>>();
+        columns.push(format!("{} TEXT", quote_sqlite_identifier(SQLITE_RUN_ID_COLUMN)));
+        if with_metric {
+            columns.push(format!("{} TEXT", quote_sqlite_identifier(SQLITE_METRIC_COLUMN)));
+        }
+
+        let query = format!("CREATE TABLE IF NOT EXISTS {} ({})", quote_sqlite_identifier(table), columns.join(", "));
+        self.conn.execute(&query)
+            .map_err(|e| format!("Error while creating the table {} : {}", table, e))?;
+        Ok(())
+    }
+
+    fn prepare_insert(&self, table : &str, keys : &[K], with_metric : bool) -> Result<Statement<'a>, Box<dyn std::error::Error>> {
+        let mut columns = keys.iter().map(|key| quote_sqlite_identifier(&get_sqlite_key_column_name(key))).collect::<Vec<_>>();
+        columns.push(quote_sqlite_identifier(SQLITE_RUN_ID_COLUMN));
+        if with_metric {
+            columns.push(quote_sqlite_identifier(SQLITE_METRIC_COLUMN));
+        }
+        let parameters = vec!["?"; columns.len()];
+
+        let query = format!("INSERT INTO {} ({}) VALUES ({})", quote_sqlite_identifier(table), columns.join(", "), parameters.join(", "));
+        let stmt = self.conn.prepare(&query)
+            .map_err(|e| format!("Error while preparing the query {} : {}", query, e))?;
+        Ok(stmt)
+    }
+
+    /// execute the prepared insert with the values, and reset it for the next row
+    fn execute_insert(stmt : &mut Statement, values : &[Value]) -> Result<(), Box<dyn std::error::Error>> {
+        for (index, value) in values.iter().enumerate() {
+            stmt.bind((index + 1, value))?;
+        }
+        stmt.next()?;
+        stmt.reset()?;
+        Ok(())
+    }
+
+    /// run the function in a transaction, rollback if it fails
+    fn in_transaction<T, F>(&self, f : F) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F : FnOnce() -> Result<T, Box<dyn std::error::Error>>
+    {
+        self.conn.execute("BEGIN TRANSACTION")?;
+        match f() {
+            Ok(result) => {
+                self.conn.execute("COMMIT")?;
+                Ok(result)
+            },
+            Err(e) => {
+                self.conn.execute("ROLLBACK")?;
+                Err(e)
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::{Display, Formatter};
+    use serde_derive::{Deserialize, Serialize};
+    use crate::data::plottable::Plottable;
+    use crate::data::sample::sqlite_stored_sample::{SqliteStoredSample, SqliteStoredTable};
+    use crate::data::sample_serie::memory_sample_serie::MemorySampleSerie;
+    use crate::data::sample_serie::sqlite_sample_serie::SqliteSampleSerie;
+    use crate::generate_plot_key;
+
+    generate_plot_key!(RunKey[
+        Size { "size (Mb)", Numeric },
+        Time { "time (s)", Numeric },
+        Parser { "parser", String }
+    ]);
+
+    #[derive(Debug, Clone)]
+    struct RunSample {
+        size : f32,
+        time : f32,
+        parser : String,
+    }
+
+    impl Sample<RunKey> for RunSample {
+        fn get_numeric_value(&self, key : &RunKey) -> f32 {
+            match key {
+                RunKey::Size => self.size,
+                RunKey::Time => self.time,
+                _ => panic!("not a numeric key"),
+            }
+        }
+
+        fn get_string_value(&self, key : &RunKey) -> String {
+            match key {
+                RunKey::Parser => self.parser.clone(),
+                _ => panic!("not a string key"),
+            }
+        }
+    }
+
+    fn get_memory_serie() -> MemorySampleSerie<RunSample, RunKey> {
+        MemorySampleSerie::new(vec![
+            RunSample { size : 1.0, time : 1.0, parser : "a".to_string() },
+            RunSample { size : 1.0, time : 3.0, parser : "a".to_string() },
+            RunSample { size : 2.0, time : 4.0, parser : "b".to_string() },
+        ])
+    }
+
+    #[test]
+    fn test_samples_round_trip() {
+        let conn = sqlite::open(":memory:").unwrap();
+        let memory_serie = get_memory_serie();
+        assert_eq!(SqliteWriter::new(&conn).with_run_id("run 1").insert_samples("runs", &memory_serie).unwrap(), 3);
+        assert_eq!(SqliteWriter::new(&conn).with_run_id("run 2").insert_samples("runs", &memory_serie).unwrap(), 3); // a second run is appended
+
+        let serie : SqliteSampleSerie<SqliteStoredSample<RunKey>, RunKey, _> = SqliteSampleSerie::new(&conn, SqliteStoredTable::new_samples("runs"));
+        let samples = serie.into_iter().collect::<Vec<_>>();
+        assert_eq!(samples.len(), 6);
+        assert_eq!(samples[2].get_numeric_value(&RunKey::Time), 4.0);
+        assert_eq!(samples[2].get_string_value(&RunKey::Parser), "b");
+        let run_ids = samples.iter().map(|s| s.get_run_id().cloned()).collect::<Vec<_>>();
+        assert_eq!(run_ids.iter().filter(|id| id.as_deref() == Some("run 2")).count(), 3);
+
+        // the rows of one run only
+        let table = SqliteStoredTable::new_samples("runs").with_run_id("run 1");
+        let serie : SqliteSampleSerie<SqliteStoredSample<RunKey>, RunKey, _> = SqliteSampleSerie::new(&conn, table);
+        let times = serie.into_iter().map(|s| s.get_numeric_value(&RunKey::Time)).collect::<Vec<_>>();
+        assert_eq!(times, vec![1.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_stats_round_trip() {
+        let conn = sqlite::open(":memory:").unwrap();
+        let writer = SqliteWriter::new(&conn);
+        let stats = get_memory_serie().collect_stats_sorted_by_unique_values(&vec![RunKey::Time], &RunKey::Size);
+        assert_eq!(writer.insert_stats("stats", &RunKey::Size, &stats).unwrap(), 2 * STORED_METRICS.len());
+        assert_eq!(writer.with_run_id("run 2").insert_stats("stats", &RunKey::Size, &stats).unwrap(), 2 * STORED_METRICS.len());
+
+        let table = SqliteStoredTable::new_stats("stats", MetricName::Mean).with_run_id("run 2");
+        let serie : SqliteSampleSerie<SqliteStoredSample<RunKey>, RunKey, _> = SqliteSampleSerie::new(&conn, table);
+        let points = serie.into_iter()
+            .map(|s| (s.get_numeric_value(&RunKey::Size), s.get_numeric_value(&RunKey::Time)))
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![(1.0, 2.0), (2.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_failed_insert_is_rolled_back() {
+        let conn = sqlite::open(":memory:").unwrap();
+        // a table with an incompatible schema
+        conn.execute("CREATE TABLE runs (\"size (Mb)\" REAL NOT NULL, \"time (s)\" REAL, parser TEXT NOT NULL CHECK (parser != 'b'), run_id TEXT)").unwrap();
+        let writer = SqliteWriter::new(&conn);
+        assert!(writer.insert_samples("runs", &get_memory_serie()).is_err());
+
+        let mut stmt = conn.prepare("SELECT COUNT(*) FROM runs").unwrap();
+        stmt.next().unwrap();
+        assert_eq!(stmt.read::<i64, _>(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_key_without_all_keys_return_error() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        struct ManualKey;
+
+        impl Display for ManualKey {
+            fn fmt(&self, f : &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "manual")
+            }
+        }
+
+        impl SerieKey for ManualKey {
+            fn get_display_name(&self) -> String { "manual".to_string() }
+            fn is_numeric(&self) -> bool { true }
+            fn is_string(&self) -> bool { false }
+            fn is_object(&self) -> bool { false }
+        }
+
+        let conn = sqlite::open(":memory:").unwrap();
+        let stats : HashMap<String, HashMap<ManualKey, StatsSerie>> = HashMap::new();
+        assert!(SqliteWriter::new(&conn).insert_stats("stats", &ManualKey, &stats).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::data::sample::key::SerieKey;
+use crate::stat::stats_serie::StatsSerie;
+
+
+/// represent the aggregated results of one run of the benchmarks.
+/// The results are grouped by the unique values of a grouping key, and indexed by the display name of the measured keys.
+/// NOTE : the raw values are kept to compare the runs with statistical tests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkRun {
+    run_id : String,
+    /// seconds since the unix epoch
+    timestamp : u64,
+    grouping_key : String,
+    /// group value -> measured key -> values
+    results : BTreeMap<String, BTreeMap<String, Vec<f32>>>,
+}
+
+impl BenchmarkRun {
+    /// Create a new run from the result of collect_stats_sorted_by_unique_values, with the current time as timestamp
+    /// - args: grouping_key: the key used to sort the values (sort_value_key)
+    pub fn new<K>(run_id : &str, grouping_key : &K, stats : &HashMap<String, HashMap<K, StatsSerie>>) -> Self
+    where
+        K : SerieKey
+    {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self::new_with_timestamp(run_id, timestamp, grouping_key, stats)
+    }
+
+    /// Create a new run from the result of collect_stats_sorted_by_unique_values
+    /// - args: timestamp: seconds since the unix epoch
+    pub fn new_with_timestamp<K>(run_id : &str, timestamp : u64, grouping_key : &K, stats : &HashMap<String, HashMap<K, StatsSerie>>) -> Self
+    where
+        K : SerieKey
+    {
+        let results = stats.iter().map(|(group, stats_by_key)| {
+            let values = stats_by_key.iter()
+                .map(|(key, stats_serie)| (key.get_display_name(), stats_serie.serie.clone()))
+                .collect::<BTreeMap<String, Vec<f32>>>();
+            (group.clone(), values)
+        }).collect();
+
+        BenchmarkRun {
+            run_id : run_id.to_string(),
+            timestamp,
+            grouping_key : grouping_key.get_display_name(),
+            results,
+        }
+    }
+
+    pub fn get_run_id(&self) -> &String {
+        &self.run_id
+    }
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn get_grouping_key(&self) -> &String {
+        &self.grouping_key
+    }
+
+    /// Get the groups of the run, sorted
+    pub fn get_groups(&self) -> Vec<&String> {
+        self.results.keys().collect()
+    }
+
+    /// Get the measured keys of a group, sorted
+    pub fn get_keys(&self, group : &str) -> Vec<&String> {
+        self.results.get(group).map(|values| values.keys().collect()).unwrap_or_default()
+    }
+
+    /// Get the raw values of a measured key for a group
+    pub fn get_values(&self, group : &str, key : &str) -> Option<&Vec<f32>> {
+        self.results.get(group)?.get(key)
+    }
+
+    /// Get the stats of a measured key for a group
+    pub fn get_stats(&self, group : &str, key : &str) -> Option<StatsSerie> {
+        self.get_values(group, key).map(StatsSerie::new)
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::benchmark_run::BenchmarkRun;
+
+
+const RUN_EXTENSION : &str = "run";
+
+/// A local store of the benchmark runs : a directory with one json file per run
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir : PathBuf,
+}
+
+impl HistoryStore {
+    /// Open the store in the directory (created if it doesn't exist)
+    pub fn open(dir_path : &str) -> Result<HistoryStore, Box<dyn std::error::Error>> {
+        let dir = Path::new(dir_path);
+        fs::create_dir_all(dir)?;
+        Ok(HistoryStore {
+            dir : dir.to_path_buf(),
+        })
+    }
+
+    /// Save the run, replacing the previous run with the same id
+    pub fn save_run(&self, run : &BenchmarkRun) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(self.get_run_path(run.get_run_id()), serde_json::to_string(run)?)?;
+        Ok(())
+    }
+
+    /// Load the run with the given id, None if it isn't in the store
+    pub fn load_run(&self, run_id : &str) -> Result<Option<BenchmarkRun>, Box<dyn std::error::Error>> {
+        let path = self.get_run_path(run_id);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Load all the runs, sorted by timestamp (then by id)
+    pub fn load_runs(&self) -> Result<Vec<BenchmarkRun>, Box<dyn std::error::Error>> {
+        let mut runs = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|e| e == RUN_EXTENSION) {
+                let run : BenchmarkRun = serde_json::from_str(&fs::read_to_string(&path)?)
+                    .map_err(|e| format!("Invalid run file {} : {}", path.to_string_lossy(), e))?;
+                runs.push(run);
+            }
+        }
+        runs.sort_by(|a, b| a.get_timestamp().cmp(&b.get_timestamp()).then_with(|| a.get_run_id().cmp(b.get_run_id())));
+        Ok(runs)
+    }
+
+    /// Load the most recent run before the given one, to use it as a baseline
+    pub fn load_previous_run(&self, run : &BenchmarkRun) -> Result<Option<BenchmarkRun>, Box<dyn std::error::Error>> {
+        let previous = self.load_runs()?.into_iter()
+            .rev()
+            .find(|r| r.get_run_id() != run.get_run_id() && r.get_timestamp() <= run.get_timestamp());
+        Ok(previous)
+    }
+
+    /// Remove the run from the store, return false if it wasn't in the store
+    pub fn remove_run(&self, run_id : &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let path = self.get_run_path(run_id);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path)?;
+        Ok(true)
+    }
+
+    /// get the path of the run file, the id is percent-encoded to be a valid file name
+    /// NOTE : the encoding is injective, two different ids never share a file
+    fn get_run_path(&self, run_id : &str) -> PathBuf {
+        let mut file_name = String::new();
+        for byte in run_id.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || byte == b'.' {
+                file_name.push(byte as char);
+            } else {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        self.dir.join(format!("{}.{}", file_name, RUN_EXTENSION))
+    }
+}
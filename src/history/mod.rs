@@ -0,0 +1,3 @@
+pub mod benchmark_run;
+pub mod history_store;
+pub mod regression;
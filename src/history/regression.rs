@@ -0,0 +1,190 @@
+use std::fmt::{Display, Formatter};
+
+use crate::plotter::utils::format_number;
+use crate::stat::hypothesis::mann_whitney_u;
+use crate::stat::stats_serie::{MetricName, StatsSerie};
+use crate::static_html::presentation_data::Array;
+
+use super::benchmark_run::BenchmarkRun;
+
+
+/// the status of a measured key between two runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RegressionStatus {
+    /// significantly worse than the baseline
+    Regression,
+    /// significantly better than the baseline
+    Improvement,
+    /// no significant change
+    Unchanged,
+}
+
+impl Display for RegressionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RegressionStatus::Regression => "regression",
+            RegressionStatus::Improvement => "improvement",
+            RegressionStatus::Unchanged => "unchanged",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+
+/// define how two runs are compared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegressionConfig {
+    /// the metric compared between the runs
+    pub metric : MetricName,
+    /// the minimal relative change (e.g. 0.05 for 5%) to report a regression or an improvement
+    pub relative_threshold : f64,
+    /// the significance level of the Mann–Whitney U test
+    pub alpha : f64,
+    /// if a higher value is better (false for times and memory)
+    pub higher_is_better : bool,
+}
+
+impl Default for RegressionConfig {
+    fn default() -> Self {
+        RegressionConfig {
+            metric : MetricName::Median,
+            relative_threshold : 0.05,
+            alpha : 0.05,
+            higher_is_better : false,
+        }
+    }
+}
+
+
+/// get the relative change from the baseline to the current value
+/// NOTE : from a baseline of 0, any change is infinite (and no change is 0) instead of NaN
+fn get_relative_change(baseline_value : f64, current_value : f64) -> f64 {
+    if baseline_value == 0.0 {
+        return if current_value == 0.0 { 0.0 } else { current_value.signum() * f64::INFINITY };
+    }
+    (current_value - baseline_value) / baseline_value.abs()
+}
+
+
+/// the comparison of a measured key of a group between a baseline and a new run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionEntry {
+    pub group : String,
+    pub key : String,
+    pub baseline_value : f64,
+    pub current_value : f64,
+    /// (current - baseline) / |baseline|, infinite if the baseline is 0 and the current value isn't
+    pub relative_change : f64,
+    /// the p-value of the Mann–Whitney U test between the raw values
+    pub p_value : f64,
+    pub status : RegressionStatus,
+}
+
+
+/// the comparison of a new run with a baseline run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionReport {
+    baseline_run_id : String,
+    current_run_id : String,
+    config : RegressionConfig,
+    entries : Vec<RegressionEntry>,
+}
+
+impl RegressionReport {
+    /// Compare each measured key of each group present in both runs (sorted by group then key)
+    pub fn new(baseline : &BenchmarkRun, current : &BenchmarkRun, config : RegressionConfig) -> Self {
+        let mut entries = Vec::new();
+        for group in current.get_groups() {
+            for key in current.get_keys(group) {
+                let (Some(baseline_values), Some(current_values)) = (baseline.get_values(group, key), current.get_values(group, key)) else {
+                    continue;
+                };
+                entries.push(Self::compare(group, key, baseline_values, current_values, &config));
+            }
+        }
+
+        RegressionReport {
+            baseline_run_id : baseline.get_run_id().clone(),
+            current_run_id : current.get_run_id().clone(),
+            config,
+            entries,
+        }
+    }
+
+    fn compare(group : &str, key : &str, baseline_values : &Vec<f32>, current_values : &Vec<f32>, config : &RegressionConfig) -> RegressionEntry {
+        let baseline_value = StatsSerie::new(baseline_values).get_stats(config.metric).value;
+        let current_value = StatsSerie::new(current_values).get_stats(config.metric).value;
+        let relative_change = get_relative_change(baseline_value, current_value);
+        let p_value = mann_whitney_u(baseline_values, current_values).p_value;
+
+        let status = if p_value < config.alpha && relative_change.abs() >= config.relative_threshold {
+            if (relative_change > 0.0) == config.higher_is_better {
+                RegressionStatus::Improvement
+            } else {
+                RegressionStatus::Regression
+            }
+        } else {
+            RegressionStatus::Unchanged
+        };
+
+        RegressionEntry {
+            group : group.to_string(),
+            key : key.to_string(),
+            baseline_value,
+            current_value,
+            relative_change,
+            p_value,
+            status,
+        }
+    }
+
+    pub fn get_entries(&self) -> &Vec<RegressionEntry> {
+        &self.entries
+    }
+
+    /// Get the entries with a regression
+    pub fn get_regressions(&self) -> Vec<&RegressionEntry> {
+        self.entries.iter().filter(|e| e.status == RegressionStatus::Regression).collect()
+    }
+
+    pub fn has_regression(&self) -> bool {
+        self.entries.iter().any(|e| e.status == RegressionStatus::Regression)
+    }
+
+    pub fn get_baseline_run_id(&self) -> &String {
+        &self.baseline_run_id
+    }
+
+    pub fn get_current_run_id(&self) -> &String {
+        &self.current_run_id
+    }
+
+    pub fn get_config(&self) -> &RegressionConfig {
+        &self.config
+    }
+
+    /// Create the regression table, to include it in a static html presentation
+    pub fn to_array(&self) -> Array {
+        let header = vec![
+            "group".to_string(),
+            "key".to_string(),
+            format!("{} ({})", self.config.metric, self.baseline_run_id),
+            format!("{} ({})", self.config.metric, self.current_run_id),
+            "change".to_string(),
+            "p-value".to_string(),
+            "status".to_string(),
+        ];
+
+        let data = self.entries.iter().map(|e| vec![
+            e.group.clone(),
+            e.key.clone(),
+            format_number(e.baseline_value),
+            format_number(e.current_value),
+            format!("{:+.1}%", e.relative_change * 100.0),
+            format_number(e.p_value),
+            e.status.to_string(),
+        ]).collect();
+
+        Array::new(header, data)
+    }
+}
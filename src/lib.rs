@@ -3,4 +3,5 @@ pub mod plotter;
 pub mod params;
 pub mod stat;
 pub mod wrapper;
-pub mod static_html;
\ No newline at end of file
+pub mod static_html;
+pub mod history;
\ No newline at end of file
@@ -1,10 +1,56 @@
-/// size of one figure in the grid (width, height)
+/// default size of one figure in the grid (width, height)
 pub const ONE_FIG_SIZE : (u32, u32) = (820, 640);
 
-pub const LABEL_HORIZONTAL_SIZE : u32 = 150;
+/// width of the area on the right of a chart for its color bar
+pub const COLOR_BAR_SIZE : u32 = 150;
 
 pub const FIGURE_CAPTION_FONT_SIZE : u32 = 30;
 
+/// font size of the title of a figure, above the captions of its subplots
+pub const FIGURE_TITLE_FONT_SIZE : u32 = 36;
 
+/// font size of the labels of the axes
+pub const LABEL_FONT_SIZE : u32 = 12;
 
-pub const HTML_TEMPLATE : &'static str = include_str!("../ressources/static.html");
\ No newline at end of file
+/// opacity of the outliers plotted in a muted style
+pub const OUTLIER_OPACITY : f64 = 0.25;
+
+/// opacity of the data drawn under the smoothed series
+pub const RAW_DATA_OPACITY : f64 = 0.3;
+
+/// font size of the values written in the cells of a heatmap
+pub const HEATMAP_VALUE_FONT_SIZE : u32 = 15;
+
+/// number of color steps drawn in the color bar of a heatmap
+pub const HEATMAP_COLOR_BAR_NB_STEPS : usize = 100;
+
+/// number of points used to draw a fitted curve
+pub const FIT_CURVE_NB_POINTS : usize = 200;
+
+/// fraction of the width of a category taken by its bars (the rest is the gap between the categories)
+pub const BAR_GROUP_WIDTH : f64 = 0.8;
+
+/// height of an entry of a legend
+pub const LEGEND_ENTRY_HEIGHT : u32 = 20;
+
+/// font size of the labels of a legend
+pub const LEGEND_FONT_SIZE : u32 = 15;
+
+/// max width of a label of a legend in pixels, the longer labels are truncated with an ellipsis
+pub const LEGEND_MAX_LABEL_WIDTH : u32 = 200;
+
+/// margin around the legend box in pixels
+pub const LEGEND_MARGIN : u32 = 10;
+
+/// max number of steps drawn for an empirical cumulative distribution function
+pub const ECDF_MAX_NB_POINTS : usize = 1000;
+
+/// max number of quantiles compared in a Q-Q plot
+pub const QQ_NB_QUANTILES : usize = 100;
+
+
+
+pub const HTML_TEMPLATE : &str = include_str!("../ressources/static.html");
+
+/// the script drawing the interactive charts of the html reports
+pub const CHART_SCRIPT : &str = include_str!("../ressources/chart.js");
@@ -0,0 +1,229 @@
+use std::ops::Range;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartContext;
+use plotters::coord::cartesian::Cartesian2d;
+use plotters::coord::ranged1d::Ranged;
+use plotters::element::{DashedPathElement, Drawable, EmptyElement, PointCollection, Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, RGBColor, ShapeStyle};
+use plotters_backend::{BackendCoord, DrawingBackend, DrawingErrorKind};
+
+use super::figure_style::FigureStyle;
+
+
+/// the opacity of the shaded intervals
+const INTERVAL_OPACITY : f64 = 0.15;
+
+
+/// the shape of an annotation, in the coordinates of the data
+/// NOTE : on a bar plot, the category i is centered on x = i + 0.5
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnnotationKind {
+    /// a dashed line at y on the whole width of the chart (e.g. a threshold)
+    HorizontalLine(f32),
+    /// a dashed line at x on the whole height of the chart
+    VerticalLine(f32),
+    /// a shaded interval of x on the whole height of the chart
+    XInterval(Range<f32>),
+    /// a shaded interval of y on the whole width of the chart
+    YInterval(Range<f32>),
+    /// the label written at the (x, y) point
+    Text((f32, f32)),
+    /// an arrow from the first point to the second one, the label is written at the first point
+    Arrow((f32, f32), (f32, f32)),
+}
+
+
+/// an annotation drawn on top of a subplot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub kind : AnnotationKind,
+    /// the text written next to the annotation
+    pub label : Option<String>,
+    /// the color of the annotation, the foreground color of the figure if None
+    pub color : Option<RGBColor>,
+}
+
+impl Annotation {
+    pub fn new(kind : AnnotationKind) -> Self {
+        Annotation {
+            kind,
+            label : None,
+            color : None,
+        }
+    }
+
+    pub fn horizontal_line(y : f32) -> Self {
+        Self::new(AnnotationKind::HorizontalLine(y))
+    }
+
+    pub fn vertical_line(x : f32) -> Self {
+        Self::new(AnnotationKind::VerticalLine(x))
+    }
+
+    pub fn x_interval(start : f32, end : f32) -> Self {
+        Self::new(AnnotationKind::XInterval(start..end))
+    }
+
+    pub fn y_interval(start : f32, end : f32) -> Self {
+        Self::new(AnnotationKind::YInterval(start..end))
+    }
+
+    pub fn text(x : f32, y : f32, text : &str) -> Self {
+        Self::new(AnnotationKind::Text((x, y))).with_label(text)
+    }
+
+    pub fn arrow(from : (f32, f32), to : (f32, f32)) -> Self {
+        Self::new(AnnotationKind::Arrow(from, to))
+    }
+
+    pub fn with_label(mut self, label : &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_color(mut self, color : RGBColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+
+/// an element drawing an arrow between two coordinates, with its head on the second one
+#[derive(Debug, Clone)]
+struct ArrowElement<Coord> {
+    points : [Coord; 2],
+    head_size : i32,
+    style : ShapeStyle,
+}
+
+impl<'a, Coord> PointCollection<'a, Coord> for &'a ArrowElement<Coord> {
+    type Point = &'a Coord;
+    type IntoIter = std::slice::Iter<'a, Coord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        self.points.iter()
+    }
+}
+
+impl<Coord, DB : DrawingBackend> Drawable<DB> for ArrowElement<Coord> {
+    fn draw<I : Iterator<Item = BackendCoord>>(
+        &self,
+        mut points : I,
+        backend : &mut DB,
+        _ : (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let (Some(from), Some(to)) = (points.next(), points.next()) else {
+            return Ok(());
+        };
+        backend.draw_line(from, to, &self.style)?;
+
+        let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return Ok(());
+        }
+        // the head is a triangle pointing to the second coordinate
+        let size = self.head_size as f64;
+        let (ux, uy) = (dx / length, dy / length);
+        let base = (to.0 as f64 - ux * size, to.1 as f64 - uy * size);
+        let vertex = |side : f64| ((base.0 - uy * size * side / 2.0).round() as i32, (base.1 + ux * size * side / 2.0).round() as i32);
+        backend.fill_polygon(vec![to, vertex(1.0), vertex(-1.0)], &self.style)
+    }
+}
+
+
+/// draw the annotations on the chart of a subplot
+/// NOTE : the annotations outside the ranges of the axes are not drawn, the intervals are cut at the limits of the axes
+pub(crate) fn draw_annotations<X, Y>(
+    chart : &mut ChartContext<'_, BitMapBackend<'_>, Cartesian2d<X, Y>>,
+    annotations : &[Annotation],
+    style : &FigureStyle,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    X : Ranged,
+    Y : Ranged,
+    X::ValueType : From<f32> + PartialOrd + Clone + 'static,
+    Y::ValueType : From<f32> + PartialOrd + Clone + 'static,
+{
+    let (x_range, y_range) = (chart.x_range(), chart.y_range());
+    let contains_x = |x : f32| x_range.start <= X::ValueType::from(x) && X::ValueType::from(x) <= x_range.end;
+    let contains_y = |y : f32| y_range.start <= Y::ValueType::from(y) && Y::ValueType::from(y) <= y_range.end;
+    let clamp_x = |x : f32| if X::ValueType::from(x) < x_range.start {
+        x_range.start.clone()
+    } else if X::ValueType::from(x) > x_range.end {
+        x_range.end.clone()
+    } else {
+        X::ValueType::from(x)
+    };
+    let clamp_y = |y : f32| if Y::ValueType::from(y) < y_range.start {
+        y_range.start.clone()
+    } else if Y::ValueType::from(y) > y_range.end {
+        y_range.end.clone()
+    } else {
+        Y::ValueType::from(y)
+    };
+
+    let (dash_size, dash_spacing) = (style.px(6), style.px(4));
+    let offset = style.px(3) as i32;
+    for annotation in annotations.iter() {
+        let color = annotation.color.unwrap_or(style.foreground);
+        let line_style = style.get_line_style(&color, 1);
+        let font = style.get_label_font().color(&color);
+        let label = annotation.label.clone().unwrap_or_default();
+
+        match &annotation.kind {
+            AnnotationKind::HorizontalLine(y) if contains_y(*y) => {
+                let (start, end) = ((x_range.start.clone(), Y::ValueType::from(*y)), (x_range.end.clone(), Y::ValueType::from(*y)));
+                chart.draw_series(std::iter::once(DashedPathElement::new(vec![start, end.clone()], dash_size, dash_spacing, line_style)))?;
+                // the label is above the line, on the right
+                chart.draw_series(std::iter::once(
+                    EmptyElement::at(end) + Text::new(label, (-offset, -offset), font.pos(Pos::new(HPos::Right, VPos::Bottom)))
+                ))?;
+            },
+            AnnotationKind::VerticalLine(x) if contains_x(*x) => {
+                let (start, end) = ((X::ValueType::from(*x), y_range.start.clone()), (X::ValueType::from(*x), y_range.end.clone()));
+                chart.draw_series(std::iter::once(DashedPathElement::new(vec![start, end.clone()], dash_size, dash_spacing, line_style)))?;
+                // the label is on the right of the line, at the top
+                chart.draw_series(std::iter::once(
+                    EmptyElement::at(end) + Text::new(label, (offset, offset), font.pos(Pos::new(HPos::Left, VPos::Top)))
+                ))?;
+            },
+            AnnotationKind::XInterval(range) => {
+                let (start, end) = ((clamp_x(range.start), y_range.start.clone()), (clamp_x(range.end), y_range.end.clone()));
+                chart.draw_series(std::iter::once(Rectangle::new([start.clone(), end], color.mix(INTERVAL_OPACITY).filled())))?;
+                chart.draw_series(std::iter::once(
+                    EmptyElement::at((start.0, y_range.end.clone())) + Text::new(label, (offset, offset), font.pos(Pos::new(HPos::Left, VPos::Top)))
+                ))?;
+            },
+            AnnotationKind::YInterval(range) => {
+                let (start, end) = ((x_range.start.clone(), clamp_y(range.start)), (x_range.end.clone(), clamp_y(range.end)));
+                chart.draw_series(std::iter::once(Rectangle::new([start, end.clone()], color.mix(INTERVAL_OPACITY).filled())))?;
+                chart.draw_series(std::iter::once(
+                    EmptyElement::at(end) + Text::new(label, (-offset, offset), font.pos(Pos::new(HPos::Right, VPos::Top)))
+                ))?;
+            },
+            AnnotationKind::Text((x, y)) if contains_x(*x) && contains_y(*y) => {
+                chart.draw_series(std::iter::once(
+                    Text::new(label, (X::ValueType::from(*x), Y::ValueType::from(*y)), font.pos(Pos::new(HPos::Center, VPos::Center)))
+                ))?;
+            },
+            AnnotationKind::Arrow(from, to) if contains_x(from.0) && contains_y(from.1) && contains_x(to.0) && contains_y(to.1) => {
+                let from = (X::ValueType::from(from.0), Y::ValueType::from(from.1));
+                let to = (X::ValueType::from(to.0), Y::ValueType::from(to.1));
+                chart.draw_series(std::iter::once(ArrowElement {
+                    points : [from.clone(), to],
+                    head_size : style.px(10) as i32,
+                    style : line_style.filled(),
+                }))?;
+                // the label is at the start of the arrow
+                chart.draw_series(std::iter::once(
+                    EmptyElement::at(from) + Text::new(label, (0, -offset), font.pos(Pos::new(HPos::Center, VPos::Bottom)))
+                ))?;
+            },
+            _ => (),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+
+/// how the range of an axis is computed from the range of the data
+/// By default, the range of the axis is the range of the data
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisRange {
+    /// if Some, the range set by the user instead of the range of the data
+    pub range : Option<Range<f32>>,
+    /// if true, the points outside the user range are not drawn, otherwise the user range is extended to contain all the points
+    pub clip : bool,
+    /// if true, the range is extended to contain 0 (e.g. to compare the heights of the subplots)
+    pub include_zero : bool,
+    /// the fraction of the range of the data added on each side (not applied on a clipped user range)
+    pub padding : f32,
+}
+
+impl AxisRange {
+    /// the range of the data
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a range set by the user, the points outside are not drawn
+    /// NOTE : panic if start >= end
+    pub fn fixed(start : f32, end : f32) -> Self {
+        if start.is_nan() || end.is_nan() || start >= end {
+            panic!("The range of an axis must be increasing, got {}..{}", start, end);
+        }
+        Self {
+            range : Some(start..end),
+            ..Self::default()
+        }
+    }
+
+    /// extend the user range to contain all the points, instead of hiding the points outside
+    pub fn without_clip(mut self) -> Self {
+        self.clip = false;
+        self
+    }
+
+    pub fn with_include_zero(mut self) -> Self {
+        self.include_zero = true;
+        self
+    }
+
+    /// NOTE : panic if the padding is negative
+    pub fn with_padding(mut self, padding : f32) -> Self {
+        if padding.is_nan() || padding < 0.0 {
+            panic!("The padding of an axis must be positive, got {}", padding);
+        }
+        self.padding = padding;
+        self
+    }
+
+    /// get the range of the points to draw, None if all the points are drawn
+    pub fn get_clip_range(&self) -> Option<Range<f32>> {
+        self.range.clone().filter(|_| self.clip)
+    }
+
+    /// get the range of the axis from the range of the data
+    pub fn apply(&self, data_range : &Range<f32>) -> Range<f32> {
+        let (mut range, padding) = match self.range.as_ref() {
+            Some(range) if self.clip => (range.clone(), 0.0),
+            Some(range) => (range.start.min(data_range.start)..range.end.max(data_range.end), self.padding),
+            None => (data_range.clone(), self.padding),
+        };
+        if self.include_zero {
+            range = range.start.min(0.0)..range.end.max(0.0);
+        }
+        let padding = (range.end - range.start) * padding;
+        range.start - padding..range.end + padding
+    }
+}
+
+impl Default for AxisRange {
+    fn default() -> Self {
+        Self {
+            range : None,
+            clip : true,
+            include_zero : false,
+            padding : 0.0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_range() {
+        let data_range = 2.0..4.0;
+        assert_eq!(AxisRange::new().apply(&data_range), 2.0..4.0);
+        assert_eq!(AxisRange::new().with_include_zero().apply(&data_range), 0.0..4.0);
+        assert_eq!(AxisRange::new().with_padding(0.25).apply(&data_range), 1.5..4.5);
+
+        let fixed = AxisRange::fixed(3.0, 5.0);
+        assert_eq!(fixed.apply(&data_range), 3.0..5.0);
+        assert_eq!(fixed.get_clip_range(), Some(3.0..5.0));
+        assert_eq!(fixed.clone().without_clip().apply(&data_range), 2.0..5.0);
+        assert_eq!(fixed.without_clip().get_clip_range(), None);
+        assert_eq!(AxisRange::new().get_clip_range(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decreasing_fixed_range() {
+        AxisRange::fixed(1.0, 0.0);
+    }
+}
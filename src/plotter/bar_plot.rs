@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::coord::ranged1d::{DefaultFormatting, KeyPointHint, Ranged};
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::Rectangle;
+use plotters::style::{Color, ShapeStyle};
+
+use crate::data::filtering::Filters;
+use crate::data::plottable::{Plottable, ValuesByCategory};
+use crate::data::sample::key::SerieKey;
+use crate::data::sample::Sample;
+use crate::params::{BAR_GROUP_WIDTH};
+use crate::stat::outlier::OutlierStrategy;
+use crate::stat::stats_serie::{MetricName, StatsSerie};
+
+use super::annotation::draw_annotations;
+use super::figure_style::FigureStyle;
+use super::layout::Layout;
+use super::legend_style::{LegendOrder, LegendStyles};
+use super::subplot_options::{get_subplot_options, SubplotOptions};
+use super::utils::{collect_legends, get_legend_area_width, get_title_height, write_legend, write_title, LegendSymbol};
+
+
+/// how the bars of the legends are drawn in a category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BarMode {
+    /// one bar per legend, side by side
+    #[default]
+    Grouped,
+    /// the bars of the legends are stacked on top of each other
+    Stacked,
+}
+
+/// the order of the categories on the x axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CategoryOrder {
+    /// by name (numerically if all the categories are numbers)
+    #[default]
+    Name,
+    /// by increasing total value (sum of the legends)
+    ValueAscending,
+    /// by decreasing total value (sum of the legends)
+    ValueDescending,
+}
+
+
+/// a bar to draw : (legend index, (x0, y0), (x1, y1))
+type Bar = (usize, (f64, f64), (f64, f64));
+
+/// the aggregated values of each category, for each legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarData {
+    categories : Vec<String>,
+    legends : Vec<String>,
+    /// values[category][legend], NaN if the legend has no value in the category
+    values : Vec<Vec<f64>>,
+}
+
+impl BarData {
+    /// aggregate the values (category -> legend -> values) with the metric
+    /// NOTE : the outliers are removed in each (category, legend) group before the aggregation
+    /// NOTE : the categories are sorted by name, the legends are sorted alphabetically
+    pub fn new(
+        values_by_category : ValuesByCategory,
+        aggregation_metric : MetricName,
+        outliers : OutlierStrategy
+    ) -> Self {
+        let mut legends = values_by_category.values().flat_map(|by_legend| by_legend.keys().cloned()).collect::<Vec<String>>();
+        legends.sort();
+        legends.dedup();
+
+        let mut categories = Vec::new();
+        let mut values = Vec::new();
+        for (category, by_legend) in values_by_category.into_iter() {
+            let row = legends.iter().map(|legend| {
+                let Some(serie) = by_legend.get(legend) else {
+                    return f64::NAN;
+                };
+                let kept = serie.iter().zip(outliers.get_outliers_mask(serie))
+                    .filter(|(_, is_outlier)| !is_outlier)
+                    .map(|(v, _)| *v)
+                    .collect::<Vec<f32>>();
+                if kept.is_empty() {
+                    return f64::NAN;
+                }
+                StatsSerie::new(&kept).get_stats(aggregation_metric).value
+            }).collect();
+            categories.push(category);
+            values.push(row);
+        }
+
+        let mut self_ = BarData {
+            categories,
+            legends,
+            values,
+        };
+        self_.sort(CategoryOrder::Name);
+        self_
+    }
+
+    /// sort the categories
+    pub fn sort(&mut self, order : CategoryOrder) -> &mut Self {
+        let mut indexes = (0..self.categories.len()).collect::<Vec<usize>>();
+        match order {
+            CategoryOrder::Name => {
+                let numbers = self.categories.iter().map(|c| c.parse::<f64>().ok()).collect::<Option<Vec<f64>>>();
+                match numbers {
+                    Some(numbers) => indexes.sort_by(|a, b| numbers[*a].total_cmp(&numbers[*b])),
+                    None => indexes.sort_by(|a, b| self.categories[*a].cmp(&self.categories[*b])),
+                }
+            },
+            CategoryOrder::ValueAscending => indexes.sort_by(|a, b| self.get_total(*a).total_cmp(&self.get_total(*b))),
+            CategoryOrder::ValueDescending => indexes.sort_by(|a, b| self.get_total(*b).total_cmp(&self.get_total(*a))),
+        }
+
+        self.categories = indexes.iter().map(|i| self.categories[*i].clone()).collect();
+        self.values = indexes.iter().map(|i| self.values[*i].clone()).collect();
+        self
+    }
+
+    pub fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    pub fn get_legends(&self) -> &Vec<String> {
+        &self.legends
+    }
+
+    pub fn get_values(&self) -> &Vec<Vec<f64>> {
+        &self.values
+    }
+
+    /// get the value of the legend in the category
+    pub fn get(&self, category : &str, legend : &str) -> Option<f64> {
+        let i = self.categories.iter().position(|c| c == category)?;
+        let j = self.legends.iter().position(|l| l == legend)?;
+        Some(self.values[i][j]).filter(|v| !v.is_nan())
+    }
+
+    /// get the sum of the values of the legends in the category (the NaN values are ignored)
+    pub fn get_total(&self, category_index : usize) -> f64 {
+        self.values[category_index].iter().filter(|v| !v.is_nan()).sum()
+    }
+
+    /// get the range of the y axis for the mode, 0 is always included
+    pub fn get_value_range(&self, mode : BarMode) -> Range<f64> {
+        let (mut min, mut max) = (0.0_f64, 0.0_f64);
+        for row in self.values.iter() {
+            let row = row.iter().filter(|v| !v.is_nan());
+            match mode {
+                BarMode::Grouped => {
+                    min = row.clone().cloned().fold(min, f64::min);
+                    max = row.cloned().fold(max, f64::max);
+                },
+                BarMode::Stacked => {
+                    // the positive and the negative values are stacked apart
+                    min = min.min(row.clone().filter(|v| **v < 0.0).sum());
+                    max = max.max(row.filter(|v| **v > 0.0).sum());
+                },
+            }
+        }
+        if min == max {
+            return 0.0..1.0;
+        }
+        min..max
+    }
+
+    /// get the bars to draw, the category i is centered on i + 0.5
+    fn get_bars(&self, mode : BarMode) -> Vec<Bar> {
+        let nb_legends = self.legends.len().max(1);
+        let mut bars = Vec::new();
+        for (i, row) in self.values.iter().enumerate() {
+            let start = i as f64 + (1.0 - BAR_GROUP_WIDTH) / 2.0;
+            let (mut positive, mut negative) = (0.0, 0.0);
+            for (j, value) in row.iter().enumerate().filter(|(_, v)| !v.is_nan()) {
+                match mode {
+                    BarMode::Grouped => {
+                        let width = BAR_GROUP_WIDTH / nb_legends as f64;
+                        let x0 = start + j as f64 * width;
+                        bars.push((j, (x0, 0.0), (x0 + width, *value)));
+                    },
+                    BarMode::Stacked => {
+                        let base = if *value >= 0.0 { &mut positive } else { &mut negative };
+                        bars.push((j, (start, *base), (start + BAR_GROUP_WIDTH, *base + value)));
+                        *base += value;
+                    },
+                }
+            }
+        }
+        bars
+    }
+}
+
+
+/// the x axis of a bar plot : the category i is centered on i + 0.5, the key points are the centers of the categories
+struct CategoryCoord {
+    nb_categories : usize,
+}
+
+impl Ranged for CategoryCoord {
+    type FormatOption = DefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value : &f64, limit : (i32, i32)) -> i32 {
+        let size = self.nb_categories.max(1) as f64;
+        limit.0 + (value / size * (limit.1 - limit.0) as f64).round() as i32
+    }
+
+    fn key_points<Hint : KeyPointHint>(&self, hint : Hint) -> Vec<f64> {
+        // skip some categories if there are too many labels
+        let step = self.nb_categories.div_ceil(hint.max_num_points().max(1)).max(1);
+        (0..self.nb_categories).step_by(step).map(|i| i as f64 + 0.5).collect()
+    }
+
+    fn range(&self) -> Range<f64> {
+        0.0..self.nb_categories.max(1) as f64
+    }
+}
+
+
+/// plot the given data as bars, one group of bars per category
+/// take a list of series to plot, to the format (category_serie_key, y_serie_key, filter)
+/// The category key can be a string or a numeric key, the y values of each category are aggregated with the aggregation_metric
+/// If y_serie_key is None, the samples are counted (use MetricName::Additive)
+/// If filter is Some, the data will be filtered by the given key and the given function (true to keep the data)
+/// NOTE : the number of series to plot must be equal to the number of subplots (the cells of the layout)
+/// NOTE : options are the options of the subplots (bar mode, category order, y outliers, texts, axis ranges, annotations) : empty for the default options, one for every subplot, or one per subplot
+/// NOTE : an OutlierConfig can be given instead of the SubplotOptions, to set only the outliers
+/// return the aggregated values of each subplot
+#[allow(clippy::too_many_arguments)]
+pub fn bar_plot<'plot_lt, S, Key, Plot, Opt>(
+    data : &'plot_lt Plot,
+    legend_serie_key : Option<Key>,
+    save_path : &str,
+    layout : &Layout,
+    figure_style : &FigureStyle,
+
+    series : Vec<(Key, Option<Key>, Option<&'plot_lt Filters<Key>>)>,
+
+    options : &[Opt],
+    aggregation_metric : MetricName,
+) -> Result<Vec<BarData>, Box<dyn std::error::Error>>
+where
+    Opt : Clone + Into<SubplotOptions>,
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    if series.len() != layout.get_nb_of_subplots() {
+        panic!("The number of series to plot ({}) is not equal to the number of subplots ({})", series.len(), layout.get_nb_of_subplots());
+    }
+
+    // initialise the plotter
+    let image_path_o = Path::new(save_path);
+    // (w, h)
+    // the legend area is sized to fit all the legends
+    let legends = collect_legends(data, &legend_serie_key, series.iter().map(|(_, _, filters)| *filters));
+    let label_size = get_legend_area_width(&legends, &legend_serie_key, layout.get_size(figure_style.get_subplot_size()).1, figure_style);
+    let grid_size = layout.get_size(figure_style.get_subplot_size());
+    let global_size = (grid_size.0 + label_size, grid_size.1 + get_title_height(layout.get_title(), figure_style));
+
+    // global drawing
+    let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
+    root_drawing_area.fill(&figure_style.background)?;
+    // write the title above the subplots
+    let figure_drawing_area = write_title(&root_drawing_area, layout.get_title(), figure_style)?;
+    // isolate the label area
+    let (chart_drawing_area, label_drawing_area) =
+        figure_drawing_area.split_horizontally(global_size.0 - label_size);
+
+    // get the drawing area for each subplot (row, col)
+    let child_drawing_areas = layout.split(&chart_drawing_area);
+
+    let mut all_bar_data = Vec::new();
+
+    // associate each legend to a style
+    let mut legend_styles = LegendStyles::default();
+    let mut used_legends = HashSet::new();
+
+    // plot each serie
+    for (subplot_index, (
+            (category_serie_key, y_serie_key, filters), root)
+        )
+    in series.into_iter().zip(child_drawing_areas.iter()).enumerate() {
+
+        let subplot_options = get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index);
+
+        // get the data
+        let values_by_category = data.collect_by_category(category_serie_key, y_serie_key, legend_serie_key, filters);
+        let mut bar_data = BarData::new(values_by_category, aggregation_metric, subplot_options.outliers.y);
+        bar_data.sort(subplot_options.category_order);
+
+        // define the chart
+        let nb_categories = bar_data.categories.len();
+        let range_y = bar_data.get_value_range(subplot_options.bar_mode);
+        // keep a margin above (and below) the bars
+        let margin = (range_y.end - range_y.start) * 0.05;
+        let range_y = if range_y.start < 0.0 { range_y.start - margin } else { 0.0 }..range_y.end + margin;
+        let range_y = subplot_options.y_range.apply(&(range_y.start as f32..range_y.end as f32));
+        let range_y = range_y.start as f64..range_y.end as f64;
+
+        let y_serie_name = if let Some(y_serie_key) = y_serie_key {
+            format!("{} of {}", aggregation_metric, y_serie_key.get_display_name())
+        } else {
+            "count".to_string()
+        };
+
+        let caption = subplot_options.get_caption(|| format!("{} per {}", y_serie_name, category_serie_key.get_display_name()));
+        let (x_label, y_label) = subplot_options.get_axis_labels(category_serie_key.get_display_name(), y_serie_name);
+        let mut chart = ChartBuilder::on(root)
+            .caption(caption.as_str(), figure_style.get_caption_font())
+            .margin(figure_style.px(5))
+            .x_label_area_size(figure_style.px(40))
+            .y_label_area_size(figure_style.px(60))
+            .build_cartesian_2d(CategoryCoord { nb_categories }, range_y.clone())?;
+
+        let categories = bar_data.categories.clone();
+        // the values are formatted with the unit of the y key (the counts have no unit)
+        let y_unit = y_serie_key.filter(|_| aggregation_metric != MetricName::NbValues).map(|key| key.get_unit()).unwrap_or_default();
+        let mut mesh = chart.configure_mesh();
+        figure_style.configure_mesh(&mut mesh);
+        mesh.disable_x_mesh()
+            .x_desc(x_label)
+            .y_desc(y_label)
+            .x_labels(nb_categories)
+            .x_label_formatter(&|x| categories.get(x.floor() as usize).cloned().unwrap_or_default())
+            .y_label_formatter(&|y| y_unit.format(*y))
+            .draw()?;
+
+        // the colors follow the order of the legends
+        let styles = bar_data.legends.iter()
+            .map(|legend| legend_styles.get_style(legend).color.filled())
+            .collect::<Vec<ShapeStyle>>();
+        used_legends.extend(bar_data.legends.iter().cloned());
+
+        // plot the bars, cut at the limits of the y axis
+        let clamp = |(x, y) : (f64, f64)| (x, y.clamp(range_y.start, range_y.end));
+        chart
+            .draw_series(
+                bar_data.get_bars(subplot_options.bar_mode).into_iter()
+                    .map(|(legend_index, start, end)| Rectangle::new([clamp(start), clamp(end)], styles[legend_index])),
+            )?;
+        draw_annotations(&mut chart, &subplot_options.annotations, figure_style)?;
+
+        all_bar_data.push(bar_data);
+    }// end of for each serie
+
+    write_legend(&label_drawing_area, &legend_styles.get_entries(&used_legends, &LegendOrder::default()), &legend_serie_key, LegendSymbol::Marker, figure_style)?;
+
+    root_drawing_area.present()?;
+
+    Ok(all_bar_data)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn get_values() -> ValuesByCategory {
+        let mut values = ValuesByCategory::new();
+        values.entry("json".to_string()).or_default().insert("a".to_string(), vec![1.0, 3.0]);
+        values.entry("json".to_string()).or_default().insert("b".to_string(), vec![4.0]);
+        values.entry("csv".to_string()).or_default().insert("a".to_string(), vec![10.0, 10.0, 10.0, 1000.0]);
+        values.entry("xml".to_string()).or_default().insert("b".to_string(), vec![-1.0]);
+        values
+    }
+
+    #[test]
+    fn test_bar_data() {
+        let mut bar_data = BarData::new(get_values(), MetricName::Mean, OutlierStrategy::None);
+        assert_eq!(bar_data.get_categories(), &vec!["csv".to_string(), "json".to_string(), "xml".to_string()]);
+        assert_eq!(bar_data.get_legends(), &vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(bar_data.get("json", "a"), Some(2.0));
+        assert_eq!(bar_data.get("xml", "a"), None);
+        assert_eq!(bar_data.get_total(1), 6.0);
+
+        bar_data.sort(CategoryOrder::ValueAscending);
+        assert_eq!(bar_data.get_categories(), &vec!["xml".to_string(), "json".to_string(), "csv".to_string()]);
+        bar_data.sort(CategoryOrder::ValueDescending);
+        assert_eq!(bar_data.get_categories()[0], "csv");
+
+        // the outliers are removed before the aggregation
+        let bar_data = BarData::new(get_values(), MetricName::Mean, OutlierStrategy::Iqr(1.5));
+        assert_eq!(bar_data.get("csv", "a"), Some(10.0));
+    }
+
+    #[test]
+    fn test_numeric_categories_order() {
+        let mut values = ValuesByCategory::new();
+        for category in ["10", "9", "100"] {
+            values.entry(category.to_string()).or_default().insert("All".to_string(), vec![1.0]);
+        }
+        let bar_data = BarData::new(values, MetricName::Additive, OutlierStrategy::None);
+        assert_eq!(bar_data.get_categories(), &vec!["9".to_string(), "10".to_string(), "100".to_string()]);
+    }
+
+    #[test]
+    fn test_bars() {
+        let bar_data = BarData::new(get_values(), MetricName::Mean, OutlierStrategy::None);
+        assert_eq!(bar_data.get_value_range(BarMode::Grouped), -1.0..257.5);
+
+        let grouped = bar_data.get_bars(BarMode::Grouped);
+        assert_eq!(grouped.len(), 4);
+        // json : a then b side by side
+        assert_eq!(grouped[1], (0, (1.1, 0.0), (1.5, 2.0)));
+        assert_eq!(grouped[2], (1, (1.5, 0.0), (1.9, 4.0)));
+
+        let stacked = bar_data.get_bars(BarMode::Stacked);
+        // json : b on top of a
+        let (legend, start, end) = stacked[2];
+        assert_eq!((legend, start.1, end.1), (1, 2.0, 6.0));
+        assert_relative_eq!(end.0 - start.0, BAR_GROUP_WIDTH);
+        assert_eq!(bar_data.get_value_range(BarMode::Stacked), -1.0..257.5);
+    }
+}
@@ -0,0 +1,210 @@
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::coord::Shift;
+use plotters::drawing::DrawingArea;
+use plotters::element::Rectangle;
+use plotters::style::{Color, RGBColor};
+
+use crate::params::{HEATMAP_COLOR_BAR_NB_STEPS, HEATMAP_VALUE_FONT_SIZE};
+
+use super::figure_style::FigureStyle;
+use super::utils::format_number;
+
+
+/// the palettes to map a value to a color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorPalette {
+    /// blue -> white -> red, centered on the middle of the range (e.g. for correlations)
+    Diverging,
+    /// dark purple -> green -> yellow (viridis), for counts and densities
+    Sequential,
+}
+
+impl ColorPalette {
+    fn get_stops(&self) -> &'static [(u8, u8, u8)] {
+        match self {
+            ColorPalette::Diverging => &[
+                (59, 76, 192),
+                (141, 176, 254),
+                (247, 247, 247),
+                (244, 154, 123),
+                (180, 4, 38),
+            ],
+            ColorPalette::Sequential => &[
+                (68, 1, 84),
+                (59, 82, 139),
+                (33, 145, 140),
+                (94, 201, 98),
+                (253, 231, 37),
+            ],
+        }
+    }
+
+    /// get the color at the position (between 0 and 1), interpolated between the stops of the palette
+    pub fn get_color(&self, position : f64) -> RGBColor {
+        let stops = self.get_stops();
+        let scaled = position.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let index = (scaled.floor() as usize).min(stops.len() - 2);
+        let fraction = scaled - index as f64;
+        let (start, end) = (stops[index], stops[index + 1]);
+        let interpolate = |a : u8, b : u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+        RGBColor(interpolate(start.0, end.0), interpolate(start.1, end.1), interpolate(start.2, end.2))
+    }
+}
+
+
+/// map the values of a range to the colors of a palette
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorScale {
+    palette : ColorPalette,
+    min : f64,
+    max : f64,
+    /// if true, the position of a value is computed on its logarithm (the values <= 0 take the first color)
+    log : bool,
+}
+
+impl ColorScale {
+    pub fn new(palette : ColorPalette, min : f64, max : f64) -> Self {
+        ColorScale {
+            palette,
+            min,
+            max,
+            log : false,
+        }
+    }
+
+    /// the diverging scale between -1 and 1, for the correlations
+    pub fn new_correlation() -> Self {
+        ColorScale::new(ColorPalette::Diverging, -1.0, 1.0)
+    }
+
+    /// use a logarithmic scale
+    /// NOTE : the min is set to 1 if it is not strictly positive (e.g. for counts)
+    pub fn with_log(mut self) -> Self {
+        self.log = true;
+        if self.min <= 0.0 {
+            self.min = 1.0_f64.min(self.max);
+        }
+        self
+    }
+
+    pub fn get_palette(&self) -> ColorPalette {
+        self.palette
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    pub fn is_log(&self) -> bool {
+        self.log
+    }
+
+    /// get the position (between 0 and 1) of the value in the scale
+    pub fn get_position(&self, value : f64) -> f64 {
+        let (value, min, max) = if self.log {
+            if value <= 0.0 {
+                return 0.0;
+            }
+            (value.ln(), self.min.ln(), self.max.ln())
+        } else {
+            (value, self.min, self.max)
+        };
+        if max <= min {
+            return 0.5;
+        }
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    /// get the value at the position (between 0 and 1), the inverse of get_position
+    pub fn get_value(&self, position : f64) -> f64 {
+        if self.log {
+            (self.min.ln() + position * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + position * (self.max - self.min)
+        }
+    }
+
+    /// get the color of the value, None for a NaN value
+    pub fn get_color(&self, value : f64) -> Option<RGBColor> {
+        if value.is_nan() {
+            return None;
+        }
+        Some(self.palette.get_color(self.get_position(value)))
+    }
+}
+
+
+/// get a text color readable on the background color
+pub(crate) fn get_contrast_text_color(background : &RGBColor) -> RGBColor {
+    let luminance = 0.299 * background.0 as f64 + 0.587 * background.1 as f64 + 0.114 * background.2 as f64;
+    if luminance > 140.0 {
+        RGBColor(0, 0, 0)
+    } else {
+        RGBColor(255, 255, 255)
+    }
+}
+
+
+/// draw the color bar of the scale on the given drawing area : the y axis is the position in the scale, labelled with the values
+pub(crate) fn draw_color_bar(
+    color_bar_drawing_area : &DrawingArea<BitMapBackend<'_>, Shift>,
+    color_scale : &ColorScale,
+    figure_style : &FigureStyle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut color_bar = ChartBuilder::on(color_bar_drawing_area)
+        .margin_top(figure_style.px(figure_style.caption_font_size + 20))
+        .margin_bottom(figure_style.px(65))
+        .margin_left(figure_style.px(20))
+        .margin_right(figure_style.px(10))
+        .right_y_label_area_size(figure_style.px(70))
+        .build_cartesian_2d(0.0..1.0, 0.0..1.0)?;
+
+    let mut mesh = color_bar.configure_mesh();
+    figure_style.configure_mesh(&mut mesh);
+    mesh.disable_mesh()
+        .disable_x_axis()
+        .y_labels(6)
+        .label_style(figure_style.get_font(HEATMAP_VALUE_FONT_SIZE))
+        .y_label_formatter(&|position : &f64| format_number(color_scale.get_value(*position)))
+        .draw()?;
+
+    let step = 1.0 / HEATMAP_COLOR_BAR_NB_STEPS as f64;
+    color_bar.draw_series((0..HEATMAP_COLOR_BAR_NB_STEPS).map(|i| {
+        let position = i as f64 * step;
+        Rectangle::new(
+            [(0.0, position), (1.0, position + step)],
+            color_scale.get_palette().get_color(position + step / 2.0).filled(),
+        )
+    }))?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_scale() {
+        let scale = ColorScale::new_correlation();
+        assert_eq!(scale.get_color(-1.0), Some(RGBColor(59, 76, 192)));
+        assert_eq!(scale.get_color(0.0), Some(RGBColor(247, 247, 247)));
+        assert_eq!(scale.get_color(2.0), Some(RGBColor(180, 4, 38)));
+        assert_eq!(scale.get_color(f64::NAN), None);
+        assert_eq!(scale.get_value(0.75), 0.5);
+
+        let log_scale = ColorScale::new(ColorPalette::Sequential, 0.0, 100.0).with_log();
+        assert_eq!(log_scale.get_range(), (1.0, 100.0));
+        assert!((log_scale.get_position(10.0) - 0.5).abs() < 1e-12);
+        assert_eq!(log_scale.get_position(0.0), 0.0);
+        assert!((log_scale.get_value(0.5) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_contrast_text_color() {
+        assert_eq!(get_contrast_text_color(&RGBColor(253, 231, 37)), RGBColor(0, 0, 0));
+        assert_eq!(get_contrast_text_color(&RGBColor(68, 1, 84)), RGBColor(255, 255, 255));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::Circle;
+use plotters::series::{DashedLineSeries, LineSeries};
+use plotters::style::Color;
+
+use crate::data::filtering::Filters;
+use crate::data::plottable::Plottable;
+use crate::data::sample::key::SerieKey;
+use crate::data::sample::Sample;
+use crate::params::{ECDF_MAX_NB_POINTS, QQ_NB_QUANTILES};
+use crate::stat::stats_serie::StatsSerie;
+
+use super::figure_style::FigureStyle;
+use super::layout::Layout;
+use super::legend_style::{LegendOrder, LegendStyles};
+use super::utils::{collect_legends, format_number, format_number_f32, get_legend_area_width, get_title_height, write_legend, write_title, LegendSymbol};
+
+
+/// a (x, y) point
+type Point = (f32, f32);
+/// the step curve of each legend
+pub type LegendCurves = Vec<(String, Vec<Point>)>;
+
+/// collect the values of the key for each legend, on the filtered samples
+fn collect_values_by_legend<'plot_lt, S, Key, Plot>(
+    data : &'plot_lt Plot,
+    serie_key : Key,
+    legend_serie_key : Option<Key>,
+    filters : Option<&'plot_lt Filters<Key>>,
+) -> HashMap<String, StatsSerie>
+where
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    let mut values_by_legend : HashMap<String, Vec<f32>> = HashMap::new();
+    for (legend, (value, _)) in data.into_iter_with_filter((serie_key, None), legend_serie_key, filters) {
+        values_by_legend.entry(legend).or_default().push(value);
+    }
+    values_by_legend.into_iter().map(|(legend, values)| (legend, StatsSerie::new(&values))).collect()
+}
+
+/// get the points of the step curve of the ecdf, starting at the level start_level before the first value
+/// NOTE : the ecdf is reduced to about ECDF_MAX_NB_POINTS steps to accelerate the plotting
+fn get_ecdf_steps(ecdf : &[Point], start_level : f32) -> Vec<Point> {
+    let step = ecdf.len().div_ceil(ECDF_MAX_NB_POINTS).max(1);
+    let mut steps = Vec::new();
+    let mut level = start_level;
+    for (i, (x, y)) in ecdf.iter().enumerate() {
+        // always keep the last point
+        if i % step != 0 && i != ecdf.len() - 1 {
+            continue;
+        }
+        steps.push((*x, level));
+        steps.push((*x, *y));
+        level = *y;
+    }
+    steps
+}
+
+/// get the range of the values, extended if all the values are equal
+fn get_range(values : impl Iterator<Item = f32>) -> Range<f32> {
+    let (min, max) = values.fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)));
+    if min > max {
+        return 0.0..1.0;
+    }
+    if min == max {
+        return min - 0.5..max + 0.5;
+    }
+    min..max
+}
+
+
+/// plot the empirical cumulative distribution function (ECDF) of the values of a key, one curve per legend
+/// take a list of series to plot, to the format (serie_key, filter)
+/// If filter is Some, the data will be filtered by the given key and the given function (true to keep the data)
+/// If complementary is true, the complementary CDF (fraction of the values > x) is plotted on a log y axis, to show the tail of the distribution
+/// NOTE : the number of series to plot must be equal to the number of subplots (the cells of the layout)
+/// return the step curve of each legend for each subplot, the complementary cdf in log10
+pub fn ecdf_plot<'plot_lt, S, Key, Plot>(
+    data : &'plot_lt Plot,
+    legend_serie_key : Option<Key>,
+    save_path : &str,
+    layout : &Layout,
+    figure_style : &FigureStyle,
+
+    series : Vec<(Key, Option<&'plot_lt Filters<Key>>)>,
+
+    complementary : bool,
+) -> Result<Vec<LegendCurves>, Box<dyn std::error::Error>>
+where
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    if series.len() != layout.get_nb_of_subplots() {
+        panic!("The number of series to plot ({}) is not equal to the number of subplots ({})", series.len(), layout.get_nb_of_subplots());
+    }
+
+    // initialise the plotter
+    let image_path_o = Path::new(save_path);
+    // (w, h)
+    // the legend area is sized to fit all the legends
+    let legends = collect_legends(data, &legend_serie_key, series.iter().map(|(_, filters)| *filters));
+    let label_size = get_legend_area_width(&legends, &legend_serie_key, layout.get_size(figure_style.get_subplot_size()).1, figure_style);
+    let grid_size = layout.get_size(figure_style.get_subplot_size());
+    let global_size = (grid_size.0 + label_size, grid_size.1 + get_title_height(layout.get_title(), figure_style));
+
+    // global drawing
+    let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
+    root_drawing_area.fill(&figure_style.background)?;
+    // write the title above the subplots
+    let figure_drawing_area = write_title(&root_drawing_area, layout.get_title(), figure_style)?;
+    // isolate the label area
+    let (chart_drawing_area, label_drawing_area) =
+        figure_drawing_area.split_horizontally(global_size.0 - label_size);
+
+    // get the drawing area for each subplot (row, col)
+    let child_drawing_areas = layout.split(&chart_drawing_area);
+
+    // associate each legend to a style
+    let mut legend_styles = LegendStyles::default();
+    let mut used_legends = HashSet::new();
+    let mut all_curves = Vec::new();
+
+    // plot each serie
+    for ((serie_key, filters), root) in series.into_iter().zip(child_drawing_areas.iter()) {
+        let stats_by_legend = collect_values_by_legend(data, serie_key, legend_serie_key, filters);
+        let mut legends = stats_by_legend.keys().cloned().collect::<Vec<String>>();
+        legends.sort();
+
+        // the step curve of each legend, the complementary cdf is in log10 (the null values can't be drawn)
+        let curves = legends.iter().map(|legend| {
+            let ecdf = stats_by_legend[legend].get_ecdf();
+            let steps = if complementary {
+                let ccdf = ecdf.iter().map(|(x, y)| (*x, 1.0 - y)).collect::<Vec<Point>>();
+                get_ecdf_steps(&ccdf, 1.0).into_iter()
+                    .filter(|(_, y)| *y > 0.0)
+                    .map(|(x, y)| (x, y.log10()))
+                    .collect()
+            } else {
+                get_ecdf_steps(&ecdf, 0.0)
+            };
+            (legend.clone(), steps)
+        }).collect::<LegendCurves>();
+
+        // define the chart
+        let range_x = get_range(stats_by_legend.values().flat_map(|s| s.sorted_serie.iter().cloned()));
+        let range_y = if complementary {
+            let min = curves.iter().flat_map(|(_, steps)| steps.iter().map(|p| p.1)).fold(0.0_f32, f32::min);
+            (if min < 0.0 { min } else { -1.0 })..0.0
+        } else {
+            0.0..1.0
+        };
+
+        let (caption, y_desc) = if complementary {
+            (format!("complementary cdf of {}", serie_key.get_display_name()), "fraction of the values > x")
+        } else {
+            (format!("cdf of {}", serie_key.get_display_name()), "fraction of the values <= x")
+        };
+        let mut chart = ChartBuilder::on(root)
+            .caption(caption.as_str(), figure_style.get_caption_font())
+            .margin(figure_style.px(5))
+            .x_label_area_size(figure_style.px(40))
+            .y_label_area_size(figure_style.px(60))
+            .build_cartesian_2d(range_x, range_y)?;
+
+        let mut mesh = chart.configure_mesh();
+        figure_style.configure_mesh(&mut mesh);
+        // the values are formatted with the unit of the key
+        let unit = serie_key.get_unit();
+        mesh.x_desc(serie_key.get_display_name().as_str())
+            .y_desc(y_desc)
+            .x_label_formatter(&|x| unit.format(*x as f64))
+            .y_label_formatter(&|y| if complementary { format_number(10_f64.powf(*y as f64)) } else { format_number_f32(y) })
+            .draw()?;
+
+        // plot the curves
+        legend_styles.assign_sorted(legends.iter());
+        used_legends.extend(legends);
+        for (legend, steps) in curves.iter() {
+            let style = legend_styles.get_style(legend);
+
+            match style.dash.get_pattern() {
+                None => chart.draw_series(LineSeries::new(steps.iter().cloned(), figure_style.get_line_style(&style.color, 2)))?,
+                Some((size, spacing)) => chart.draw_series(
+                    DashedLineSeries::new(steps.iter().cloned(), figure_style.px(size), figure_style.px(spacing), figure_style.get_line_style(&style.color, 2))
+                )?,
+            };
+        }
+        all_curves.push(curves);
+    }// end of for each serie
+
+    write_legend(&label_drawing_area, &legend_styles.get_entries(&used_legends, &LegendOrder::default()), &legend_serie_key, LegendSymbol::Line, figure_style)?;
+
+    root_drawing_area.present()?;
+
+    Ok(all_curves)
+}
+
+
+/// get the pairs of quantiles of the two series, for QQ_NB_QUANTILES probabilities (or less if the series are smaller)
+pub fn get_quantile_pairs(serie_x : &StatsSerie, serie_y : &StatsSerie) -> Vec<Point> {
+    let nb_quantiles = QQ_NB_QUANTILES.min(serie_x.sorted_serie.len()).min(serie_y.sorted_serie.len());
+    (0..nb_quantiles).map(|i| {
+        let q = (i as f64 + 0.5) / nb_quantiles as f64;
+        (serie_x.get_quantile(q) as f32, serie_y.get_quantile(q) as f32)
+    }).collect()
+}
+
+/// plot the quantiles of the values of a key for two legends against each other (Q-Q plot)
+/// the points are on the dashed line y = x if the two legends have the same distribution
+/// take a list of series to plot, to the format (serie_key, filter)
+/// If filter is Some, the data will be filtered by the given key and the given function (true to keep the data)
+/// NOTE : legends are the values of the legend_serie_key compared, (x axis, y axis)
+/// NOTE : the number of series to plot must be equal to the number of subplots (the cells of the layout)
+/// return the pairs of quantiles of each subplot
+/// return an error if a legend has no value
+pub fn qq_plot<'plot_lt, S, Key, Plot>(
+    data : &'plot_lt Plot,
+    legend_serie_key : Key,
+    legends : (&str, &str),
+    save_path : &str,
+    layout : &Layout,
+    figure_style : &FigureStyle,
+
+    series : Vec<(Key, Option<&'plot_lt Filters<Key>>)>,
+) -> Result<Vec<Vec<Point>>, Box<dyn std::error::Error>>
+where
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    if series.len() != layout.get_nb_of_subplots() {
+        panic!("The number of series to plot ({}) is not equal to the number of subplots ({})", series.len(), layout.get_nb_of_subplots());
+    }
+
+    // get the quantiles of all the subplots before drawing, to fail without writing the image
+    let mut all_quantiles = Vec::new();
+    for (serie_key, filters) in series.into_iter() {
+        let stats_by_legend = collect_values_by_legend(data, serie_key, Some(legend_serie_key), filters);
+        let get_serie = |legend : &str| {
+            stats_by_legend.get(legend)
+                .ok_or_else(|| format!("No value of {} for the legend {}", serie_key.get_display_name(), legend))
+        };
+        all_quantiles.push((serie_key, get_quantile_pairs(get_serie(legends.0)?, get_serie(legends.1)?)));
+    }
+
+    // initialise the plotter
+    let image_path_o = Path::new(save_path);
+    // (w, h)
+    let grid_size = layout.get_size(figure_style.get_subplot_size());
+    let global_size = (grid_size.0, grid_size.1 + get_title_height(layout.get_title(), figure_style));
+
+    // global drawing
+    let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
+    root_drawing_area.fill(&figure_style.background)?;
+    // write the title above the subplots
+    let figure_drawing_area = write_title(&root_drawing_area, layout.get_title(), figure_style)?;
+
+    // get the drawing area for each subplot (row, col)
+    let child_drawing_areas = layout.split(&figure_drawing_area);
+    let color = LegendStyles::default().get_style_at(0).color;
+
+    // plot each serie
+    for ((serie_key, quantiles), root) in all_quantiles.iter().zip(child_drawing_areas.iter()) {
+        // define the chart, the same range on both axes to compare with y = x
+        let range = get_range(quantiles.iter().flat_map(|(x, y)| [*x, *y]));
+
+        let caption = format!("Q-Q plot of {}", serie_key.get_display_name());
+        let mut chart = ChartBuilder::on(root)
+            .caption(caption.as_str(), figure_style.get_caption_font())
+            .margin(figure_style.px(5))
+            .x_label_area_size(figure_style.px(40))
+            .y_label_area_size(figure_style.px(60))
+            .build_cartesian_2d(range.clone(), range.clone())?;
+
+        let mut mesh = chart.configure_mesh();
+        figure_style.configure_mesh(&mut mesh);
+        mesh.x_desc(format!("quantiles of {}", legends.0).as_str())
+            .y_desc(format!("quantiles of {}", legends.1).as_str())
+            .x_label_formatter(&|x| serie_key.get_unit().format(*x as f64))
+            .y_label_formatter(&|y| serie_key.get_unit().format(*y as f64))
+            .draw()?;
+
+        // the reference line
+        chart
+            .draw_series(
+                DashedLineSeries::new(vec![(range.start, range.start), (range.end, range.end)], figure_style.px(5), figure_style.px(5), figure_style.get_line_style(&figure_style.foreground.mix(0.5), 1)),
+            )?;
+
+        chart
+            .draw_series(
+                quantiles.iter()
+                    .map(|(x, y)| Circle::new((*x, *y), figure_style.px(3), color.filled())),
+            )?;
+    }// end of for each serie
+
+    root_drawing_area.present()?;
+
+    Ok(all_quantiles.into_iter().map(|(_, quantiles)| quantiles).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ecdf_steps() {
+        let ecdf = vec![(1.0, 0.5), (2.0, 1.0)];
+        assert_eq!(get_ecdf_steps(&ecdf, 0.0), vec![(1.0, 0.0), (1.0, 0.5), (2.0, 0.5), (2.0, 1.0)]);
+
+        // the large ecdf are reduced, the last point is kept
+        let large = (0..10 * ECDF_MAX_NB_POINTS).map(|i| (i as f32, (i + 1) as f32)).collect::<Vec<Point>>();
+        let steps = get_ecdf_steps(&large, 0.0);
+        assert!(steps.len() <= 2 * ECDF_MAX_NB_POINTS + 2);
+        assert_eq!(steps.last(), large.last());
+    }
+
+    #[test]
+    fn test_quantile_pairs() {
+        let serie_x = StatsSerie::new(&(0..100).map(|v| v as f32).collect());
+        let serie_y = StatsSerie::new(&(0..100).map(|v| 2.0 * v as f32).collect());
+        let pairs = get_quantile_pairs(&serie_x, &serie_y);
+        assert_eq!(pairs.len(), QQ_NB_QUANTILES.min(100));
+        assert!(pairs.iter().all(|(x, y)| (2.0 * x - y).abs() < 1e-3));
+
+        // limited by the smallest serie
+        let small = StatsSerie::new(&vec![1.0, 2.0]);
+        assert_eq!(get_quantile_pairs(&serie_x, &small).len(), 2);
+    }
+}
@@ -0,0 +1,226 @@
+use plotters::chart::{MeshStyle, SecondaryMeshStyle};
+use plotters::coord::ranged1d::{Ranged, ValueFormatter};
+use plotters::backend::DrawingBackend;
+use plotters::style::{Color, IntoFont, RGBColor, ShapeStyle, TextStyle};
+
+use crate::params::{FIGURE_CAPTION_FONT_SIZE, LABEL_FONT_SIZE, ONE_FIG_SIZE};
+
+
+/// the colors of a figure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Theme {
+    /// dark texts on a white background
+    #[default]
+    Light,
+    /// light texts on a dark background, to match a dark HTML report
+    Dark,
+}
+
+
+/// the style of the lines of the grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridStyle {
+    pub visible : bool,
+    pub color : RGBColor,
+    /// opacity of the lines at the labels
+    pub bold_opacity : f64,
+    /// opacity of the lines between the labels
+    pub light_opacity : f64,
+}
+
+impl GridStyle {
+    pub fn new(theme : Theme) -> Self {
+        match theme {
+            Theme::Light => GridStyle {
+                visible : true,
+                color : RGBColor(0, 0, 0),
+                bold_opacity : 0.2,
+                light_opacity : 0.1,
+            },
+            Theme::Dark => GridStyle {
+                visible : true,
+                color : RGBColor(255, 255, 255),
+                bold_opacity : 0.25,
+                light_opacity : 0.08,
+            },
+        }
+    }
+
+    /// hide the grid (the axes are still drawn)
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        GridStyle::new(Theme::default())
+    }
+}
+
+
+/// the style of a figure, given to every plot function : size, fonts and colors
+/// NOTE : all the sizes in pixels (subplots, fonts, margins, lines, markers) are multiplied by the scale factor, to get high-DPI images
+#[derive(Debug, Clone, PartialEq)]
+pub struct FigureStyle {
+    /// size of one subplot (w, h) in pixels, before the scale factor
+    pub subplot_size : (u32, u32),
+    /// factor applied to all the sizes (2.0 for a high-DPI image)
+    pub scale : f64,
+    pub font_family : String,
+    pub caption_font_size : u32,
+    /// font size of the labels of the axes and of the legends
+    pub label_font_size : u32,
+    pub background : RGBColor,
+    /// color of the texts and of the axes
+    pub foreground : RGBColor,
+    pub grid : GridStyle,
+}
+
+impl FigureStyle {
+    pub fn new(theme : Theme) -> Self {
+        let (background, foreground) = match theme {
+            Theme::Light => (RGBColor(255, 255, 255), RGBColor(0, 0, 0)),
+            Theme::Dark => (RGBColor(30, 30, 30), RGBColor(230, 230, 230)),
+        };
+        FigureStyle {
+            subplot_size : ONE_FIG_SIZE,
+            scale : 1.0,
+            font_family : "sans-serif".to_string(),
+            caption_font_size : FIGURE_CAPTION_FONT_SIZE,
+            label_font_size : LABEL_FONT_SIZE,
+            background,
+            foreground,
+            grid : GridStyle::new(theme),
+        }
+    }
+
+    pub fn dark() -> Self {
+        FigureStyle::new(Theme::Dark)
+    }
+
+    pub fn with_subplot_size(mut self, width : u32, height : u32) -> Self {
+        self.subplot_size = (width, height);
+        self
+    }
+
+    /// NOTE : panic if the scale factor isn't strictly positive
+    pub fn with_scale(mut self, scale : f64) -> Self {
+        if scale.is_nan() || scale <= 0.0 {
+            panic!("The scale factor must be strictly positive, got {}", scale);
+        }
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_font(mut self, font_family : &str, caption_font_size : u32, label_font_size : u32) -> Self {
+        self.font_family = font_family.to_string();
+        self.caption_font_size = caption_font_size;
+        self.label_font_size = label_font_size;
+        self
+    }
+
+    pub fn with_colors(mut self, background : RGBColor, foreground : RGBColor) -> Self {
+        self.background = background;
+        self.foreground = foreground;
+        self
+    }
+
+    pub fn with_grid(mut self, grid : GridStyle) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// get a size in pixels multiplied by the scale factor
+    pub fn px(&self, size : u32) -> u32 {
+        (size as f64 * self.scale).round() as u32
+    }
+
+    /// get the size of a subplot in pixels (w, h), with the scale factor
+    pub fn get_subplot_size(&self) -> (u32, u32) {
+        (self.px(self.subplot_size.0), self.px(self.subplot_size.1))
+    }
+
+    /// get the font of the given size (before the scale factor), in the foreground color
+    pub fn get_font(&self, size : u32) -> TextStyle<'_> {
+        (self.font_family.as_str(), self.px(size)).into_font().color(&self.foreground)
+    }
+
+    pub fn get_caption_font(&self) -> TextStyle<'_> {
+        self.get_font(self.caption_font_size)
+    }
+
+    pub fn get_label_font(&self) -> TextStyle<'_> {
+        self.get_font(self.label_font_size)
+    }
+
+    /// get the style of a line of the given width (before the scale factor)
+    pub fn get_line_style<C : Color>(&self, color : &C, width : u32) -> ShapeStyle {
+        color.stroke_width(self.px(width).max(1))
+    }
+
+    /// apply the fonts and the colors to the mesh of a chart
+    pub(crate) fn configure_mesh<'a, 'b, 's : 'b, X, Y, DB>(&'s self, mesh : &mut MeshStyle<'a, 'b, X, Y, DB>)
+    where
+        X : Ranged,
+        Y : Ranged,
+        DB : DrawingBackend,
+    {
+        mesh.label_style(self.get_label_font())
+            .axis_desc_style(self.get_label_font())
+            .axis_style(self.get_line_style(&self.foreground, 1))
+            .set_all_tick_mark_size(self.px(5))
+            .bold_line_style(self.get_line_style(&self.grid.color.mix(self.grid.bold_opacity), 1))
+            .light_line_style(self.get_line_style(&self.grid.color.mix(self.grid.light_opacity), 1));
+        if !self.grid.visible {
+            mesh.disable_mesh();
+        }
+    }
+
+    /// apply the fonts and the colors to the secondary axes of a chart (without grid)
+    pub(crate) fn configure_secondary_axes<'a, 'b, 's : 'b, X, Y, DB>(&'s self, axes : &mut SecondaryMeshStyle<'a, 'b, X, Y, DB>)
+    where
+        X : Ranged,
+        Y : Ranged,
+        X : ValueFormatter<X::ValueType>,
+        Y : ValueFormatter<Y::ValueType>,
+        DB : DrawingBackend,
+    {
+        axes.label_style(self.get_label_font())
+            .axis_desc_style(self.get_label_font())
+            .axis_style(self.get_line_style(&self.foreground, 1))
+            .set_all_tick_mark_size(self.px(5));
+    }
+}
+
+impl Default for FigureStyle {
+    fn default() -> Self {
+        FigureStyle::new(Theme::default())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_figure_style() {
+        let style = FigureStyle::default();
+        assert_eq!(style.get_subplot_size(), ONE_FIG_SIZE);
+        assert_eq!(style.background, RGBColor(255, 255, 255));
+
+        let style = FigureStyle::dark().with_subplot_size(400, 300).with_scale(2.0);
+        assert_eq!(style.get_subplot_size(), (800, 600));
+        assert_eq!(style.px(5), 10);
+        assert_eq!(style.background, RGBColor(30, 30, 30));
+        assert_eq!(style.grid.color, RGBColor(255, 255, 255));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_null_scale() {
+        FigureStyle::default().with_scale(0.0);
+    }
+}
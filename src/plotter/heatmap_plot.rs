@@ -0,0 +1,224 @@
+use std::ops::Range;
+use std::path::Path;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::coord::ranged1d::{IntoSegmentedCoord, SegmentValue};
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::{Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::Color;
+
+use crate::params::{COLOR_BAR_SIZE, HEATMAP_VALUE_FONT_SIZE};
+use crate::stat::correlation::CorrelationMatrix;
+use crate::stat::linspace::Linspace;
+
+use super::color_scale::{draw_color_bar, get_contrast_text_color, ColorPalette, ColorScale};
+use super::figure_style::FigureStyle;
+use super::utils::format_number;
+
+
+/// a 2D grid of values, with a label for each column (x) and each row (y)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapGrid {
+    x_labels : Vec<String>,
+    y_labels : Vec<String>,
+    /// values[row][column], the row 0 is drawn at the top
+    values : Vec<Vec<f64>>,
+}
+
+impl HeatmapGrid {
+    /// create a grid, values[row][column]
+    /// NOTE : panic if the size of the values doesn't match the labels
+    pub fn new(x_labels : Vec<String>, y_labels : Vec<String>, values : Vec<Vec<f64>>) -> Self {
+        if values.len() != y_labels.len() || values.iter().any(|row| row.len() != x_labels.len()) {
+            panic!("The size of the values doesn't match the labels ({} columns and {} rows)", x_labels.len(), y_labels.len());
+        }
+        HeatmapGrid {
+            x_labels,
+            y_labels,
+            values,
+        }
+    }
+
+    /// count the points in a grid of nb_bins (x, y) bins over the range of the points
+    /// NOTE : the rows are sorted by decreasing y (the highest y at the top), the labels are the centers of the bins
+    pub fn new_binned_counts(points : &[(f32, f32)], nb_bins : (usize, usize)) -> Self {
+        let (nb_x_bins, nb_y_bins) = (nb_bins.0.max(1), nb_bins.1.max(1));
+        let get_range = |values : Vec<f32>| {
+            let min = values.iter().cloned().fold(f32::MAX, f32::min);
+            let max = values.iter().cloned().fold(f32::MIN, f32::max);
+            if min > max { 0.0..1.0 } else if min == max { min - 0.5..max + 0.5 } else { min..max }
+        };
+        let x_range = get_range(points.iter().map(|p| p.0).collect());
+        let y_range = get_range(points.iter().map(|p| p.1).collect());
+
+        let mut values = vec![vec![0.0; nb_x_bins]; nb_y_bins];
+        let x_linspace = Linspace::new(x_range.start as f64, x_range.end as f64, nb_x_bins + 1);
+        let y_linspace = Linspace::new(y_range.start as f64, y_range.end as f64, nb_y_bins + 1);
+        for (x, y) in points.iter() {
+            let (Some(column), Some(row)) = (x_linspace.index_of(&(*x as f64)), y_linspace.index_of(&(*y as f64))) else {
+                continue;
+            };
+            // the max is in the last bin, the rows are reversed
+            values[nb_y_bins - 1 - row.min(nb_y_bins - 1)][column.min(nb_x_bins - 1)] += 1.0;
+        }
+
+        let get_centers = |range : &Range<f32>, nb : usize| {
+            let step = (range.end - range.start) / nb as f32;
+            (0..nb).map(|i| format_number((range.start + (i as f32 + 0.5) * step) as f64)).collect::<Vec<String>>()
+        };
+        let mut y_labels = get_centers(&y_range, nb_y_bins);
+        y_labels.reverse();
+
+        HeatmapGrid::new(get_centers(&x_range, nb_x_bins), y_labels, values)
+    }
+
+    pub fn get_x_labels(&self) -> &Vec<String> {
+        &self.x_labels
+    }
+
+    pub fn get_y_labels(&self) -> &Vec<String> {
+        &self.y_labels
+    }
+
+    pub fn get_values(&self) -> &Vec<Vec<f64>> {
+        &self.values
+    }
+
+    /// get the (min, max) of the values, without the NaN values
+    pub fn get_value_range(&self) -> (f64, f64) {
+        let values = self.values.iter().flatten().filter(|v| !v.is_nan());
+        let min = values.clone().cloned().fold(f64::MAX, f64::min);
+        let max = values.cloned().fold(f64::MIN, f64::max);
+        if min > max {
+            return (0.0, 1.0);
+        }
+        (min, max)
+    }
+}
+
+impl From<&CorrelationMatrix> for HeatmapGrid {
+    fn from(matrix : &CorrelationMatrix) -> Self {
+        HeatmapGrid::new(matrix.get_labels().clone(), matrix.get_labels().clone(), matrix.get_values().clone())
+    }
+}
+
+
+/// get the label of a segment
+fn get_segment_label(labels : &[String], segment : &SegmentValue<usize>) -> String {
+    match segment {
+        SegmentValue::CenterOf(i) => labels.get(*i).cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// plot the grid as a heatmap, with a color bar on the right
+/// If color_scale is None, a sequential scale over the range of the values is used
+/// NOTE : If show_values is true, the value is written in each cell
+/// NOTE : The NaN values are left blank
+/// NOTE : the figure has the size of one subplot of figure_style, plus the color bar
+pub fn heatmap_plot(
+    grid : &HeatmapGrid,
+    save_path : &str,
+    figure_style : &FigureStyle,
+    caption : &str,
+    color_scale : Option<ColorScale>,
+    show_values : bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let color_scale = color_scale.unwrap_or_else(|| {
+        let (min, max) = grid.get_value_range();
+        ColorScale::new(ColorPalette::Sequential, min, max)
+    });
+    let nb_columns = grid.x_labels.len();
+    let nb_rows = grid.y_labels.len();
+
+    // initialise the plotter
+    let image_path_o = Path::new(save_path);
+    // (w, h)
+    let color_bar_size = figure_style.px(COLOR_BAR_SIZE);
+    let global_size = (figure_style.get_subplot_size().0 + color_bar_size, figure_style.get_subplot_size().1);
+
+    // global drawing
+    let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
+    root_drawing_area.fill(&figure_style.background)?;
+    // isolate the color bar area
+    let (chart_drawing_area, color_bar_drawing_area) =
+        root_drawing_area.split_horizontally(global_size.0 - color_bar_size);
+
+    let mut chart = ChartBuilder::on(&chart_drawing_area)
+        .caption(caption, figure_style.get_caption_font())
+        .margin(figure_style.px(5))
+        .x_label_area_size(figure_style.px(60))
+        .y_label_area_size(figure_style.px(100))
+        // a segmented range has one segment per value of the range (bounds included)
+        .build_cartesian_2d((0..nb_columns.max(1) - 1).into_segmented(), (0..nb_rows.max(1) - 1).into_segmented())?;
+
+    let mut mesh = chart.configure_mesh();
+    figure_style.configure_mesh(&mut mesh);
+    mesh.disable_mesh()
+        .x_labels(nb_columns)
+        .y_labels(nb_rows)
+        .x_label_formatter(&|segment| get_segment_label(&grid.x_labels, segment))
+        // the row 0 is at the top
+        .y_label_formatter(&|segment| match segment {
+            SegmentValue::CenterOf(i) if *i < nb_rows => grid.y_labels[nb_rows - 1 - i].clone(),
+            _ => String::new(),
+        })
+        .draw()?;
+
+    // draw the cells
+    for (row, row_values) in grid.values.iter().enumerate() {
+        let y = nb_rows - 1 - row;
+        for (column, value) in row_values.iter().enumerate() {
+            let Some(color) = color_scale.get_color(*value) else {
+                continue;
+            };
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(SegmentValue::Exact(column), SegmentValue::Exact(y)), (SegmentValue::Exact(column + 1), SegmentValue::Exact(y + 1))],
+                color.filled(),
+            )))?;
+
+            if show_values {
+                let text_color = get_contrast_text_color(&color);
+                let style = figure_style.get_font(HEATMAP_VALUE_FONT_SIZE)
+                    .color(&text_color)
+                    .pos(Pos::new(HPos::Center, VPos::Center));
+                chart.draw_series(std::iter::once(Text::new(
+                    format_number(*value),
+                    (SegmentValue::CenterOf(column), SegmentValue::CenterOf(y)),
+                    style,
+                )))?;
+            }
+        }
+    }
+
+    draw_color_bar(&color_bar_drawing_area, &color_scale, figure_style)?;
+
+    root_drawing_area.present()?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binned_counts() {
+        let points = vec![(0.0, 0.0), (0.1, 0.1), (1.0, 1.0), (1.0, 0.0), (0.6, 0.9)];
+        let grid = HeatmapGrid::new_binned_counts(&points, (2, 2));
+        assert_eq!(grid.get_x_labels(), &vec!["0.25".to_string(), "0.75".to_string()]);
+        assert_eq!(grid.get_y_labels(), &vec!["0.75".to_string(), "0.25".to_string()]);
+        // the top row has the highest y
+        assert_eq!(grid.get_values(), &vec![vec![0.0, 2.0], vec![2.0, 1.0]]);
+        assert_eq!(grid.get_value_range(), (0.0, 2.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_grid() {
+        HeatmapGrid::new(vec!["a".to_string()], vec!["b".to_string()], vec![vec![1.0, 2.0]]);
+    }
+}
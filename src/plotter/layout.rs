@@ -1,8 +1,57 @@
+use std::ops::Range;
+
+use plotters::backend::DrawingBackend;
+use plotters::coord::Shift;
+use plotters::drawing::DrawingArea;
+
+
+/// the cell of a subplot in the grid of a layout, it can span several rows and columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubplotCell {
+    pub row : usize,
+    pub col : usize,
+    pub row_span : usize,
+    pub col_span : usize,
+}
+
+impl SubplotCell {
+    pub fn new(row : usize, col : usize) -> Self {
+        Self {
+            row,
+            col,
+            row_span : 1,
+            col_span : 1,
+        }
+    }
+
+    pub fn with_span(mut self, row_span : usize, col_span : usize) -> Self {
+        self.row_span = row_span;
+        self.col_span = col_span;
+        self
+    }
+
+    fn overlaps(&self, other : &SubplotCell) -> bool {
+        self.row < other.row + other.row_span && other.row < self.row + self.row_span
+            && self.col < other.col + other.col_span && other.col < self.col + self.col_span
+    }
+}
+
+
 /// define a layout for the subplots
+/// By default, each cell of the grid is a subplot, row by row
+/// NOTE : the shared axes are used by line_plot and scatter_plot
 #[derive(Debug, Clone)]
 pub struct Layout {
     pub height : usize,
     pub width : usize,
+    /// the title of the figure, above the subplots
+    title : Option<String>,
+    /// the cells of the subplots, in the order of the series (None for every cell of the grid)
+    cells : Option<Vec<SubplotCell>>,
+    /// the subplots in the same column (with the same span) have the same x range, the x labels are only on the bottom one
+    shared_x : bool,
+    /// the subplots in the same row (with the same span) have the same y range, the y labels are only on the left one
+    shared_y : bool,
 }
 
 impl Layout {
@@ -10,14 +59,191 @@ impl Layout {
         Self {
             height,
             width,
+            title : None,
+            cells : None,
+            shared_x : false,
+            shared_y : false,
         }
     }
+
+    /// get a grid as square as possible for the given number of subplots, the last cells are empty if needed
+    pub fn auto(nb_of_subplots : usize) -> Self {
+        let width = (nb_of_subplots as f64).sqrt().ceil().max(1.0) as usize;
+        let height = nb_of_subplots.div_ceil(width).max(1);
+        let layout = Self::new(width, height);
+        if nb_of_subplots == width * height {
+            return layout;
+        }
+        let cells = (0..nb_of_subplots).map(|index| SubplotCell::new(index / width, index % width)).collect();
+        layout.with_cells(cells)
+    }
+
+    /// place the subplots in the given cells, in the order of the series : the cells without subplot are empty
+    /// NOTE : panic if a cell is outside the grid, is empty or overlaps another cell
+    pub fn with_cells(mut self, cells : Vec<SubplotCell>) -> Self {
+        for (index, cell) in cells.iter().enumerate() {
+            if cell.row_span == 0 || cell.col_span == 0 {
+                panic!("The cell {:?} must span at least one row and one column", cell);
+            }
+            if cell.row + cell.row_span > self.height || cell.col + cell.col_span > self.width {
+                panic!("The cell {:?} is outside the grid ({} rows, {} columns)", cell, self.height, self.width);
+            }
+            if let Some(other) = cells[..index].iter().find(|other| other.overlaps(cell)) {
+                panic!("The cell {:?} overlaps the cell {:?}", cell, other);
+            }
+        }
+        self.cells = Some(cells);
+        self
+    }
+
+    pub fn with_title(mut self, title : &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_shared_x(mut self, shared_x : bool) -> Self {
+        self.shared_x = shared_x;
+        self
+    }
+
+    pub fn with_shared_y(mut self, shared_y : bool) -> Self {
+        self.shared_y = shared_y;
+        self
+    }
+
     /// get the plotter layout (row, col)
     pub fn get_plotter_layout(&self) -> (usize, usize) {
         (self.height, self.width)
     }
 
     pub fn get_nb_of_subplots(&self) -> usize {
-        self.height * self.width
+        match self.cells.as_ref() {
+            Some(cells) => cells.len(),
+            None => self.height * self.width,
+        }
+    }
+
+    /// get the cells of the subplots, in the order of the series
+    pub fn get_cells(&self) -> Vec<SubplotCell> {
+        match self.cells.as_ref() {
+            Some(cells) => cells.clone(),
+            None => (0..self.height * self.width).map(|index| SubplotCell::new(index / self.width, index % self.width)).collect(),
+        }
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn is_shared_x(&self) -> bool {
+        self.shared_x
+    }
+
+    pub fn is_shared_y(&self) -> bool {
+        self.shared_y
+    }
+
+    /// get the size in pixels of the grid (w, h), with the given size for one cell
+    pub fn get_size(&self, cell_size : (u32, u32)) -> (u32, u32) {
+        (self.width as u32 * cell_size.0, self.height as u32 * cell_size.1)
+    }
+
+    /// split the drawing area in the areas of the subplots, in the order of the series
+    pub(crate) fn split<DB : DrawingBackend>(&self, drawing_area : &DrawingArea<DB, Shift>) -> Vec<DrawingArea<DB, Shift>> {
+        let (width, height) = drawing_area.dim_in_pixel();
+        let x = |col : usize| (col as u32 * width) / self.width as u32;
+        let y = |row : usize| (row as u32 * height) / self.height as u32;
+        self.get_cells().iter().map(|cell| {
+            let (left, top) = (x(cell.col), y(cell.row));
+            let (right, bottom) = (x(cell.col + cell.col_span), y(cell.row + cell.row_span));
+            drawing_area.clone().shrink((left, top), (right - left, bottom - top))
+        }).collect()
     }
-}
\ No newline at end of file
+
+    /// give the same x range to the subplots sharing their x axis, and the same y range to the subplots sharing their y axis
+    /// NOTE : ranges are the (x, y) ranges of the subplots, in the order of the series
+    pub fn share_ranges(&self, ranges : &mut [(Range<f32>, Range<f32>)]) {
+        let cells = self.get_cells();
+        let union = |a : &Range<f32>, b : &Range<f32>| a.start.min(b.start)..a.end.max(b.end);
+        if self.shared_x {
+            let shared = (0..ranges.len()).map(|index| {
+                (0..ranges.len())
+                    .filter(|other| (cells[*other].col, cells[*other].col_span) == (cells[index].col, cells[index].col_span))
+                    .fold(ranges[index].0.clone(), |range, other| union(&range, &ranges[other].0))
+            }).collect::<Vec<Range<f32>>>();
+            ranges.iter_mut().zip(shared).for_each(|(range, shared)| range.0 = shared);
+        }
+        if self.shared_y {
+            let shared = (0..ranges.len()).map(|index| {
+                (0..ranges.len())
+                    .filter(|other| (cells[*other].row, cells[*other].row_span) == (cells[index].row, cells[index].row_span))
+                    .fold(ranges[index].1.clone(), |range, other| union(&range, &ranges[other].1))
+            }).collect::<Vec<Range<f32>>>();
+            ranges.iter_mut().zip(shared).for_each(|(range, shared)| range.1 = shared);
+        }
+    }
+
+    /// return true if the x labels of the subplot are drawn (always without shared x axis, only for the bottom subplot of the column otherwise)
+    pub fn has_x_labels(&self, subplot_index : usize) -> bool {
+        if !self.shared_x {
+            return true;
+        }
+        let cells = self.get_cells();
+        let cell = cells[subplot_index];
+        !cells.iter().any(|other| (other.col, other.col_span) == (cell.col, cell.col_span) && other.row > cell.row)
+    }
+
+    /// return true if the y labels of the subplot are drawn (always without shared y axis, only for the left subplot of the row otherwise)
+    pub fn has_y_labels(&self, subplot_index : usize) -> bool {
+        if !self.shared_y {
+            return true;
+        }
+        let cells = self.get_cells();
+        let cell = cells[subplot_index];
+        !cells.iter().any(|other| (other.row, other.row_span) == (cell.row, cell.row_span) && other.col < cell.col)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_layout() {
+        let layout = Layout::auto(3);
+        assert_eq!(layout.get_plotter_layout(), (2, 2));
+        assert_eq!(layout.get_nb_of_subplots(), 3);
+        assert_eq!(layout.get_cells()[2], SubplotCell::new(1, 0));
+
+        let layout = Layout::auto(6);
+        assert_eq!(layout.get_plotter_layout(), (2, 3));
+        assert_eq!(layout.get_nb_of_subplots(), 6);
+
+        assert_eq!(Layout::auto(1).get_plotter_layout(), (1, 1));
+    }
+
+    #[test]
+    fn test_shared_ranges() {
+        // a subplot on the whole first row, two subplots on the second row
+        let layout = Layout::new(2, 2)
+            .with_cells(vec![SubplotCell::new(0, 0).with_span(1, 2), SubplotCell::new(1, 0), SubplotCell::new(1, 1)])
+            .with_shared_x(true)
+            .with_shared_y(true);
+        let mut ranges = vec![(0.0..1.0, 0.0..10.0), (-1.0..1.0, 5.0..20.0), (2.0..3.0, -5.0..0.0)];
+        layout.share_ranges(&mut ranges);
+        assert_eq!(ranges, vec![(0.0..1.0, 0.0..10.0), (-1.0..1.0, -5.0..20.0), (2.0..3.0, -5.0..20.0)]);
+
+        assert!(layout.has_x_labels(0) && layout.has_x_labels(1));
+        assert!(layout.has_y_labels(1) && !layout.has_y_labels(2));
+
+        let layout = Layout::new(1, 2).with_shared_x(true);
+        assert!(!layout.has_x_labels(0) && layout.has_x_labels(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overlapping_cells() {
+        Layout::new(2, 2).with_cells(vec![SubplotCell::new(0, 0).with_span(2, 1), SubplotCell::new(1, 0)]);
+    }
+}
@@ -0,0 +1,472 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use plotters::element::{Drawable, PointCollection};
+use plotters::style::{Palette, RGBColor, ShapeStyle};
+use plotters_backend::{BackendCoord, DrawingBackend, DrawingErrorKind};
+
+use super::utils::CustomPalette;
+
+
+/// the palettes to give a color to each legend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LegendPalette {
+    /// 21 distinct colors
+    #[default]
+    Default,
+    /// Okabe-Ito palette (8 colors), safe for the color blind
+    ColorBlind,
+    /// Paul Tol bright palette (7 colors), safe for the color blind
+    Tol,
+    /// Tableau 10 palette
+    Tableau,
+}
+
+impl LegendPalette {
+    pub fn get_colors(&self) -> &'static [(u8, u8, u8)] {
+        match self {
+            LegendPalette::Default => CustomPalette::COLORS,
+            LegendPalette::ColorBlind => &[
+                (230, 159, 0),
+                (86, 180, 233),
+                (0, 158, 115),
+                (240, 228, 66),
+                (0, 114, 178),
+                (213, 94, 0),
+                (204, 121, 167),
+                (0, 0, 0),
+            ],
+            LegendPalette::Tol => &[
+                (68, 119, 170),
+                (102, 204, 238),
+                (34, 136, 51),
+                (204, 187, 68),
+                (238, 102, 119),
+                (170, 51, 119),
+                (187, 187, 187),
+            ],
+            LegendPalette::Tableau => &[
+                (31, 119, 180),
+                (255, 127, 14),
+                (44, 160, 44),
+                (214, 39, 40),
+                (148, 103, 189),
+                (140, 86, 75),
+                (227, 119, 194),
+                (127, 127, 127),
+                (188, 189, 34),
+                (23, 190, 207),
+            ],
+        }
+    }
+}
+
+
+/// the shape of the points of a legend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+    Cross,
+}
+
+impl MarkerShape {
+    /// all the shapes, in the order of assignment
+    pub const ALL : [MarkerShape; 5] = [MarkerShape::Circle, MarkerShape::Square, MarkerShape::Triangle, MarkerShape::Diamond, MarkerShape::Cross];
+
+    /// get the element drawing the marker of the given size (in pixels) at the coordinate
+    pub fn get_element<Coord>(&self, coord : Coord, size : i32, style : ShapeStyle) -> Marker<Coord> {
+        Marker {
+            coord,
+            shape : *self,
+            size,
+            style,
+        }
+    }
+}
+
+
+/// an element drawing a marker shape at a coordinate
+#[derive(Debug, Clone)]
+pub struct Marker<Coord> {
+    coord : Coord,
+    shape : MarkerShape,
+    size : i32,
+    style : ShapeStyle,
+}
+
+impl<'a, Coord> PointCollection<'a, Coord> for &'a Marker<Coord> {
+    type Point = &'a Coord;
+    type IntoIter = std::iter::Once<&'a Coord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.coord)
+    }
+}
+
+impl<Coord, DB : DrawingBackend> Drawable<DB> for Marker<Coord> {
+    fn draw<I : Iterator<Item = BackendCoord>>(
+        &self,
+        mut points : I,
+        backend : &mut DB,
+        _ : (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let Some((x, y)) = points.next() else {
+            return Ok(());
+        };
+        let size = self.size;
+        let filled = self.style.filled;
+        match self.shape {
+            MarkerShape::Circle => backend.draw_circle((x, y), size as u32, &self.style, filled),
+            MarkerShape::Square => backend.draw_rect((x - size, y - size), (x + size, y + size), &self.style, filled),
+            MarkerShape::Triangle | MarkerShape::Diamond => {
+                let size = size + 1;
+                let vertices = if self.shape == MarkerShape::Triangle {
+                    vec![(x, y - size), (x + size, y + size), (x - size, y + size)]
+                } else {
+                    vec![(x, y - size), (x + size, y), (x, y + size), (x - size, y)]
+                };
+                if filled {
+                    backend.fill_polygon(vertices, &self.style)
+                } else {
+                    let first = vertices[0];
+                    backend.draw_path(vertices.into_iter().chain(std::iter::once(first)), &self.style)
+                }
+            },
+            MarkerShape::Cross => {
+                let size = size + 1;
+                backend.draw_line((x - size, y - size), (x + size, y + size), &self.style)?;
+                backend.draw_line((x - size, y + size), (x + size, y - size), &self.style)
+            },
+        }
+    }
+}
+
+/// the dash pattern of the lines of a legend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineDash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineDash {
+    /// all the dashes, in the order of assignment
+    pub const ALL : [LineDash; 3] = [LineDash::Solid, LineDash::Dashed, LineDash::Dotted];
+
+    /// get the (size, spacing) of the dashes in pixels, None for a solid line
+    pub fn get_pattern(&self) -> Option<(u32, u32)> {
+        match self {
+            LineDash::Solid => None,
+            LineDash::Dashed => Some((10, 5)),
+            LineDash::Dotted => Some((2, 4)),
+        }
+    }
+
+    /// get the next dash in the order of assignment
+    pub fn next(&self) -> LineDash {
+        let index = LineDash::ALL.iter().position(|dash| dash == self).unwrap_or(0);
+        LineDash::ALL[(index + 1) % LineDash::ALL.len()]
+    }
+}
+
+
+/// the style of a legend : its color, the shape of its points and the dash of its lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LegendStyle {
+    pub color : RGBColor,
+    pub marker : MarkerShape,
+    pub dash : LineDash,
+}
+
+impl LegendStyle {
+    pub fn new(color : RGBColor) -> Self {
+        LegendStyle {
+            color,
+            marker : MarkerShape::default(),
+            dash : LineDash::default(),
+        }
+    }
+
+    pub fn with_marker(mut self, marker : MarkerShape) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    pub fn with_dash(mut self, dash : LineDash) -> Self {
+        self.dash = dash;
+        self
+    }
+
+    /// get the style of the line drawn against a secondary y axis : the same color with the next dash
+    pub fn get_secondary(&self) -> Self {
+        self.with_dash(self.dash.next())
+    }
+}
+
+
+/// the order of the entries of a legend
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LegendOrder {
+    /// the order of assignment of the styles (the legends given to LegendStyles::with_order, then the others sorted)
+    #[default]
+    Assignment,
+    /// the numbers in the labels are compared by value ("run 2" before "run 10")
+    Natural,
+    /// the labels which are numbers sorted by value, then the other labels in natural order
+    Numeric,
+    /// the given labels first, then the other labels in natural order
+    Custom(Vec<String>),
+}
+
+impl LegendOrder {
+    /// sort the entries (label, value) of a legend, keeping the order of assignment for LegendOrder::Assignment
+    pub fn sort<T>(&self, entries : &mut [(String, T)]) {
+        match self {
+            LegendOrder::Assignment => (),
+            LegendOrder::Natural => entries.sort_by(|a, b| natural_cmp(&a.0, &b.0)),
+            LegendOrder::Numeric => entries.sort_by(|a, b| numeric_cmp(&a.0, &b.0)),
+            LegendOrder::Custom(order) => {
+                let position = |label : &String| order.iter().position(|l| l == label).unwrap_or(order.len());
+                entries.sort_by(|a, b| position(&a.0).cmp(&position(&b.0)).then_with(|| natural_cmp(&a.0, &b.0)));
+            },
+        }
+    }
+}
+
+/// compare two labels, the numbers in the labels are compared by value ("run 2" before "run 10")
+pub fn natural_cmp(a : &str, b : &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let take_number = |chars : &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+                let number_a = take_number(&mut a_chars);
+                let number_b = take_number(&mut b_chars);
+                // compare the values without overflow : the longest number without the leading zeros is the greatest
+                let (trimmed_a, trimmed_b) = (number_a.trim_start_matches('0'), number_b.trim_start_matches('0'));
+                let ordering = trimmed_a.len().cmp(&trimmed_b.len())
+                    .then_with(|| trimmed_a.cmp(trimmed_b))
+                    .then_with(|| number_a.len().cmp(&number_b.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            },
+            (Some(ca), Some(cb)) => {
+                if ca != cb {
+                    return ca.cmp(&cb);
+                }
+                a_chars.next();
+                b_chars.next();
+            },
+        }
+    }
+}
+
+/// compare two labels, the numbers by value before the other labels in natural order
+fn numeric_cmp(a : &str, b : &str) -> Ordering {
+    match (a.parse::<f64>().ok(), b.parse::<f64>().ok()) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => natural_cmp(a, b),
+    }
+}
+
+
+/// associate a style to each legend
+/// The styles are assigned in order : the colors of the palette first, then the next marker shape and line dash with the colors again
+/// NOTE : the same LegendStyles can be given to several figures to keep the same styles
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LegendStyles {
+    palette : LegendPalette,
+    /// the legends, in the order of assignment
+    legends : Vec<String>,
+    styles : HashMap<String, LegendStyle>,
+}
+
+impl LegendStyles {
+    pub fn new(palette : LegendPalette) -> Self {
+        LegendStyles {
+            palette,
+            legends : Vec::new(),
+            styles : HashMap::new(),
+        }
+    }
+
+    /// assign the styles to the legends in the given order (before the legends found in the data)
+    pub fn with_order<I, L>(mut self, legends : I) -> Self
+    where
+        I : IntoIterator<Item = L>,
+        L : AsRef<str>,
+    {
+        for legend in legends {
+            self.get_style(legend.as_ref());
+        }
+        self
+    }
+
+    /// set the style of a legend
+    pub fn with_style(mut self, legend : &str, style : LegendStyle) -> Self {
+        if !self.styles.contains_key(legend) {
+            self.legends.push(legend.to_string());
+        }
+        self.styles.insert(legend.to_string(), style);
+        self
+    }
+
+    pub fn get_palette(&self) -> LegendPalette {
+        self.palette
+    }
+
+    /// get the legends, in the order of assignment
+    pub fn get_legends(&self) -> &Vec<String> {
+        &self.legends
+    }
+
+    /// get the style of the legend if it's already assigned
+    pub fn get(&self, legend : &str) -> Option<&LegendStyle> {
+        self.styles.get(legend)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.legends.is_empty()
+    }
+
+    /// get the style of the n-th legend assigned
+    pub fn get_style_at(&self, index : usize) -> LegendStyle {
+        let colors = self.palette.get_colors();
+        let (r, g, b) = colors[index % colors.len()];
+        let cycle = index / colors.len();
+        LegendStyle::new(RGBColor(r, g, b))
+            .with_marker(MarkerShape::ALL[cycle % MarkerShape::ALL.len()])
+            .with_dash(LineDash::ALL[cycle % LineDash::ALL.len()])
+    }
+
+    /// get the style of the legend, assigned to the next style if it's a new legend
+    pub fn get_style(&mut self, legend : &str) -> LegendStyle {
+        if let Some(style) = self.styles.get(legend) {
+            return *style;
+        }
+        let style = self.get_style_at(self.legends.len());
+        self.legends.push(legend.to_string());
+        self.styles.insert(legend.to_string(), style);
+        style
+    }
+
+    /// get the legends used with their style in the given order (to draw the legend)
+    pub(crate) fn get_entries(&self, used_legends : &HashSet<String>, order : &LegendOrder) -> Vec<(String, LegendStyle)> {
+        let mut entries = self.legends.iter()
+            .filter(|legend| used_legends.contains(*legend))
+            .map(|legend| (legend.clone(), self.styles[legend]))
+            .collect::<Vec<(String, LegendStyle)>>();
+        order.sort(&mut entries);
+        entries
+    }
+
+    /// assign the styles of the new legends in sorted order (numerically if all the legends are numbers, in natural order otherwise)
+    /// NOTE : used to have the same styles whatever the order of the data
+    pub fn assign_sorted<'a, I>(&mut self, legends : I)
+    where
+        I : IntoIterator<Item = &'a String>,
+    {
+        let mut new_legends = legends.into_iter().filter(|l| !self.styles.contains_key(*l)).collect::<Vec<&String>>();
+        if new_legends.iter().all(|l| l.parse::<f64>().is_ok()) {
+            new_legends.sort_by(|a, b| numeric_cmp(a, b));
+        } else {
+            new_legends.sort_by(|a, b| natural_cmp(a, b));
+        }
+        new_legends.dedup();
+        for legend in new_legends {
+            self.get_style(legend);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style_assignment() {
+        let mut styles = LegendStyles::new(LegendPalette::Tol);
+        let legends = ["b".to_string(), "c".to_string(), "a".to_string()];
+        styles.assign_sorted(legends.iter());
+        assert_eq!(styles.get_legends(), &vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(styles.get("a").unwrap().color, RGBColor(68, 119, 170));
+        assert_eq!(styles.get_style("b").color, RGBColor(102, 204, 238));
+
+        // the order doesn't change for the known legends
+        styles.assign_sorted(["0".to_string(), "a".to_string()].iter());
+        assert_eq!(styles.get_legends()[3], "0");
+
+        // after the colors, the marker and the dash change
+        let style = styles.get_style_at(7);
+        assert_eq!(style.color, RGBColor(68, 119, 170));
+        assert_eq!((style.marker, style.dash), (MarkerShape::Square, LineDash::Dashed));
+
+        // the secondary line has the same color with the next dash
+        assert_eq!(style.get_secondary().color, style.color);
+        assert_eq!(style.get_secondary().dash, LineDash::Dotted);
+        assert_eq!(LineDash::Dotted.next(), LineDash::Solid);
+    }
+
+    #[test]
+    fn test_user_styles() {
+        let red = LegendStyle::new(RGBColor(255, 0, 0)).with_marker(MarkerShape::Cross);
+        let mut styles = LegendStyles::new(LegendPalette::ColorBlind)
+            .with_order(["10", "2"])
+            .with_style("custom", red);
+        assert_eq!(styles.get_style("custom"), red);
+        assert_eq!(styles.get_style("2").color, RGBColor(86, 180, 233));
+
+        // the numbers are sorted numerically
+        let mut numbers = LegendStyles::default();
+        numbers.assign_sorted(["10".to_string(), "9".to_string()].iter());
+        assert_eq!(numbers.get_legends(), &vec!["9".to_string(), "10".to_string()]);
+    }
+
+    #[test]
+    fn test_legend_order() {
+        assert_eq!(natural_cmp("run 2", "run 10"), Ordering::Less);
+        assert_eq!(natural_cmp("v1.10", "v1.9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a02", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp("b", "a10"), Ordering::Greater);
+        assert_eq!(natural_cmp("99999999999999999999999", "100000000000000000000000"), Ordering::Less);
+
+        let entries = |labels : &[&str]| labels.iter().map(|l| (l.to_string(), ())).collect::<Vec<(String, ())>>();
+        let labels = |entries : Vec<(String, ())>| entries.into_iter().map(|(l, _)| l).collect::<Vec<String>>();
+
+        let mut natural = entries(&["run 10", "run 2", "baseline"]);
+        LegendOrder::Natural.sort(&mut natural);
+        assert_eq!(labels(natural), vec!["baseline", "run 2", "run 10"]);
+
+        // the labels which aren't numbers don't panic
+        let mut numeric = entries(&["10", "n/a", "-1.5", "2"]);
+        LegendOrder::Numeric.sort(&mut numeric);
+        assert_eq!(labels(numeric), vec!["-1.5", "2", "10", "n/a"]);
+
+        let mut custom = entries(&["a", "b", "c", "d"]);
+        LegendOrder::Custom(vec!["c".to_string(), "unknown".to_string(), "a".to_string()]).sort(&mut custom);
+        assert_eq!(labels(custom), vec!["c", "a", "b", "d"]);
+
+        let mut assignment = entries(&["b", "a"]);
+        LegendOrder::Assignment.sort(&mut assignment);
+        assert_eq!(labels(assignment), vec!["b", "a"]);
+    }
+}
@@ -1,48 +1,269 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::Path;
 
 use plotters::backend::BitMapBackend;
 use plotters::chart::ChartBuilder;
 use plotters::drawing::IntoDrawingArea;
-use plotters::series::LineSeries;
-use plotters::style::{Color, IntoFont, Palette, PaletteColor, WHITE};
+use plotters::element::Circle;
+use plotters::series::{DashedLineSeries, LineSeries};
+use plotters::style::Color;
 
 use crate::data::filtering::Filters;
 use crate::data::plottable::Plottable;
 use crate::data::sample::key::SerieKey;
 use crate::data::sample::Sample;
-use crate::params::{FIGURE_CAPTION_FONT_SIZE, LABEL_HORIZONTAL_SIZE, ONE_FIG_SIZE};
+use crate::params::{OUTLIER_OPACITY, RAW_DATA_OPACITY};
+use crate::stat::fitting::{FitModel, FitResult};
 use crate::stat::stats_serie::MetricName;
 
+use super::annotation::draw_annotations;
+use super::axis_range::AxisRange;
+use super::figure_style::FigureStyle;
 use super::layout::Layout;
+use super::legend_style::LegendStyle;
 use super::plot_data::PlotData;
-use super::utils::{format_number_f32, write_legend, CustomPalette};
+use super::subplot_options::{get_subplot_options, LegendOptions, SubplotOptions};
+use super::utils::{collect_legends, get_fit_curve_segments, get_legend_area_width, get_title_height, write_legend, write_subplot_legend, write_title, LegendSymbol};
 
 
 
+/// a serie of a line plot : the x key, the y key (None to count the values) and the filters
+/// If secondary_y_serie_key is Some, a second metric is drawn against a y axis on the right, with its own range
+/// NOTE : the tuples (x_serie_key, y_serie_key, filters) are converted to a serie without secondary y axis
+#[derive(Clone)]
+pub struct LineSerie<'a, Key : SerieKey> {
+    pub x_serie_key : Key,
+    pub y_serie_key : Option<Key>,
+    pub secondary_y_serie_key : Option<Key>,
+    pub filters : Option<&'a Filters<Key>>,
+}
 
+impl<'a, Key : SerieKey> LineSerie<'a, Key> {
+    pub fn new(x_serie_key : Key, y_serie_key : Option<Key>, filters : Option<&'a Filters<Key>>) -> Self {
+        LineSerie {
+            x_serie_key,
+            y_serie_key,
+            secondary_y_serie_key : None,
+            filters,
+        }
+    }
+
+    pub fn with_secondary_y(mut self, secondary_y_serie_key : Key) -> Self {
+        self.secondary_y_serie_key = Some(secondary_y_serie_key);
+        self
+    }
+}
+
+impl<'a, Key : SerieKey> From<(Key, Option<Key>, Option<&'a Filters<Key>>)> for LineSerie<'a, Key> {
+    fn from((x_serie_key, y_serie_key, filters) : (Key, Option<Key>, Option<&'a Filters<Key>>)) -> Self {
+        LineSerie::new(x_serie_key, y_serie_key, filters)
+    }
+}
+
+
+/// the data of a line (one line per legend), computed before drawing
+struct LineData {
+    plot_data : PlotData,
+    raw_plot_data : Option<PlotData>,
+    fits : HashMap<String, FitResult>,
+}
+
+/// the data of the secondary y axis of a subplot
+struct SecondaryLine<Key> {
+    y_serie_key : Key,
+    line_data : LineData,
+    range_y : Range<f32>,
+}
+
+/// the data of a subplot, computed before drawing
+struct LineSubplot<Key> {
+    x_serie_key : Key,
+    y_serie_key : Option<Key>,
+    legend_serie_key : Option<Key>,
+    line_data : LineData,
+    secondary : Option<SecondaryLine<Key>>,
+}
+
+
+/// get the data of a line : aggregated, fitted, smoothed, clipped to the ranges set by the user and compressed (sorted by x)
+#[allow(clippy::too_many_arguments)]
+fn get_line_data<S, Key, Plot>(
+    data : &Plot,
+    serie_keys : (Key, Option<Key>),
+    legend_serie_key : Option<Key>,
+    filters : Option<&Filters<Key>>,
+    subplot_options : &SubplotOptions,
+    fit_model : Option<FitModel>,
+    y_range : &AxisRange,
+    aggregation_metric : &MetricName,
+) -> LineData
+where
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    let data_it = data.into_iter_with_filter(
+        serie_keys, 
+        legend_serie_key, 
+        filters
+    );
+    let mut plot_data = PlotData::from_it_without_compression(data_it, Some(*aggregation_metric), subplot_options.outliers);
+    // fit on all the points, before the compression
+    let fits = match fit_model {
+        Some(model) => plot_data.fit(model),
+        None => HashMap::new(),
+    };
+    // smooth after the aggregation, keeping the raw data to draw it underneath
+    let mut raw_plot_data = None;
+    if let Some(smoothing) = subplot_options.smoothing {
+        if subplot_options.show_raw {
+            let mut raw = plot_data.clone();
+            raw.compress_with(&subplot_options.compression);
+            raw_plot_data = Some(raw);
+        }
+        plot_data.smooth(smoothing);
+    }
+    // hide the points outside the ranges set by the user
+    let clip_ranges = (subplot_options.x_range.get_clip_range(), y_range.get_clip_range());
+    plot_data.clip(clip_ranges.0.as_ref(), clip_ranges.1.as_ref());
+    if let Some(raw_plot_data) = raw_plot_data.as_mut() {
+        raw_plot_data.clip(clip_ranges.0.as_ref(), clip_ranges.1.as_ref());
+    }
+    // the compressed series are sorted by x, to be drawn as lines
+    plot_data.compress_with(&subplot_options.compression);
+
+    LineData { plot_data, raw_plot_data, fits }
+}
+
+/// get the label of the secondary y axis of a subplot, used to name the secondary lines in the legend
+fn get_secondary_y_label<Key : SerieKey>(serie : &LineSerie<'_, Key>, subplot_options : &SubplotOptions) -> Option<String> {
+    serie.secondary_y_serie_key
+        .map(|key| subplot_options.secondary_y_label.clone().unwrap_or_else(|| key.get_display_name()))
+}
+
+/// add the entries of the secondary lines to the entries of the legend
+/// each legend is followed by one entry "legend (label)" per label of a secondary y axis, with the style given by get_secondary
+fn add_secondary_entries<T>(
+    entries : Vec<(String, T)>,
+    secondary_y_labels : &[String],
+    get_secondary : impl Fn(&T) -> T,
+) -> Vec<(String, T)>
+{
+    let mut labelled_entries = Vec::new();
+    for (legend, value) in entries {
+        for secondary_y_label in secondary_y_labels.iter() {
+            labelled_entries.push((format!("{} ({})", legend, secondary_y_label), get_secondary(&value)));
+        }
+        labelled_entries.insert(labelled_entries.len() - secondary_y_labels.len(), (legend, value));
+    }
+    // the same label can be on several subplots
+    let mut seen = HashSet::new();
+    labelled_entries.retain(|(label, _)| seen.insert(label.clone()));
+    labelled_entries
+}
+
+/// get the entries of the legend drawn inside a subplot
+/// NOTE : without legend key, the single legend "All" names nothing : only the secondary line is named, by the label of its axis
+fn get_subplot_entries<T>(
+    entries : Vec<(String, T)>,
+    has_legend_key : bool,
+    secondary_y_labels : &[String],
+    get_secondary : impl Fn(&T) -> T,
+) -> Vec<(String, T)>
+{
+    if has_legend_key {
+        return add_secondary_entries(entries, secondary_y_labels, get_secondary);
+    }
+    entries.iter()
+        .flat_map(|(_, value)| secondary_y_labels.iter().map(|secondary_y_label| (secondary_y_label.clone(), get_secondary(value))))
+        .collect()
+}
+
+/// get the data of a subplot and the ranges of its axes (x, primary y), before sharing the ranges
+#[allow(clippy::too_many_arguments)]
+fn get_line_subplot<S, Key, Plot>(
+    data : &Plot,
+    serie : LineSerie<'_, Key>,
+    legend_serie_key : Option<Key>,
+    subplot_options : &SubplotOptions,
+    aggregation_metric : &MetricName,
+) -> (LineSubplot<Key>, (Range<f32>, Range<f32>))
+where
+    Key : SerieKey,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'a> &'a Plot: IntoIterator<Item = S>,
+{
+    let LineSerie { x_serie_key, y_serie_key, secondary_y_serie_key, filters } = serie;
+
+    // get the data
+    let line_data = get_line_data(
+        data,
+        (x_serie_key, y_serie_key),
+        legend_serie_key,
+        filters,
+        subplot_options,
+        subplot_options.fit_model,
+        &subplot_options.y_range,
+        aggregation_metric,
+    );
+
+    // the ranges of the axes, from the range of the data and the options
+    let (mut range_x, range_y) = line_data.plot_data.get_range();
+    // the secondary y axis has its own range, the x axis contains both metrics
+    let secondary = secondary_y_serie_key.map(|secondary_y_serie_key| {
+        let secondary_line_data = get_line_data(
+            data,
+            (x_serie_key, Some(secondary_y_serie_key)),
+            legend_serie_key,
+            filters,
+            subplot_options,
+            None,
+            &subplot_options.secondary_y_range,
+            aggregation_metric,
+        );
+        let (secondary_range_x, secondary_range_y) = secondary_line_data.plot_data.get_range();
+        range_x = range_x.start.min(secondary_range_x.start)..range_x.end.max(secondary_range_x.end);
+        SecondaryLine {
+            y_serie_key : secondary_y_serie_key,
+            line_data : secondary_line_data,
+            range_y : subplot_options.secondary_y_range.apply(&secondary_range_y),
+        }
+    });
+    let ranges = (subplot_options.x_range.apply(&range_x), subplot_options.y_range.apply(&range_y));
 
+    (LineSubplot { x_serie_key, y_serie_key, legend_serie_key, line_data, secondary }, ranges)
+}
 
 
 /// plot the given data as a line
-/// take a list of series to plot, to the format (x_serie_key, y_serie_key, filter)
+/// take a list of series to plot, to the format (x_serie_key, y_serie_key, filter) or LineSerie
 /// If filter is Some, the data will be filtered by the given key and the given function (true to keep the data))
-/// NOTE : the number of series to plot must be equal to the number of subplots
-/// NOTE : If remove_outliers is Some, the outliers will be removed from the data with the given key
+/// NOTE : the number of series to plot must be equal to the number of subplots (the cells of the layout)
+/// NOTE : legend_serie_keys is one legend key for all the subplots, or one per subplot (LegendKeys) : the subplots with different legend keys have their own colors and legend
+/// NOTE : options are the options of the subplots (outliers, fit, smoothing, texts, axis ranges, annotations) : empty for the default options, one for every subplot, or one per subplot
+/// NOTE : an OutlierConfig can be given instead of the SubplotOptions, to set only the outliers
+/// NOTE : the lines of a secondary y key are dashed like LegendStyle::get_secondary (with hollow outliers), and named "legend (secondary y label)" in the legend ; the fits and annotations are on the primary y axis only
+/// return the fits of each subplot (empty if there is no fit_model), to show the coefficients in a table with FitResult::to_array
 /// NOTE : The aggregation_metrics is the metric used to aggregate the data with the same x value
-pub fn line_plot<'plot_lt, S, Key, Plot>(
+#[allow(clippy::too_many_arguments)]
+pub fn line_plot<'plot_lt, S, Key, Plot, Opt>(
     data : &'plot_lt Plot, 
-    legend_serie_key : Option<Key>,
+    legend : impl Into<LegendOptions<Key>>,
     save_path : &str,
     layout : &Layout,
+    figure_style : &FigureStyle,
 
-    series : Vec<(Key, Option<Key>, Option<&'plot_lt Filters<Key>>)>,
+    series : Vec<impl Into<LineSerie<'plot_lt, Key>>>,
     
-    remove_outlier : bool,
+    options : &[Opt],
     aggregation_metric : MetricName,
-) -> Result<(), Box<dyn std::error::Error>> 
+) -> Result<Vec<HashMap<String, FitResult>>, Box<dyn std::error::Error>> 
 where
-    Key : SerieKey,
+    Opt : Clone + Into<SubplotOptions>,
+    Key : SerieKey + 'plot_lt,
     S : Sample<Key>,
     Plot : Plottable<S, Key>,
     for<'a> &'a Plot: IntoIterator<Item = S>,
@@ -50,89 +271,280 @@ where
     if series.len() != layout.get_nb_of_subplots() {
         panic!("The number of series to plot ({}) is not equal to the number of subplots ({})", series.len(), layout.get_nb_of_subplots());
     }
+    let series = series.into_iter().map(|serie| serie.into()).collect::<Vec<LineSerie<Key>>>();
+
+    // the labels of the secondary y axes, named in the legend (None for the subplots without secondary y axis)
+    let secondary_y_labels = series.iter().enumerate()
+        .map(|(subplot_index, serie)| get_secondary_y_label(serie, &get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index)))
+        .collect::<Vec<Option<String>>>();
 
     // initialise the plotter
     let image_path_o = Path::new(save_path);
+    // the subplots with the same legend key share the legend on the right, otherwise each subplot has its own legend
+    let legend = legend.into();
+    let legend_serie_keys = &legend.keys;
+    let shared_legend_serie_key = legend_serie_keys.get_shared_key();
+    let shared_secondary_y_labels = secondary_y_labels.iter().flatten().cloned().collect::<Vec<String>>();
+    // the legend area is sized to fit all the legends
+    let label_size = match shared_legend_serie_key.as_ref() {
+        Some(legend_serie_key) => {
+            let legends = collect_legends(data, legend_serie_key, series.iter().map(|serie| serie.filters));
+            let legends = add_secondary_entries(legends.into_iter().map(|legend| (legend, ())).collect(), &shared_secondary_y_labels, |_| ())
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect::<HashSet<String>>();
+            get_legend_area_width(&legends, legend_serie_key, layout.get_size(figure_style.get_subplot_size()).1, figure_style)
+        },
+        None => 0,
+    };
     // (w, h)
-    let global_size = (layout.width as u32 * ONE_FIG_SIZE.0 + LABEL_HORIZONTAL_SIZE, layout.height as u32 * ONE_FIG_SIZE.1);
+    let grid_size = layout.get_size(figure_style.get_subplot_size());
+    let global_size = (grid_size.0 + label_size, grid_size.1 + get_title_height(layout.get_title(), figure_style));
 
     // global drawing
     let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
-    root_drawing_area.fill(&WHITE)?;
+    root_drawing_area.fill(&figure_style.background)?;
+    // write the title above the subplots
+    let figure_drawing_area = write_title(&root_drawing_area, layout.get_title(), figure_style)?;
     // isolate the label area
     let (chart_drawing_area, label_drawing_area) = 
-        root_drawing_area.split_horizontally(global_size.0 - LABEL_HORIZONTAL_SIZE);
+        figure_drawing_area.split_horizontally(global_size.0 - label_size);
 
     // get the drawing area for each subplot (row, col)
-    let child_drawing_areas = chart_drawing_area.split_evenly(layout.get_plotter_layout());
+    let child_drawing_areas = layout.split(&chart_drawing_area);
     
-    // associate each legend to a color
-    let mut legend_to_color : HashMap<String, PaletteColor<CustomPalette>> = HashMap::new();
-    let mut legend_index = 0;
+    let mut all_fits = Vec::new();
+
+    // the style of each legend, and the legends to write in the legend
+    let mut legend_styles = legend.styles.clone();
+    let mut used_legends = HashSet::new();
+
+    // get the data of each subplot before drawing, to share the ranges of the axes
+    let mut subplots = Vec::new();
+    let mut ranges = Vec::new();
+    for (subplot_index, serie) in series.into_iter().enumerate() {
+        let subplot_options = get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index);
+        let legend_serie_key = legend_serie_keys.get_legend_key(layout.get_nb_of_subplots(), subplot_index);
+        let (subplot, subplot_ranges) = get_line_subplot(data, serie, legend_serie_key, &subplot_options, &aggregation_metric);
+        subplots.push(subplot);
+        ranges.push(subplot_ranges);
+    }
+    layout.share_ranges(&mut ranges);
 
     // plot each serie
-    for (_, (
-            (x_serie_key, y_serie_key, filters), root)
-        )
-    in series.into_iter().zip(child_drawing_areas.iter()).enumerate() {
-        
-        
-        // get the data
-        let data_it = data.into_iter_with_filter(
-            (x_serie_key, y_serie_key), 
-            legend_serie_key.clone(), 
-            filters
-        );
-        let plot_data = PlotData::from_it(data_it, Some(aggregation_metric.clone()), remove_outlier);
+    for (subplot_index, ((subplot, (range_x, range_y)), root)) in subplots.into_iter().zip(ranges).zip(child_drawing_areas.iter()).enumerate() {
+        let LineSubplot { x_serie_key, y_serie_key, legend_serie_key, line_data, secondary } = subplot;
+        let LineData { plot_data, raw_plot_data, fits } = line_data;
+        // the styles are independent if the legend is not shared
+        if shared_legend_serie_key.is_none() {
+            legend_styles = legend.styles.clone();
+            used_legends.clear();
+        }
 
         // define the chart
-        let (range_x, range_y) = plot_data.get_range();
-
         let y_serie_name = if let Some(y_serie_key) = y_serie_key {
             y_serie_key.get_display_name()
         } else {
             "count".to_string()
         };
 
-        let caption = format!("{} per {}", y_serie_name, x_serie_key.get_display_name());
-        let mut chart = ChartBuilder::on(&root)
-            .caption(caption.as_str(), ("sans-serif", FIGURE_CAPTION_FONT_SIZE).into_font())
-            .margin(5)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
-            .build_cartesian_2d(range_x, range_y)?;
-
-        chart.configure_mesh()
-            .x_desc(x_serie_key.get_display_name().as_str())
-            .y_desc(y_serie_name.as_str())
-            .x_label_formatter(&format_number_f32)
-            .y_label_formatter(&format_number_f32)
+        let subplot_options = get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index);
+        let caption = subplot_options.get_caption(|| format!("{} per {}", y_serie_name, x_serie_key.get_display_name()));
+        let (x_label, y_label) = subplot_options.get_axis_labels(x_serie_key.get_display_name(), y_serie_name);
+        let mut chart = ChartBuilder::on(root)
+            .caption(caption.as_str(), figure_style.get_caption_font())
+            .margin(figure_style.px(5))
+            .x_label_area_size(figure_style.px(40))
+            .y_label_area_size(figure_style.px(60))
+            .right_y_label_area_size(if secondary.is_some() { figure_style.px(60) } else { 0 })
+            .build_cartesian_2d(range_x.clone(), range_y.clone())?
+            // the secondary coordinates take the right label area before the primary axes are drawn
+            .set_secondary_coord(range_x.clone(), secondary.as_ref().map_or(range_y.clone(), |secondary| secondary.range_y.clone()));
+        // the labels of the shared axes are only on the outer subplots
+        let (has_x_labels, has_y_labels) = (layout.has_x_labels(subplot_index), layout.has_y_labels(subplot_index));
+        let mut mesh = chart.configure_mesh();
+        figure_style.configure_mesh(&mut mesh);
+        // the values are formatted with the units of the keys (the counts have no unit)
+        let y_unit = y_serie_key.filter(|_| aggregation_metric != MetricName::NbValues).map(|key| key.get_unit()).unwrap_or_default();
+        let x_unit = x_serie_key.get_unit();
+        mesh.x_desc(if has_x_labels { x_label } else { String::new() })
+            .y_desc(if has_y_labels { y_label } else { String::new() })
+            .x_label_formatter(&|x| if has_x_labels { x_unit.format(*x as f64) } else { String::new() })
+            .y_label_formatter(&|y| if has_y_labels { y_unit.format(*y as f64) } else { String::new() })
             .draw()?;
 
 
-        // plot the data
-        for (legend, data_for_legend) in plot_data.into_iter() {
-            let color = 
-                legend_to_color.entry(legend.to_string())
-                    .or_insert_with(|| { 
-                        legend_index += 1; 
-                        CustomPalette::pick(legend_index - 1) 
-                    });
+        // the outliers to show, plotted after the data
+        let removed = plot_data.get_removed_data().clone();
+
+        // assign the styles in sorted order, to keep the same styles whatever the order of the data
+        let drawn_legends = plot_data.get_data().keys()
+            .chain(removed.keys())
+            .chain(fits.keys())
+            .chain(secondary.iter().flat_map(|secondary| secondary.line_data.plot_data.get_data().keys()))
+            .cloned()
+            .collect::<Vec<String>>();
+        legend_styles.assign_sorted(drawn_legends.iter());
+        used_legends.extend(drawn_legends);
+
+        // the lines drawn in pixels, to place the legend inside the chart where it hides the fewest data
+        let mut drawn_lines = Vec::new();
+        if shared_legend_serie_key.is_none() {
+            let to_pixels = |serie : &[(f32, f32)]| serie.iter().map(|point| chart.backend_coord(point)).collect::<Vec<(i32, i32)>>();
+            drawn_lines.extend(plot_data.get_data().values().map(|serie| to_pixels(serie)));
+            drawn_lines.extend(removed.values().flatten().map(|point| vec![chart.backend_coord(point)]));
+            drawn_lines.extend(fits.values().flat_map(|fit| get_fit_curve_segments(fit, &range_x, &range_y)).map(|segment| to_pixels(&segment)));
+            if let Some(secondary) = secondary.as_ref() {
+                let secondary_chart = chart.borrow_secondary();
+                drawn_lines.extend(
+                    secondary.line_data.plot_data.get_data().values()
+                        .map(|serie| serie.iter().map(|point| secondary_chart.backend_coord(point)).collect::<Vec<(i32, i32)>>())
+                );
+            }
+        }
+
+        // plot the raw data faintly under the smoothed series
+        for (legend, raw_serie) in raw_plot_data.into_iter().flatten() {
+            let style = legend_styles.get_style(&legend);
 
             chart
                 .draw_series(
                     LineSeries::new(
-                        data_for_legend.iter().map(|(x, y)| (*x, *y)),
-                        color.filled().stroke_width(1),
+                        raw_serie,
+                        figure_style.get_line_style(&style.color.mix(RAW_DATA_OPACITY), 1),
                     )
                 )?;
         }
+
+        // plot the data
+        for (legend, points) in plot_data.into_iter() {
+            let style = legend_styles.get_style(&legend);
+
+            match style.dash.get_pattern() {
+                None => chart.draw_series(LineSeries::new(points, figure_style.get_line_style(&style.color, 1)))?,
+                Some((size, spacing)) => chart.draw_series(
+                    DashedLineSeries::new(points, figure_style.px(size), figure_style.px(spacing), figure_style.get_line_style(&style.color, 1))
+                )?,
+            };
+        }
+
+        // plot the outliers in a muted style
+        for (legend, outliers) in removed.into_iter() {
+            let style = legend_styles.get_style(&legend);
+
+            chart
+                .draw_series(
+                    outliers.iter()
+                        .map(|(x, y)| Circle::new((*x, *y), figure_style.px(2), style.color.mix(OUTLIER_OPACITY))),
+            )?;
+        }
+
+        // plot the fitted curves
+        for (legend, fit) in fits.iter() {
+            let style = legend_styles.get_style(legend);
+            // each part of the curve inside the y range is drawn separately
+            for segment in get_fit_curve_segments(fit, &range_x, &range_y) {
+                chart
+                    .draw_series(
+                        DashedLineSeries::new(segment, figure_style.px(5), figure_style.px(5), figure_style.get_line_style(&style.color, 2)),
+                    )?;
+            }
+        }
+        draw_annotations(&mut chart, &subplot_options.annotations, figure_style)?;
+
+        // plot the secondary metric against the right axis
+        if let Some(SecondaryLine { y_serie_key : secondary_y_serie_key, line_data, .. }) = secondary {
+            let secondary_y_label = subplot_options.secondary_y_label.clone().unwrap_or_else(|| secondary_y_serie_key.get_display_name());
+            let secondary_y_unit = Some(secondary_y_serie_key).filter(|_| aggregation_metric != MetricName::NbValues).map(|key| key.get_unit()).unwrap_or_default();
+            let mut axes = chart.configure_secondary_axes();
+            figure_style.configure_secondary_axes(&mut axes);
+            axes.y_desc(secondary_y_label)
+                .y_label_formatter(&|y| secondary_y_unit.format(*y as f64))
+                .draw()?;
+
+            for (legend, raw_serie) in line_data.raw_plot_data.into_iter().flatten() {
+                let style = legend_styles.get_style(&legend).get_secondary();
+                chart.draw_secondary_series(LineSeries::new(raw_serie, figure_style.get_line_style(&style.color.mix(RAW_DATA_OPACITY), 1)))?;
+            }
+            let removed = line_data.plot_data.get_removed_data().clone();
+            for (legend, points) in line_data.plot_data.into_iter() {
+                let style = legend_styles.get_style(&legend).get_secondary();
+
+                match style.dash.get_pattern() {
+                    None => chart.draw_secondary_series(LineSeries::new(points, figure_style.get_line_style(&style.color, 1)))?,
+                    Some((size, spacing)) => chart.draw_secondary_series(
+                        DashedLineSeries::new(points, figure_style.px(size), figure_style.px(spacing), figure_style.get_line_style(&style.color, 1))
+                    )?,
+                };
+            }
+            // the outliers of the secondary metric are hollow, the dash of the secondary style can't be seen on a point
+            for (legend, outliers) in removed.into_iter() {
+                let style = legend_styles.get_style(&legend).get_secondary();
+                chart.draw_secondary_series(
+                    outliers.iter().map(|(x, y)| Circle::new((*x, *y), figure_style.px(2), figure_style.get_line_style(&style.color.mix(OUTLIER_OPACITY), 1)))
+                )?;
+            }
+        }
+
+        if shared_legend_serie_key.is_none() {
+            let subplot_secondary_y_labels = secondary_y_labels[subplot_index].iter().cloned().collect::<Vec<String>>();
+            let entries = get_subplot_entries(
+                legend_styles.get_entries(&used_legends, &legend.order),
+                legend_serie_key.is_some(),
+                &subplot_secondary_y_labels,
+                LegendStyle::get_secondary,
+            );
+            let plotting_area = chart.plotting_area().get_pixel_range();
+            write_subplot_legend(root, &plotting_area, &drawn_lines, &entries, &legend_serie_key, LegendSymbol::Line, figure_style)?;
+        }
+        all_fits.push(fits);
     }// end of for each serie
 
-    write_legend(&label_drawing_area, &legend_to_color, &legend_serie_key)?;
+    if let Some(legend_serie_key) = shared_legend_serie_key {
+        let entries = add_secondary_entries(legend_styles.get_entries(&used_legends, &legend.order), &shared_secondary_y_labels, LegendStyle::get_secondary);
+        write_legend(&label_drawing_area, &entries, &legend_serie_key, LegendSymbol::Line, figure_style)?;
+    }
 
     root_drawing_area.present()?;
 
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(all_fits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_secondary_entries() {
+        let entries = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+
+        fn labels(entries : &[(String, i32)]) -> Vec<(&str, i32)> {
+            entries.iter().map(|(label, value)| (label.as_str(), *value)).collect()
+        }
+
+        // without secondary y axis, the entries are unchanged
+        let without_secondary = add_secondary_entries(entries.clone(), &[], |v| -v);
+        assert_eq!(labels(&without_secondary), [("a", 1), ("b", 2)]);
+
+        // one entry per secondary line, with the secondary style
+        let with_secondary = add_secondary_entries(entries.clone(), &["latency".to_string()], |v| -v);
+        assert_eq!(labels(&with_secondary), [("a", 1), ("a (latency)", -1), ("b", 2), ("b (latency)", -2)]);
+
+        // the same label on several subplots gives a single entry
+        let shared = add_secondary_entries(entries, &["latency".to_string(), "latency".to_string()], |v| -v);
+        assert_eq!(labels(&shared), [("a", 1), ("a (latency)", -1), ("b", 2), ("b (latency)", -2)]);
+    }
+
+    #[test]
+    fn test_get_subplot_entries() {
+        let entries = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+        let labels = |entries : Vec<(String, i32)>| entries.into_iter().map(|(label, _)| label).collect::<Vec<String>>();
+
+        assert_eq!(labels(get_subplot_entries(entries.clone(), true, &["latency".to_string()], |v| -v)), ["a", "a (latency)", "b", "b (latency)"]);
+        // without legend key, the line "All" isn't in the legend, the secondary line is named by its axis
+        let all = vec![("All".to_string(), 1)];
+        assert!(get_subplot_entries(all.clone(), false, &[], |v| -v).is_empty());
+        assert_eq!(get_subplot_entries(all, false, &["latency".to_string()], |v| -v), [("latency".to_string(), -1)]);
+    }
+}
@@ -3,8 +3,17 @@
 
 pub mod scatter_plot;
 pub mod line_plot;
+pub mod heatmap_plot;
+pub mod bar_plot;
+pub mod distribution_plot;
 
 
 pub mod utils;
 pub mod layout;
 pub mod plot_data;
+pub mod subplot_options;
+pub mod color_scale;
+pub mod legend_style;
+pub mod figure_style;
+pub mod axis_range;
+pub mod annotation;
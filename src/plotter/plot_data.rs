@@ -3,8 +3,10 @@ use std::mem;
 use std::ops::Range;
 
 use crate::data::rangeable::Rangeable;
-use crate::stat::compression::compress_data_serie;
-use crate::stat::remove_outliers;
+use crate::stat::compression::Compression;
+use crate::stat::fitting::{FitModel, FitResult};
+use crate::stat::outlier::OutlierConfig;
+use crate::stat::smoothing::Smoothing;
 use crate::stat::stats_serie::{MetricName, StatsSerie};
 
 
@@ -19,6 +21,8 @@ type Point = (f32, f32);
 #[derive(Debug, Clone)]
 pub struct PlotData {
     data:  HashMap<String, Vec<Point>>,
+    /// the outliers removed from the data, indexed by legend (empty if they are not shown)
+    removed:  HashMap<String, Vec<Point>>,
     x_range: Range<f32>,
     y_range: Range<f32>,
 }
@@ -26,7 +30,19 @@ pub struct PlotData {
 impl PlotData {
     /// create a new PlotData from an iterator of (String, Point) and a metric to aggregate the data
     /// Also compress the data to accelerate the plotting
-    pub fn from_it<It>(mut data : It, aggregation_metric : Option<MetricName>, remove_outlier : bool) -> Self
+    /// NOTE : the outliers are detected on each legend before the aggregation, they are kept apart only if outlier_config.show_removed is true
+    pub fn from_it<It>(data : It, aggregation_metric : Option<MetricName>, outlier_config : OutlierConfig) -> Self
+    where
+        It : Iterator<Item = (String, Point)> + Rangeable
+    {
+        let mut self_ = Self::from_it_without_compression(data, aggregation_metric, outlier_config);
+        self_.compress();
+        self_
+    }
+
+    /// same as from_it, without the compression
+    /// NOTE : useful to work on all the points (e.g. to fit a model), the compression can be done after with compress
+    pub fn from_it_without_compression<It>(mut data : It, aggregation_metric : Option<MetricName>, outlier_config : OutlierConfig) -> Self
     where
         It : Iterator<Item = (String, Point)> + Rangeable
     {
@@ -35,47 +51,58 @@ impl PlotData {
             data_collected.entry(key).or_insert_with(Vec::new).push(point);
         }
 
-        if remove_outlier {
-            for (_, serie) in data_collected.iter_mut() {
-                *serie = remove_outliers(mem::take(serie));
+        let mut removed = HashMap::new();
+        let (mut x_range, mut y_range) = data.get_range().unwrap_or((0.0..1.0, 0.0..1.0));
+        if !outlier_config.is_none() {
+            for (key, serie) in data_collected.iter_mut() {
+                let (kept, outliers) = outlier_config.split_outliers(mem::take(serie));
+                *serie = kept;
+                if outlier_config.show_removed && !outliers.is_empty() {
+                    removed.insert(key.clone(), outliers);
+                }
             }
+            // the range is limited to the kept points (extended to the shown outliers below)
+            (x_range, y_range) = Self::get_range_from_hashmap(&data_collected);
         }
 
-        let (mut x_range, y_range) = data.get_range().unwrap_or((0.0..1.0, 0.0..1.0));
-
         if x_range.start == x_range.end {
             x_range = x_range.start - 0.5..x_range.end + 0.5;
         }
-
+        if y_range.start == y_range.end {
+            y_range = y_range.start - 0.5..y_range.end + 0.5;
+        }
 
         let mut self_ = Self {
             data : data_collected,
+            removed,
             x_range,
             y_range,
         };
+        self_.extend_range_with_removed();
 
         if let Some(metric) = aggregation_metric {
             self_ = self_.apply_aggregator(metric).unwrap();
         }
 
-        self_.compress();
-
         self_
     }
 
 
-    /// compress the data to accelerate the plotting
-    fn compress(&mut self) -> &mut Self{
+    /// compress the data to accelerate the plotting, with the default compression
+    pub fn compress(&mut self) -> &mut Self {
+        self.compress_with(&Compression::default())
+    }
+
+    /// compress the data with the given compression over the range of the data
+    /// NOTE : the series are sorted by x after the compression (even without compression), to draw them as lines
+    pub fn compress_with(&mut self, compression : &Compression) -> &mut Self {
         let (range_x, range_y) = self.get_range();
-        let original_data = mem::replace(&mut self.data, HashMap::new()); // take out the map
-        // Transform the data.
+        let original_data = mem::take(&mut self.data); // take out the map
         self.data = original_data.into_iter().map(|(key, serie)| {
-            // Now you can avoid cloning the key, as `key` is owned here due to `into_iter()`.
-            let compressed_serie = compress_data_serie(serie, &range_x, &range_y);
-            (key, compressed_serie) // No need to clone the key.
+            (key, compression.apply(serie, &range_x, &range_y))
         }).collect();
 
-        return self
+        self
     }
 
     /// aggregate the data and combine the value with the same x value with a specified metric
@@ -109,7 +136,57 @@ impl PlotData {
             // replace the serie with the aggregated one
             aggregated_data.insert(key.clone(), aggregated_serie);
         }
-        Ok(aggregated_data.into())
+        let mut aggregated : PlotData = aggregated_data.into();
+        aggregated.removed = self.removed;
+        aggregated.extend_range_with_removed();
+        Ok(aggregated)
+    }
+
+    /// fit the model on the serie of each legend
+    /// NOTE : the legends without enough points to fit the model are skipped
+    pub fn fit(&self, model : FitModel) -> HashMap<String, FitResult> {
+        self.data.iter()
+            .filter_map(|(legend, serie)| FitResult::new(model, serie).map(|fit| (legend.clone(), fit)))
+            .collect()
+    }
+
+    /// smooth the serie of each legend (the series are sorted by x)
+    /// NOTE : the range is extended if a smoothed value is outside (e.g. with a local regression)
+    pub fn smooth(&mut self, smoothing : Smoothing) -> &mut Self {
+        for serie in self.data.values_mut() {
+            *serie = smoothing.apply(serie);
+        }
+        let (_, smoothed_y_range) = Self::get_range_from_hashmap(&self.data);
+        if self.data.values().any(|serie| !serie.is_empty()) {
+            self.y_range = self.y_range.start.min(smoothed_y_range.start)..self.y_range.end.max(smoothed_y_range.end);
+        }
+        self
+    }
+
+    /// remove the points (and the outliers shown) outside the given ranges, None to keep all the points on an axis
+    /// NOTE : the range of the data is not changed
+    pub fn clip(&mut self, x_range : Option<&Range<f32>>, y_range : Option<&Range<f32>>) -> &mut Self {
+        let x_range = x_range.map_or(f32::MIN..=f32::MAX, |range| range.start..=range.end);
+        let y_range = y_range.map_or(f32::MIN..=f32::MAX, |range| range.start..=range.end);
+        for serie in self.data.values_mut().chain(self.removed.values_mut()) {
+            serie.retain(|(x, y)| x_range.contains(x) && y_range.contains(y));
+        }
+        self
+    }
+
+    /// extend the range to contain the outliers shown
+    fn extend_range_with_removed(&mut self) {
+        if self.removed.is_empty() {
+            return;
+        }
+        let (removed_x_range, removed_y_range) = Self::get_range_from_hashmap(&self.removed);
+        let (data_x_range, data_y_range) = if self.data.values().all(|serie| serie.is_empty()) {
+            (removed_x_range.clone(), removed_y_range.clone())
+        } else {
+            (self.x_range.clone(), self.y_range.clone())
+        };
+        self.x_range = data_x_range.start.min(removed_x_range.start)..data_x_range.end.max(removed_x_range.end);
+        self.y_range = data_y_range.start.min(removed_y_range.start)..data_y_range.end.max(removed_y_range.end);
     }
 
     fn get_range_from_hashmap(data : &HashMap<String, Vec<Point>>) -> (Range<f32>, Range<f32>) {
@@ -152,6 +229,11 @@ impl PlotData {
             x_min -= 0.5;
             x_max += 0.5;
         }
+
+        if y_min == y_max {
+            y_min -= 0.5;
+            y_max += 0.5;
+        }
     
         (x_min..x_max, y_min..y_max)
     }
@@ -160,6 +242,11 @@ impl PlotData {
         &self.data
     }
 
+    /// get the outliers to plot in a muted style, indexed by legend
+    pub fn get_removed_data(&self) -> &HashMap<String, Vec<Point>> {
+        &self.removed
+    }
+
     pub fn get_range(&self) -> (Range<f32>, Range<f32>) {
         (self.x_range.clone(), self.y_range.clone())
     }
@@ -170,6 +257,7 @@ impl From<HashMap<String, Vec<Point>>> for PlotData {
         let (x_range, y_range) = PlotData::get_range_from_hashmap(&data);
         Self {
             data,
+            removed : HashMap::new(),
             x_range,
             y_range,
         }
@@ -189,4 +277,41 @@ impl IntoIterator for PlotData {
     fn into_iter(self) -> Self::IntoIter {
         self.data.into_iter()
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_values_range() {
+        let data = HashMap::from([("a".to_string(), vec![(1.0, 2.0), (3.0, 2.0)])]);
+        let plot_data = PlotData::from(data);
+        assert_eq!(plot_data.get_range(), (1.0..3.0, 1.5..2.5));
+    }
+
+    #[test]
+    fn test_clip() {
+        let data = HashMap::from([("a".to_string(), vec![(0.0, 0.0), (1.0, 5.0), (2.0, 10.0)])]);
+        let mut plot_data = PlotData::from(data);
+        plot_data.clip(None, Some(&(0.0..6.0)));
+        assert_eq!(plot_data.get_data()["a"], vec![(0.0, 0.0), (1.0, 5.0)]);
+        assert_eq!(plot_data.get_range(), (0.0..2.0, 0.0..10.0));
+    }
+
+    #[test]
+    fn test_compression() {
+        let serie = (0..1000).rev().map(|i| (i as f32, (i % 10) as f32)).collect::<Vec<Point>>();
+        let mut plot_data = PlotData::from(HashMap::from([("a".to_string(), serie.clone())]));
+        plot_data.compress_with(&Compression::none());
+        assert_eq!(plot_data.get_data()["a"].len(), 1000);
+        assert!(plot_data.get_data()["a"].windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // the series under the threshold are kept as they are
+        let mut plot_data = PlotData::from(HashMap::from([("a".to_string(), serie[..100].to_vec())]));
+        plot_data.compress();
+        assert_eq!(plot_data.get_data()["a"].len(), 100);
+        assert_eq!(plot_data.get_data()["a"][0], (900.0, 0.0));
+    }
+}
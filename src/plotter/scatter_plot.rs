@@ -1,40 +1,73 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::Path;
 
 
 use plotters::backend::BitMapBackend;
 use plotters::chart::ChartBuilder;
 use plotters::drawing::IntoDrawingArea;
-use plotters::element::Circle;
-use plotters::style::{Color, IntoFont, Palette, PaletteColor, WHITE};
+use plotters::element::Polygon;
+use plotters::series::DashedLineSeries;
+use plotters::style::{Color, ShapeStyle};
 
 use crate::data::filtering::Filters;
 use crate::data::plottable::Plottable;
 use crate::data::sample::key::SerieKey;
 use crate::data::sample::Sample;
-use crate::params::{FIGURE_CAPTION_FONT_SIZE, LABEL_HORIZONTAL_SIZE, ONE_FIG_SIZE};
+use crate::params::{COLOR_BAR_SIZE, OUTLIER_OPACITY};
+use crate::stat::compression::{DensityBin, DensityBins};
+use crate::stat::fitting::FitResult;
 
+use super::annotation::draw_annotations;
+use super::figure_style::FigureStyle;
 use super::layout::Layout;
+use super::legend_style::LegendStyle;
+use super::color_scale::{draw_color_bar, ColorPalette, ColorScale};
 use super::plot_data::PlotData;
-use super::utils::{format_number_f32, write_legend, CustomPalette};
+use super::subplot_options::{get_subplot_options, LegendOptions, SubplotOptions};
+use super::utils::{collect_legends, get_fit_curve_segments, get_legend_area_width, get_title_height, write_legend, write_subplot_legend, write_title, LegendSymbol};
+
+
+/// the bins of a subplot in density mode, with the ranges used to bin the points
+struct DensitySubplot {
+    bins : DensityBins,
+    density_bins : Vec<DensityBin>,
+    range_x : Range<f32>,
+    range_y : Range<f32>,
+}
+
+/// the data of a subplot, computed before drawing
+struct ScatterSubplot<Key> {
+    x_serie_key : Key,
+    y_serie_key : Option<Key>,
+    legend_serie_key : Option<Key>,
+    plot_data : PlotData,
+    fits : HashMap<String, FitResult>,
+    density : Option<DensitySubplot>,
+}
 
 
 /// plot the given data
 /// take a list of series to plot, to the format (x_serie_key, y_serie_key, filter)
 /// If filter is Some, the data will be filtered by the given key and the given function (true to keep the data)
-/// NOTE : the number of series to plot must be equal to the number of subplots
-/// NOTE : If remove_outliers is Some, the outliers will be removed from the data with the given key
-pub fn scatter_plot<'plot_lt, S, Key, Plot>(
+/// NOTE : the number of series to plot must be equal to the number of subplots (the cells of the layout)
+/// NOTE : legend_serie_keys is one legend key for all the subplots, or one per subplot (LegendKeys) : the subplots with different legend keys have their own colors and legend
+/// NOTE : options are the options of the subplots (outliers, fit, density, texts, axis ranges, annotations) : empty for the default options, one for every subplot, or one per subplot
+/// NOTE : an OutlierConfig can be given instead of the SubplotOptions, to set only the outliers
+/// return the fits of each subplot (empty if there is no fit_model), to show the coefficients in a table with FitResult::to_array
+pub fn scatter_plot<'plot_lt, S, Key, Plot, Opt>(
     data : &'plot_lt Plot, 
-    legend_serie_key : Option<Key>,
+    legend : impl Into<LegendOptions<Key>>,
     save_path : &str,
     layout : &Layout,
+    figure_style : &FigureStyle,
 
     series : Vec<(Key, Option<Key>, Option<&'plot_lt Filters<Key>>)>,
     
-    remove_outlier : bool,
-) -> Result<(), Box<dyn std::error::Error>> 
+    options : &[Opt],
+) -> Result<Vec<HashMap<String, FitResult>>, Box<dyn std::error::Error>> 
 where
+    Opt : Clone + Into<SubplotOptions>,
     Key : SerieKey,
     S : Sample<Key>,
     Plot : Plottable<S, Key>,
@@ -47,84 +80,219 @@ where
 
     // initialise the plotter
     let image_path_o = Path::new(save_path);
+    // the subplots with the same legend key share the legend on the right, otherwise each subplot has its own legend
+    let legend = legend.into();
+    let legend_serie_keys = &legend.keys;
+    let shared_legend_serie_key = legend_serie_keys.get_shared_key();
+    // the legend area is sized to fit all the legends (no legend if all the subplots are in density mode)
+    let all_density = (0..layout.get_nb_of_subplots())
+        .all(|subplot_index| get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index).density.is_some());
+    let label_size = match shared_legend_serie_key.as_ref().filter(|_| !all_density) {
+        Some(legend_serie_key) => {
+            let legends = collect_legends(data, legend_serie_key, series.iter().map(|(_, _, filters)| *filters));
+            get_legend_area_width(&legends, legend_serie_key, layout.get_size(figure_style.get_subplot_size()).1, figure_style)
+        },
+        None => 0,
+    };
     // (w, h)
-    let global_size = (layout.width as u32 * ONE_FIG_SIZE.0 + LABEL_HORIZONTAL_SIZE, layout.height as u32 * ONE_FIG_SIZE.1);
+    let grid_size = layout.get_size(figure_style.get_subplot_size());
+    let global_size = (grid_size.0 + label_size, grid_size.1 + get_title_height(layout.get_title(), figure_style));
 
     // global drawing
     let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
-    root_drawing_area.fill(&WHITE)?;
+    root_drawing_area.fill(&figure_style.background)?;
+    // write the title above the subplots
+    let figure_drawing_area = write_title(&root_drawing_area, layout.get_title(), figure_style)?;
     // isolate the label area
     let (chart_drawing_area, label_drawing_area) = 
-        root_drawing_area.split_horizontally(global_size.0 - LABEL_HORIZONTAL_SIZE);
+        figure_drawing_area.split_horizontally(global_size.0 - label_size);
 
     // get the drawing area for each subplot (row, col)
-    let child_drawing_areas = chart_drawing_area.split_evenly(layout.get_plotter_layout());
+    let child_drawing_areas = layout.split(&chart_drawing_area);
     
-    // associate each legend to a color
-    let mut legend_to_color : HashMap<String, PaletteColor<CustomPalette>> = HashMap::new();
-    let mut legend_index = 0;
+    let mut all_fits = Vec::new();
+
+    // the style of each legend, and the legends to write in the legend
+    let mut legend_styles = legend.styles.clone();
+    let mut used_legends = HashSet::new();
+
+    // get the data of each subplot before drawing, to share the ranges of the axes
+    let mut subplots = Vec::new();
+    let mut ranges = Vec::new();
+    for (subplot_index, (x_serie_key, y_serie_key, filters)) in series.into_iter().enumerate() {
+        let subplot_options = get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index);
+        let legend_serie_key = legend_serie_keys.get_legend_key(layout.get_nb_of_subplots(), subplot_index);
+
+        // get the data
+        let data_it = data.into_iter_with_filter(
+            (x_serie_key, y_serie_key), 
+            legend_serie_key, 
+            filters
+        );
+        let mut plot_data = PlotData::from_it_without_compression(data_it, None, subplot_options.outliers);
+        // fit on all the points, before the compression
+        let fits = match subplot_options.fit_model {
+            Some(model) => plot_data.fit(model),
+            None => HashMap::new(),
+        };
+        // hide the points outside the ranges set by the user
+        plot_data.clip(subplot_options.x_range.get_clip_range().as_ref(), subplot_options.y_range.get_clip_range().as_ref());
+        // the ranges of the axes, from the range of the data and the options
+        let (data_range_x, data_range_y) = plot_data.get_range();
+        let (range_x, range_y) = (subplot_options.x_range.apply(&data_range_x), subplot_options.y_range.apply(&data_range_y));
+        ranges.push((range_x.clone(), range_y.clone()));
+
+        // in density mode, count all the points (of all the legends) before the compression
+        let density = subplot_options.density.map(|bins| {
+            let points = plot_data.get_data().values().flatten().cloned().collect::<Vec<(f32, f32)>>();
+            let density_bins = bins.bin_data_serie(&points, &range_x, &range_y);
+            DensitySubplot { bins, density_bins, range_x, range_y }
+        });
+        plot_data.compress_with(&subplot_options.compression);
+
+        subplots.push(ScatterSubplot { x_serie_key, y_serie_key, legend_serie_key, plot_data, fits, density });
+    }
+    layout.share_ranges(&mut ranges);
 
     // plot each serie
-    for (_, (
-            (x_serie_key, y_serie_key, filters), root)
-        )
-    in series.into_iter().zip(child_drawing_areas.iter()).enumerate() {
+    for (subplot_index, ((subplot, (range_x, range_y)), root)) in subplots.into_iter().zip(ranges).zip(child_drawing_areas.iter()).enumerate() {
+        let ScatterSubplot { x_serie_key, y_serie_key, legend_serie_key, plot_data, fits, density } = subplot;
+        // the styles are independent if the legend is not shared
+        if shared_legend_serie_key.is_none() {
+            legend_styles = legend.styles.clone();
+            used_legends.clear();
+        }
 
         let y_series_name = if let Some(y_serie_key) = y_serie_key.as_ref() {
             y_serie_key.get_display_name()
         } else {
             "count".to_string()
         };
-       
-        // get the data
-        let data_it = data.into_iter_with_filter(
-            (x_serie_key, y_serie_key), 
-            legend_serie_key.clone(), 
-            filters
-        );
-        let plot_data = PlotData::from_it(data_it, None, remove_outlier);
 
+        // the density take the right of the subplot for its color bar
+        let (root, color_bar_area) = match density {
+            Some(_) => {
+                let (chart_area, color_bar_area) = root.split_horizontally(root.dim_in_pixel().0.saturating_sub(figure_style.px(COLOR_BAR_SIZE)));
+                (chart_area, Some(color_bar_area))
+            },
+            None => (root.clone(), None),
+        };
 
         // define the chart
-        let (range_x, range_y) = plot_data.get_range();
-
-        let caption = format!("{} per {}", y_series_name, x_serie_key.get_display_name());
+        let subplot_options = get_subplot_options(options, layout.get_nb_of_subplots(), subplot_index);
+        let caption = subplot_options.get_caption(|| format!("{} per {}", y_series_name, x_serie_key.get_display_name()));
+        let (x_label, y_label) = subplot_options.get_axis_labels(x_serie_key.get_display_name(), y_series_name);
         let mut chart = ChartBuilder::on(&root)
-            .caption(caption.as_str(), ("sans-serif", FIGURE_CAPTION_FONT_SIZE).into_font())
-            .margin(5)
-            .x_label_area_size(40)
-            .y_label_area_size(60)
+            .caption(caption.as_str(), figure_style.get_caption_font())
+            .margin(figure_style.px(5))
+            .x_label_area_size(figure_style.px(40))
+            .y_label_area_size(figure_style.px(60))
             .build_cartesian_2d(range_x.clone(), range_y.clone())?;
 
-        chart.configure_mesh()
-            .x_desc(x_serie_key.get_display_name().as_str())
-            .y_desc(y_series_name)
-            .x_label_formatter(&format_number_f32)
-            .y_label_formatter(&format_number_f32)
+        // the labels of the shared axes are only on the outer subplots
+        let (has_x_labels, has_y_labels) = (layout.has_x_labels(subplot_index), layout.has_y_labels(subplot_index));
+        let mut mesh = chart.configure_mesh();
+        figure_style.configure_mesh(&mut mesh);
+        // the values are formatted with the units of the keys (the count has no unit)
+        let (x_unit, y_unit) = (x_serie_key.get_unit(), y_serie_key.map(|key| key.get_unit()).unwrap_or_default());
+        mesh.x_desc(if has_x_labels { x_label } else { String::new() })
+            .y_desc(if has_y_labels { y_label } else { String::new() })
+            .x_label_formatter(&|x| if has_x_labels { x_unit.format(*x as f64) } else { String::new() })
+            .y_label_formatter(&|y| if has_y_labels { y_unit.format(*y as f64) } else { String::new() })
             .draw()?;
 
-        // plot the data
-        for (legend, points) in plot_data.into_iter() {
-            // update the legend color
-            let color = 
-                legend_to_color.entry(legend.to_string())
-                    .or_insert_with(|| { 
-                        legend_index += 1; 
-                        CustomPalette::pick(legend_index - 1) 
-                    });
+        // the outliers to show, plotted after the data
+        let removed = plot_data.get_removed_data().clone();
+
+        // assign the styles in sorted order, to keep the same styles whatever the order of the data
+        let drawn_legends = plot_data.get_data().keys().filter(|_| density.is_none())
+            .chain(removed.keys())
+            .chain(fits.keys())
+            .cloned()
+            .collect::<Vec<String>>();
+        legend_styles.assign_sorted(drawn_legends.iter());
+        used_legends.extend(drawn_legends);
+
+        // the points and lines drawn in pixels, to place the legend inside the chart where it hides the fewest data
+        let mut drawn_lines = Vec::new();
+        if shared_legend_serie_key.is_none() {
+            let drawn_points = match density.as_ref() {
+                Some(density) => density.density_bins.iter().map(|bin| bin.center).collect::<Vec<(f32, f32)>>(),
+                None => plot_data.get_data().values().flatten().cloned().collect(),
+            };
+            drawn_lines.extend(drawn_points.iter().chain(removed.values().flatten()).map(|point| vec![chart.backend_coord(point)]));
+            drawn_lines.extend(
+                fits.values()
+                    .flat_map(|fit| get_fit_curve_segments(fit, &range_x, &range_y))
+                    .map(|segment| segment.iter().map(|point| chart.backend_coord(point)).collect::<Vec<(i32, i32)>>())
+            );
+        }
+
+        // plot the density, the color of a bin depend on its number of points
+        if let (Some(density), Some(color_bar_area)) = (density.as_ref(), color_bar_area.as_ref()) {
+            let max_count = density.density_bins.iter().map(|b| b.count).max().unwrap_or(1);
+            let color_scale = ColorScale::new(ColorPalette::Sequential, 1.0, max_count as f64).with_log();
+            chart
+                .draw_series(
+                    density.density_bins.iter().map(|bin| {
+                        let color = color_scale.get_color(bin.count as f64).unwrap();
+                        Polygon::new(density.bins.get_bin_vertices(bin.center, &density.range_x, &density.range_y), color.filled())
+                    })
+                )?;
+            draw_color_bar(color_bar_area, &color_scale, figure_style)?;
+        }
+
+        // plot the data (replaced by the density in density mode)
+        for (legend, points) in plot_data.into_iter().filter(|_| density.is_none()) {
+            let style = legend_styles.get_style(&legend);
        
             chart
                 .draw_series(
                     points.iter()
-                        .map(|(x, y)| Circle::new((*x, *y), 2, color.filled())),
+                        .map(|(x, y)| style.marker.get_element((*x, *y), figure_style.px(2) as i32, style.color.filled())),
             )?;
         }
+
+        // plot the outliers in a muted style
+        for (legend, outliers) in removed.into_iter() {
+            let style = legend_styles.get_style(&legend);
+
+            chart
+                .draw_series(
+                    outliers.iter()
+                        .map(|(x, y)| style.marker.get_element((*x, *y), figure_style.px(2) as i32, ShapeStyle::from(style.color.mix(OUTLIER_OPACITY)))),
+            )?;
+        }
+
+        // plot the fitted curves
+        for (legend, fit) in fits.iter() {
+            let style = legend_styles.get_style(legend);
+            // each part of the curve inside the y range is drawn separately
+            for segment in get_fit_curve_segments(fit, &range_x, &range_y) {
+                chart
+                    .draw_series(
+                        DashedLineSeries::new(segment, figure_style.px(5), figure_style.px(5), figure_style.get_line_style(&style.color, 2)),
+                    )?;
+            }
+        }
+        draw_annotations(&mut chart, &subplot_options.annotations, figure_style)?;
+        if shared_legend_serie_key.is_none() {
+            // without legend key, the single legend "All" names nothing
+            let entries = legend_styles.get_entries(&used_legends, &legend.order).into_iter()
+                .filter(|_| legend_serie_key.is_some())
+                .collect::<Vec<(String, LegendStyle)>>();
+            let plotting_area = chart.plotting_area().get_pixel_range();
+            write_subplot_legend(&root, &plotting_area, &drawn_lines, &entries, &legend_serie_key, LegendSymbol::Marker, figure_style)?;
+        }
+        all_fits.push(fits);
     }// end of for each serie
 
-    write_legend(&label_drawing_area, &legend_to_color, &legend_serie_key)?;
+    if let Some(legend_serie_key) = shared_legend_serie_key {
+        write_legend(&label_drawing_area, &legend_styles.get_entries(&used_legends, &legend.order), &legend_serie_key, LegendSymbol::Marker, figure_style)?;
+    }
 
     root_drawing_area.present()?;
 
 
-    Ok(())
+    Ok(all_fits)
 }
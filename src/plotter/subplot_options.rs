@@ -0,0 +1,314 @@
+use crate::data::sample::key::SerieKey;
+use crate::stat::compression::{Compression, DensityBins};
+use crate::stat::fitting::FitModel;
+use crate::stat::outlier::OutlierConfig;
+use crate::stat::smoothing::Smoothing;
+
+use super::annotation::Annotation;
+use super::axis_range::AxisRange;
+use super::bar_plot::{BarMode, CategoryOrder};
+use super::legend_style::{LegendOrder, LegendStyles};
+
+
+/// the options of one subplot
+/// NOTE : The plot functions take a list of options : empty for the default options, one for every subplot, or one per subplot
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubplotOptions {
+    /// how the outliers are detected and shown
+    pub outliers : OutlierConfig,
+    /// if Some, the model is fitted on each legend and drawn as a dashed curve
+    pub fit_model : Option<FitModel>,
+    /// if Some, the serie of each legend is smoothed after the aggregation (line plots only)
+    pub smoothing : Option<Smoothing>,
+    /// if true, the data before the smoothing is drawn faintly underneath
+    pub show_raw : bool,
+    /// if Some, the points are replaced by their density, colored with a log scale (scatter plots only)
+    pub density : Option<DensityBins>,
+    /// how the bars of the legends are drawn (bar plots only)
+    pub bar_mode : BarMode,
+    /// the order of the categories (bar plots only)
+    pub category_order : CategoryOrder,
+    /// the caption of the subplot, generated from the keys if None
+    pub caption : Option<String>,
+    /// the label of the x axis, the display name of the x key if None
+    pub x_label : Option<String>,
+    /// the label of the y axis, the display name of the y key if None
+    pub y_label : Option<String>,
+    /// the range of the x axis (line and scatter plots only)
+    pub x_range : AxisRange,
+    /// the range of the y axis
+    pub y_range : AxisRange,
+    /// the label of the secondary y axis, the display name of the secondary y key if None (line plots only)
+    pub secondary_y_label : Option<String>,
+    /// the range of the secondary y axis, independent of the shared axes (line plots only)
+    pub secondary_y_range : AxisRange,
+    /// the reference lines, intervals, texts and arrows drawn on top of the data
+    pub annotations : Vec<Annotation>,
+    /// how the series are compressed before drawing (line and scatter plots only)
+    pub compression : Compression,
+}
+
+impl SubplotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_outliers(mut self, outliers : OutlierConfig) -> Self {
+        self.outliers = outliers;
+        self
+    }
+
+    pub fn with_fit(mut self, fit_model : FitModel) -> Self {
+        self.fit_model = Some(fit_model);
+        self
+    }
+
+    pub fn with_density(mut self, density : DensityBins) -> Self {
+        self.density = Some(density);
+        self
+    }
+
+    pub fn with_bars(mut self, bar_mode : BarMode, category_order : CategoryOrder) -> Self {
+        self.bar_mode = bar_mode;
+        self.category_order = category_order;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing : Smoothing, show_raw : bool) -> Self {
+        self.smoothing = Some(smoothing);
+        self.show_raw = show_raw;
+        self
+    }
+
+    pub fn with_caption(mut self, caption : &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+
+    pub fn with_x_label(mut self, x_label : &str) -> Self {
+        self.x_label = Some(x_label.to_string());
+        self
+    }
+
+    pub fn with_y_label(mut self, y_label : &str) -> Self {
+        self.y_label = Some(y_label.to_string());
+        self
+    }
+
+    pub fn with_x_range(mut self, x_range : AxisRange) -> Self {
+        self.x_range = x_range;
+        self
+    }
+
+    pub fn with_y_range(mut self, y_range : AxisRange) -> Self {
+        self.y_range = y_range;
+        self
+    }
+
+    pub fn with_secondary_y_label(mut self, secondary_y_label : &str) -> Self {
+        self.secondary_y_label = Some(secondary_y_label.to_string());
+        self
+    }
+
+    pub fn with_secondary_y_range(mut self, secondary_y_range : AxisRange) -> Self {
+        self.secondary_y_range = secondary_y_range;
+        self
+    }
+
+    pub fn with_compression(mut self, compression : Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_annotation(mut self, annotation : Annotation) -> Self {
+        self.annotations.push(annotation);
+        self
+    }
+
+    /// get the caption, or the given default caption if there is no custom caption
+    pub(crate) fn get_caption(&self, default : impl FnOnce() -> String) -> String {
+        self.caption.clone().unwrap_or_else(default)
+    }
+
+    /// get the labels of the axes (x, y), or the given default labels if there are no custom labels
+    pub(crate) fn get_axis_labels(&self, x_default : String, y_default : String) -> (String, String) {
+        (self.x_label.clone().unwrap_or(x_default), self.y_label.clone().unwrap_or(y_default))
+    }
+}
+
+impl From<OutlierConfig> for SubplotOptions {
+    fn from(outliers : OutlierConfig) -> Self {
+        Self::new().with_outliers(outliers)
+    }
+}
+
+
+/// the legend keys of the subplots
+/// NOTE : a single Option<Key> is shared by all the subplots
+#[derive(Debug, Clone, PartialEq)]
+pub enum LegendKeys<Key> {
+    /// the same legend key for all the subplots
+    Shared(Option<Key>),
+    /// one legend key per subplot (None for no legend)
+    PerSubplot(Vec<Option<Key>>),
+}
+
+impl<Key : SerieKey> LegendKeys<Key> {
+    /// get the legend key of the subplot with the given index
+    /// NOTE : panic if there is not one legend key per subplot
+    pub fn get_legend_key(&self, nb_subplots : usize, subplot_index : usize) -> Option<Key> {
+        match self {
+            LegendKeys::Shared(key) => *key,
+            LegendKeys::PerSubplot(keys) if keys.len() == nb_subplots => keys[subplot_index],
+            LegendKeys::PerSubplot(keys) => panic!("The number of legend keys ({}) is not equal to the number of subplots ({})", keys.len(), nb_subplots),
+        }
+    }
+
+    /// get the legend key shared by all the subplots
+    /// return None if the subplots use different legend keys
+    pub fn get_shared_key(&self) -> Option<Option<Key>> {
+        match self {
+            LegendKeys::Shared(key) => Some(*key),
+            LegendKeys::PerSubplot(keys) => match keys.first() {
+                Some(first) if keys.iter().any(|key| key != first) => None,
+                first => Some(first.cloned().flatten()),
+            },
+        }
+    }
+}
+
+impl<Key> From<Option<Key>> for LegendKeys<Key> {
+    fn from(key : Option<Key>) -> Self {
+        LegendKeys::Shared(key)
+    }
+}
+
+impl<Key> From<Vec<Option<Key>>> for LegendKeys<Key> {
+    fn from(keys : Vec<Option<Key>>) -> Self {
+        LegendKeys::PerSubplot(keys)
+    }
+}
+
+
+/// the legend of a figure : the legend keys of the subplots and the styles of the legends
+/// NOTE : The plot functions take anything convertible into LegendOptions : a single Option<Key>, a Vec of Option<Key> (one per subplot) or LegendKeys
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendOptions<Key> {
+    pub keys : LegendKeys<Key>,
+    /// the styles of the legends, the missing legends are assigned in sorted order
+    pub styles : LegendStyles,
+    /// the order of the entries in the legend
+    pub order : LegendOrder,
+}
+
+impl<Key> LegendOptions<Key> {
+    pub fn new(keys : impl Into<LegendKeys<Key>>) -> Self {
+        LegendOptions {
+            keys : keys.into(),
+            styles : LegendStyles::default(),
+            order : LegendOrder::default(),
+        }
+    }
+
+    pub fn with_styles(mut self, styles : LegendStyles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    pub fn with_order(mut self, order : LegendOrder) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+impl<Key> From<LegendKeys<Key>> for LegendOptions<Key> {
+    fn from(keys : LegendKeys<Key>) -> Self {
+        LegendOptions::new(keys)
+    }
+}
+
+impl<Key> From<Option<Key>> for LegendOptions<Key> {
+    fn from(key : Option<Key>) -> Self {
+        LegendOptions::new(key)
+    }
+}
+
+impl<Key> From<Vec<Option<Key>>> for LegendOptions<Key> {
+    fn from(keys : Vec<Option<Key>>) -> Self {
+        LegendOptions::new(keys)
+    }
+}
+
+
+/// get the options of the subplot with the given index
+/// NOTE : panic if there are several options but not one per subplot
+pub(crate) fn get_subplot_options<Opt : Clone + Into<SubplotOptions>>(options : &[Opt], nb_subplots : usize, subplot_index : usize) -> SubplotOptions {
+    match options.len() {
+        0 => SubplotOptions::default(),
+        1 => options[0].clone().into(),
+        len if len == nb_subplots => options[subplot_index].clone().into(),
+        len => panic!("The number of subplot options ({}) is not equal to the number of subplots ({})", len, nb_subplots),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Display, Formatter};
+
+    use serde_derive::{Deserialize, Serialize};
+
+    use crate::generate_plot_key;
+    use crate::stat::outlier::OutlierStrategy;
+
+    use super::*;
+
+    generate_plot_key!(RunKey[
+        Time { "time (s)", Numeric },
+        Parser { "parser", String },
+        File { "file", String }
+    ]);
+
+    #[test]
+    fn test_get_subplot_options() {
+        let options = vec![
+            SubplotOptions::new(),
+            SubplotOptions::new().with_outliers(true.into()).with_fit(FitModel::Linear),
+        ];
+        assert_eq!(get_subplot_options(&options, 2, 1).fit_model, Some(FitModel::Linear));
+        assert_eq!(get_subplot_options(&options, 2, 1).outliers.y, OutlierStrategy::Iqr(1.5));
+        assert_eq!(get_subplot_options(&options[1..], 3, 0), options[1]);
+        assert_eq!(get_subplot_options::<SubplotOptions>(&[], 3, 2), SubplotOptions::default());
+
+        // only the outliers of each subplot
+        let outliers = vec![OutlierConfig::none(), OutlierConfig::from(true)];
+        assert_eq!(get_subplot_options(&outliers, 2, 1), SubplotOptions::new().with_outliers(OutlierConfig::from(true)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_subplot_options_wrong_len() {
+        get_subplot_options(&[SubplotOptions::new(), SubplotOptions::new()], 3, 0);
+    }
+
+    #[test]
+    fn test_legend_keys() {
+        let shared : LegendKeys<RunKey> = Some(RunKey::Parser).into();
+        assert_eq!(shared.get_legend_key(3, 2), Some(RunKey::Parser));
+        assert_eq!(shared.get_shared_key(), Some(Some(RunKey::Parser)));
+
+        let same : LegendKeys<RunKey> = vec![Some(RunKey::Parser), Some(RunKey::Parser)].into();
+        assert_eq!(same.get_shared_key(), Some(Some(RunKey::Parser)));
+
+        let different : LegendKeys<RunKey> = vec![Some(RunKey::Parser), None, Some(RunKey::File)].into();
+        assert_eq!(different.get_legend_key(3, 1), None);
+        assert_eq!(different.get_legend_key(3, 2), Some(RunKey::File));
+        assert_eq!(different.get_shared_key(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_legend_keys_wrong_len() {
+        LegendKeys::PerSubplot(vec![Some(RunKey::Time)]).get_legend_key(2, 0);
+    }
+}
@@ -1,13 +1,23 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Range;
 
 use plotters::backend::BitMapBackend;
-use plotters::chart::{ChartBuilder, SeriesLabelPosition};
 use plotters::coord::Shift;
 use plotters::drawing::DrawingArea;
-use plotters::element::Circle;
-use plotters::style::{Color, Palette, PaletteColor, RGBColor, BLACK, WHITE};
+use plotters::element::{DashedPathElement, PathElement, Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, IntoFont, Palette};
 
+use crate::data::filtering::Filters;
+use crate::data::plottable::Plottable;
 use crate::data::sample::key::SerieKey;
+use crate::data::sample::Sample;
+use crate::params::{FIGURE_TITLE_FONT_SIZE, FIT_CURVE_NB_POINTS, LEGEND_ENTRY_HEIGHT, LEGEND_FONT_SIZE, LEGEND_MARGIN, LEGEND_MAX_LABEL_WIDTH};
+use crate::stat::fitting::FitResult;
+use crate::stat::linspace::Linspace;
+
+use super::figure_style::FigureStyle;
+use super::legend_style::LegendStyle;
 
 
 
@@ -45,77 +55,324 @@ impl Palette for CustomPalette {
 
 
 
-/// draw the legend on the given drawing area
-pub(crate) fn write_legend<Key> (
-    label_drawing_area: &DrawingArea<BitMapBackend<'_>, Shift>,
-    legend_to_color : &HashMap<String, PaletteColor<CustomPalette>>,
-    legend_serie_key : &Option<Key>
-) -> Result<(), Box<dyn std::error::Error>>
-where 
-    Key : SerieKey,
-{
-    // draw the legend on a fantome chart
-    let mut label_chart = 
-    ChartBuilder::on(label_drawing_area)
-    .margin(5)
-    .build_cartesian_2d(0..1, 0..1)?;
+/// how the legends are drawn in the legend box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LegendSymbol {
+    /// the marker of the legend (for points and bars)
+    Marker,
+    /// a line with the dash of the legend
+    Line,
+}
 
-    label_chart
-        .configure_mesh()
-        .disable_mesh()
-        .draw()?;
+/// the placement of the entries of a legend, computed from the size of the labels
+#[derive(Debug, Clone, PartialEq)]
+struct LegendLayout {
+    /// the title of the legend (the display name of the legend key)
+    title : Option<String>,
+    /// the labels to write (truncated), in order
+    labels : Vec<String>,
+    nb_rows : usize,
+    nb_columns : usize,
+    /// the sizes in pixels, with the scale factor of the figure
+    entry_height : u32,
+    padding : u32,
+    column_width : u32,
+    /// the size of the legend box (w, h)
+    size : (u32, u32),
+}
 
+/// width of the symbol of an entry with the space before its label
+const LEGEND_SYMBOL_WIDTH : u32 = 30;
+/// space between the border of the legend box and its entries
+const LEGEND_PADDING : u32 = 5;
+
+/// get the width of a text written in the legend
+fn get_legend_text_width(text : &str, style : &FigureStyle) -> u32 {
+    let font_size = style.px(LEGEND_FONT_SIZE);
+    let font = (style.font_family.as_str(), font_size).into_font();
+    // estimate the size if the font can't be loaded
+    font.box_size(text).map(|(w, _)| w).unwrap_or(text.chars().count() as u32 * font_size / 2)
+}
 
+/// truncate the label with an ellipsis to fit in the given width
+fn truncate_label(label : &str, max_width : u32, style : &FigureStyle) -> String {
+    if get_legend_text_width(label, style) <= max_width {
+        return label.to_string();
+    }
+    let mut chars = label.chars().collect::<Vec<char>>();
+    while chars.pop().is_some() {
+        let truncated = format!("{}…", chars.iter().collect::<String>().trim_end());
+        if get_legend_text_width(&truncated, style) <= max_width {
+            return truncated;
+        }
+    }
+    "…".to_string()
+}
 
+impl LegendLayout {
+    /// place the labels in as many columns as needed to fit in the given height
+    /// NOTE : the entries fill the first column before the next one
+    fn new(labels : &[&String], title : Option<String>, max_height : u32, style : &FigureStyle) -> Self {
+        let max_label_width = style.px(LEGEND_MAX_LABEL_WIDTH);
+        let labels = labels.iter().map(|l| truncate_label(l, max_label_width, style)).collect::<Vec<String>>();
+        let title = title.map(|t| truncate_label(&t, max_label_width, style));
+        let (entry_height, padding) = (style.px(LEGEND_ENTRY_HEIGHT).max(1), style.px(LEGEND_PADDING));
+
+        let nb_title_rows = title.is_some() as u32;
+        let max_nb_rows = (max_height.saturating_sub(2 * padding) / entry_height).saturating_sub(nb_title_rows).max(1) as usize;
+        let nb_columns = labels.len().div_ceil(max_nb_rows).max(1);
+        // balance the rows between the columns
+        let nb_rows = labels.len().div_ceil(nb_columns);
+
+        let max_label_width = labels.iter().map(|l| get_legend_text_width(l, style)).max().unwrap_or(0);
+        let column_width = style.px(LEGEND_SYMBOL_WIDTH) + max_label_width + 2 * padding;
+        let title_width = title.as_ref().map(|t| get_legend_text_width(t, style) + 2 * padding).unwrap_or(0);
+        let size = (
+            (nb_columns as u32 * column_width).max(title_width),
+            (nb_title_rows + nb_rows as u32) * entry_height + 2 * padding,
+        );
+
+        LegendLayout {
+            title,
+            labels,
+            nb_rows,
+            nb_columns,
+            entry_height,
+            padding,
+            column_width,
+            size,
+        }
+    }
 
-    let dummy_data : Vec<(i32, i32)> = Vec::new();
+    /// get the position of the center of the row of the n-th entry, relative to the top left corner of the legend box
+    fn get_entry_position(&self, index : usize) -> (i32, i32) {
+        let nb_title_rows = self.title.is_some() as usize;
+        let (column, row) = (index / self.nb_rows.max(1), index % self.nb_rows.max(1));
+        (
+            (self.padding + column as u32 * self.column_width) as i32,
+            (self.padding + (nb_title_rows + row) as u32 * self.entry_height + self.entry_height / 2) as i32,
+        )
+    }
+}
 
-    // draw phantome serie to get the legend
+/// get the labels to write in a legend (without the empty legends)
+fn get_legend_labels<'a>(legends : impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    legends.filter(|legend| !legend.is_empty()).collect()
+}
 
-    // begin by the first one indicating the key used
-    if let Some(legend_serie_key) = legend_serie_key {
-        let color = RGBColor(255, 255, 255).mix(0.0);
-        
-        let serie_unlabellized = label_chart
-                .draw_series(
-                    dummy_data.iter()
-                        .map(|(x, y)| Circle::new((*x, *y), 2, color.filled())),
-            )?;
-        serie_unlabellized.label(legend_serie_key.get_display_name()).legend(move |(x, y)| {
-            Circle::new((x, y), 5, color.filled())
-        });
+/// collect the legends of all the series (the values of the legend key, "All" without legend key), to size the legend area before drawing
+pub(crate) fn collect_legends<'a, S, Key, Plot>(
+    data : &Plot,
+    legend_serie_key : &Option<Key>,
+    filters : impl Iterator<Item = Option<&'a Filters<Key>>>,
+) -> HashSet<String>
+where
+    Key : SerieKey + 'a,
+    S : Sample<Key>,
+    Plot : Plottable<S, Key>,
+    for<'b> &'b Plot: IntoIterator<Item = S>,
+{
+    match legend_serie_key {
+        Some(legend_serie_key) => filters.flat_map(|filters| data.collect_unique_values(legend_serie_key, filters)).collect(),
+        None => HashSet::from(["All".to_string()]),
     }
+}
+
+/// get the width of the area on the right of the figure to write the legend with the given legends
+/// NOTE : the legend is wrapped in several columns if it's higher than the figure
+pub(crate) fn get_legend_area_width<Key>(legends : &HashSet<String>, legend_serie_key : &Option<Key>, height : u32, style : &FigureStyle) -> u32
+where
+    Key : SerieKey,
+{
+    let labels = get_legend_labels(legends.iter());
+    let margin = style.px(LEGEND_MARGIN);
+    let layout = LegendLayout::new(&labels, legend_serie_key.map(|k| k.get_display_name()), height.saturating_sub(2 * margin), style);
+    layout.size.0 + 2 * margin
+}
 
-    
-    let mut unique_legends = legend_to_color.keys().collect::<Vec<_>>();
-    if legend_serie_key.is_some() && legend_serie_key.unwrap().is_numeric(){
-        unique_legends.sort_by(|a, b| a.parse::<f32>().unwrap().partial_cmp(&b.parse::<f32>().unwrap()).unwrap());
+/// get the height of the title of the figure above the subplots (0 without title)
+pub(crate) fn get_title_height(title : Option<&str>, style : &FigureStyle) -> u32 {
+    title.map_or(0, |_| style.px(FIGURE_TITLE_FONT_SIZE + 20))
+}
+
+/// write the title of the figure at the top of the drawing area (if any)
+/// return the drawing area below the title
+pub(crate) fn write_title<'a>(
+    drawing_area : &DrawingArea<BitMapBackend<'a>, Shift>,
+    title : Option<&str>,
+    style : &FigureStyle,
+) -> Result<DrawingArea<BitMapBackend<'a>, Shift>, Box<dyn std::error::Error>> {
+    let Some(title) = title else {
+        return Ok(drawing_area.clone());
+    };
+    let (title_drawing_area, figure_drawing_area) = drawing_area.split_vertically(get_title_height(Some(title), style));
+    let (width, height) = title_drawing_area.dim_in_pixel();
+    let text_style = style.get_font(FIGURE_TITLE_FONT_SIZE).pos(Pos::new(HPos::Center, VPos::Center));
+    title_drawing_area.draw(&Text::new(title, (width as i32 / 2, height as i32 / 2), text_style))?;
+    Ok(figure_drawing_area)
+}
+
+/// draw the legend box with its top left corner at the given position
+fn draw_legend_box(
+    drawing_area : &DrawingArea<BitMapBackend<'_>, Shift>,
+    layout : &LegendLayout,
+    entries : &[&(String, LegendStyle)],
+    position : (i32, i32),
+    symbol : LegendSymbol,
+    style : &FigureStyle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (x0, y0) = position;
+    let corners = [(x0, y0), (x0 + layout.size.0 as i32, y0 + layout.size.1 as i32)];
+    drawing_area.draw(&Rectangle::new(corners, style.background.mix(0.8).filled()))?;
+    drawing_area.draw(&Rectangle::new(corners, style.get_line_style(&style.foreground, 1)))?;
+
+    let text_style = style.get_font(LEGEND_FONT_SIZE).pos(Pos::new(HPos::Left, VPos::Center));
+    if let Some(title) = layout.title.as_ref() {
+        let y = y0 + (layout.padding + layout.entry_height / 2) as i32;
+        drawing_area.draw(&Text::new(title.clone(), (x0 + layout.padding as i32, y), text_style.clone()))?;
     }
 
-    for legend in unique_legends.iter() {
-        // skip empty legend
-        if legend == &"" {
-            continue;
+    let symbol_width = style.px(LEGEND_SYMBOL_WIDTH) as i32;
+    for (index, ((_, legend_style), label)) in entries.iter().zip(layout.labels.iter()).enumerate() {
+        let (x, y) = layout.get_entry_position(index);
+        let (x, y) = (x0 + x, y0 + y);
+        let line = vec![(x, y), (x + symbol_width * 2 / 3, y)];
+        match (symbol, legend_style.dash.get_pattern()) {
+            (LegendSymbol::Marker, _) => drawing_area.draw(
+                &legend_style.marker.get_element((x + symbol_width / 3, y), style.px(5) as i32, legend_style.color.filled())
+            )?,
+            (LegendSymbol::Line, None) => drawing_area.draw(&PathElement::new(line, style.get_line_style(&legend_style.color, 2)))?,
+            (LegendSymbol::Line, Some((size, spacing))) => drawing_area.draw(
+                &DashedPathElement::new(line, style.px(size), style.px(spacing), style.get_line_style(&legend_style.color, 2))
+            )?,
         }
-        let color = legend_to_color.get(*legend).unwrap();
-        let serie_unlabellized = label_chart
-                .draw_series(
-                    dummy_data.iter()
-                        .map(|(x, y)| Circle::new((*x, *y), 2, color.filled())),
-            )?;
-            serie_unlabellized.label(legend.to_string()).legend(move |(x, y)| {
-                Circle::new((x, y), 5, color.filled())
-            });
+        drawing_area.draw(&Text::new(label.clone(), (x + symbol_width, y), text_style.clone()))?;
     }
+    Ok(())
+}
 
-    label_chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .position(SeriesLabelPosition::MiddleMiddle)
-        .draw()?;
+/// draw the legend in the middle of the given drawing area
+/// NOTE : entries are the legends to draw with their style, in order (nothing is drawn without entry)
+/// NOTE : the legend is wrapped in several columns if it's higher than the drawing area, and the long labels are truncated
+pub(crate) fn write_legend<Key> (
+    label_drawing_area: &DrawingArea<BitMapBackend<'_>, Shift>,
+    entries : &[(String, LegendStyle)],
+    legend_serie_key : &Option<Key>,
+    symbol : LegendSymbol,
+    style : &FigureStyle,
+) -> Result<(), Box<dyn std::error::Error>>
+where 
+    Key : SerieKey,
+{
+    let entries = entries.iter().filter(|(legend, _)| !legend.is_empty()).collect::<Vec<&(String, LegendStyle)>>();
+    // no box without entry (e.g. a scatter plot in density mode)
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let labels = get_legend_labels(entries.iter().map(|(legend, _)| legend));
+    let (width, height) = label_drawing_area.dim_in_pixel();
+    let layout = LegendLayout::new(&labels, legend_serie_key.map(|k| k.get_display_name()), height.saturating_sub(2 * style.px(LEGEND_MARGIN)), style);
+
+    let position = (
+        (width.saturating_sub(layout.size.0) / 2) as i32,
+        (height.saturating_sub(layout.size.1) / 2) as i32,
+    );
+    draw_legend_box(label_drawing_area, &layout, &entries, position, symbol, style)
+}
 
-    Ok(())
+
+/// check if the segment [a, b] crosses the box ((x_min, y_min), (x_max, y_max)), with the Liang-Barsky clipping
+fn crosses_box(a : (i32, i32), b : (i32, i32), corners : [(i32, i32); 2]) -> bool {
+    let [(x_min, y_min), (x_max, y_max)] = corners;
+    let (dx, dy) = ((b.0 - a.0) as f64, (b.1 - a.1) as f64);
+    let (mut t_min, mut t_max) = (0.0, 1.0);
+    // (p, q) of each side of the box : the segment is inside the side for t * p <= q
+    let sides = [
+        (-dx, (a.0 - x_min) as f64),
+        (dx, (x_max - a.0) as f64),
+        (-dy, (a.1 - y_min) as f64),
+        (dy, (y_max - a.1) as f64),
+    ];
+    for (p, q) in sides {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+        } else if p < 0.0 {
+            t_min = f64::max(t_min, q / p);
+        } else {
+            t_max = f64::min(t_max, q / p);
+        }
+    }
+    t_min <= t_max
+}
+
+/// get the top left corner of a legend box of the given size in the corner of the plotting area hiding the fewest data
+/// NOTE : lines are the drawn lines in pixels (a single point for a marker), the corners are tried in order : top right, top left, bottom right, bottom left
+fn get_free_corner(plotting_area : &(Range<i32>, Range<i32>), size : (u32, u32), margin : i32, lines : &[Vec<(i32, i32)>]) -> (i32, i32) {
+    let (x_range, y_range) = plotting_area;
+    let (width, height) = (size.0 as i32, size.1 as i32);
+    let (left, right) = (x_range.start + margin, x_range.end - margin - width);
+    let (top, bottom) = (y_range.start + margin, y_range.end - margin - height);
+    let nb_hidden = |(x, y) : (i32, i32)| {
+        let corners = [(x, y), (x + width, y + height)];
+        lines.iter()
+            .flat_map(|line| line.windows(2).map(|w| (w[0], w[1])).chain(line.first().filter(|_| line.len() == 1).map(|p| (*p, *p))))
+            .filter(|(a, b)| crosses_box(*a, *b, corners))
+            .count()
+    };
+    // min_by_key keeps the first corner in case of equality
+    [(right, top), (left, top), (right, bottom), (left, bottom)]
+        .into_iter()
+        .min_by_key(|corner| nb_hidden(*corner))
+        .unwrap()
+}
+
+/// draw the legend of one subplot inside its chart, in the corner of the plotting area hiding the fewest data
+/// NOTE : plotting_area is the pixel range of the plotting area in the figure, and lines are the lines drawn in it in pixels (a single point for a marker)
+pub(crate) fn write_subplot_legend<Key> (
+    subplot_drawing_area: &DrawingArea<BitMapBackend<'_>, Shift>,
+    plotting_area : &(Range<i32>, Range<i32>),
+    lines : &[Vec<(i32, i32)>],
+    entries : &[(String, LegendStyle)],
+    legend_serie_key : &Option<Key>,
+    symbol : LegendSymbol,
+    style : &FigureStyle,
+) -> Result<(), Box<dyn std::error::Error>>
+where 
+    Key : SerieKey,
+{
+    let entries = entries.iter().filter(|(legend, _)| !legend.is_empty()).collect::<Vec<&(String, LegendStyle)>>();
+    // no box without entry (e.g. a scatter plot in density mode)
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let labels = get_legend_labels(entries.iter().map(|(legend, _)| legend));
+    let margin = style.px(LEGEND_MARGIN);
+    let height = (plotting_area.1.end - plotting_area.1.start).max(0) as u32;
+    let layout = LegendLayout::new(&labels, legend_serie_key.map(|k| k.get_display_name()), height.saturating_sub(2 * margin), style);
+
+    // the position relative to the drawing area of the subplot
+    let (x, y) = get_free_corner(plotting_area, layout.size, margin as i32, lines);
+    let (subplot_x_range, subplot_y_range) = subplot_drawing_area.get_pixel_range();
+    draw_legend_box(subplot_drawing_area, &layout, &entries, (x - subplot_x_range.start, y - subplot_y_range.start), symbol, style)
+}
+
+
+/// get the points of a fitted curve over the x range, split in the segments where the curve is inside the y range
+/// NOTE : the curve is split instead of filtered, to not draw a line between two points on each side of a part out of the y range
+pub(crate) fn get_fit_curve_segments(fit : &FitResult, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<Vec<(f32, f32)>> {
+    let mut segments = vec![Vec::new()];
+    for x in Linspace::new(range_x.start as f64, range_x.end as f64, FIT_CURVE_NB_POINTS).iter() {
+        let y = fit.predict(x) as f32;
+        if y.is_finite() && y >= range_y.start && y <= range_y.end {
+            segments.last_mut().unwrap().push((x as f32, y));
+        } else if !segments.last().unwrap().is_empty() {
+            segments.push(Vec::new());
+        }
+    }
+    segments.retain(|segment| !segment.is_empty());
+    segments
 }
 
 
@@ -149,6 +406,20 @@ pub fn format_number(n: f64) -> String {
     }
 }
 
+/// Format a size in bytes to a string, with binary prefixes (1 KiB = 1024 B)
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let sign = if bytes < 0.0 { "-" } else { "" };
+    let mut value = bytes.abs();
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{}{} {}", sign, format_number(value), UNITS[unit])
+}
+
 /// Format a time in seconds to a string
 pub fn format_duration(seconds: f64) -> String {
     // Total number of seconds in a day, hour, and minute
@@ -234,6 +505,41 @@ mod tests {
         assert_eq!(format_number(-0.000123), "-1.23e-4");
     }
 
+    #[test]
+    fn test_legend_layout() {
+        // the long labels are truncated with an ellipsis
+        let long_label = "a very long label which doesn't fit in the legend".to_string();
+        let style = FigureStyle::default();
+        let truncated = truncate_label(&long_label, LEGEND_MAX_LABEL_WIDTH, &style);
+        assert!(truncated.ends_with('…'));
+        assert!(get_legend_text_width(&truncated, &style) <= LEGEND_MAX_LABEL_WIDTH);
+        assert_eq!(truncate_label("short", LEGEND_MAX_LABEL_WIDTH, &style), "short");
+
+        // the legends are wrapped in several columns when they are higher than the area
+        let labels = (0..25).map(|i| format!("legend {}", i)).collect::<Vec<String>>();
+        let labels = labels.iter().collect::<Vec<&String>>();
+        let layout = LegendLayout::new(&labels, Some("key".to_string()), 11 * LEGEND_ENTRY_HEIGHT + 2 * LEGEND_PADDING, &style);
+        assert_eq!((layout.nb_columns, layout.nb_rows), (3, 9));
+        assert!(layout.size.1 <= 11 * LEGEND_ENTRY_HEIGHT + 2 * LEGEND_PADDING);
+        assert_eq!(layout.get_entry_position(10).0, (LEGEND_PADDING + layout.column_width) as i32);
+
+        let layout = LegendLayout::new(&labels, None, 1000, &style);
+        assert_eq!((layout.nb_columns, layout.nb_rows), (1, 25));
+
+        // the sizes follow the scale factor
+        let scaled = LegendLayout::new(&labels, None, 2000, &style.clone().with_scale(2.0));
+        assert_eq!((scaled.nb_columns, scaled.entry_height), (1, 2 * LEGEND_ENTRY_HEIGHT));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0.0), "0 B");
+        assert_eq!(format_bytes(1023.0), "1023 B");
+        assert_eq!(format_bytes(1024.0), "1 KiB");
+        assert_eq!(format_bytes(5.5 * 1024.0 * 1024.0 * 1024.0), "5.5 GiB");
+        assert_eq!(format_bytes(-2048.0), "-2 KiB");
+    }
+
     #[test]
     fn test_format_duration() {
         // Test for whole days
@@ -258,4 +564,90 @@ mod tests {
         assert_eq!(format_duration(172800.0), "2d 0h 0m 0s");
         assert_eq!(format_duration(172872.0), "2d 0h 1m 12s");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fit_curve_segments() {
+        use crate::stat::fitting::FitModel;
+
+        // y = x^2, out of the y range around 0
+        let fit = FitResult { model : FitModel::Polynomial(2), coefficients : vec![0.0, 0.0, 1.0], r_squared : 1.0 };
+        let segments = get_fit_curve_segments(&fit, &(-2.0..2.0), &(1.0..4.0));
+        assert_eq!(segments.len(), 2);
+        assert!(segments[0].iter().all(|(x, y)| *x < 0.0 && (1.0..=4.0).contains(y)));
+        assert!(segments[1].iter().all(|(x, y)| *x > 0.0 && (1.0..=4.0).contains(y)));
+        assert_eq!(segments.iter().map(|segment| segment.len()).sum::<usize>(), segments[0].len() * 2);
+
+        // a curve inside the y range is a single segment
+        let segments = get_fit_curve_segments(&fit, &(-2.0..2.0), &(0.0..4.0));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].len(), FIT_CURVE_NB_POINTS);
+
+        // a curve out of the y range has no segment
+        assert!(get_fit_curve_segments(&fit, &(-2.0..2.0), &(5.0..6.0)).is_empty());
+    }
+
+    #[test]
+    fn test_no_legend_without_entry() {
+        use plotters::prelude::{IntoDrawingArea, RED};
+        use serde_derive::{Deserialize, Serialize};
+        use std::fmt::{Display, Formatter};
+
+        use crate::generate_plot_key;
+
+        generate_plot_key!(TestKey[
+            A { "A", Numeric }
+        ]);
+
+        // draw the legends on a white figure, return true if something is drawn
+        let draw = |entries : &[(String, LegendStyle)], in_subplot : bool| {
+            let mut buffer = vec![255; 200 * 100 * 3];
+            {
+                let drawing_area = BitMapBackend::with_buffer(&mut buffer, (200, 100)).into_drawing_area();
+                let style = FigureStyle::default();
+                if in_subplot {
+                    write_subplot_legend(&drawing_area, &(0..200, 0..100), &[], entries, &Some(TestKey::A), LegendSymbol::Marker, &style).unwrap();
+                } else {
+                    write_legend(&drawing_area, entries, &Some(TestKey::A), LegendSymbol::Line, &style).unwrap();
+                }
+                drawing_area.present().unwrap();
+            }
+            buffer.iter().any(|c| *c != 255)
+        };
+
+        for in_subplot in [false, true] {
+            assert!(!draw(&[], in_subplot));
+            assert!(!draw(&[(String::new(), LegendStyle::new(RED))], in_subplot));
+            assert!(draw(&[("a".to_string(), LegendStyle::new(RED))], in_subplot));
+        }
+    }
+
+    #[test]
+    fn test_crosses_box() {
+        let corners = [(10, 10), (20, 20)];
+        assert!(crosses_box((0, 15), (30, 15), corners));
+        assert!(crosses_box((15, 15), (15, 15), corners));
+        assert!(crosses_box((0, 0), (30, 30), corners));
+        assert!(!crosses_box((0, 0), (30, 5), corners));
+        assert!(!crosses_box((0, 25), (25, 30), corners));
+        assert!(!crosses_box((5, 5), (5, 5), corners));
+    }
+
+    #[test]
+    fn test_free_corner() {
+        let plotting_area = (0..100, 0..100);
+        let (size, margin) = ((20, 10), 5);
+        let (right, left, top, bottom) = (75, 5, 5, 85);
+
+        // the top right corner by default
+        assert_eq!(get_free_corner(&plotting_area, size, margin, &[]), (right, top));
+        // a line rising to the top right corner
+        let rising = vec![(0, 100), (100, 0)];
+        assert_eq!(get_free_corner(&plotting_area, size, margin, &[rising]), (left, top));
+        // a line through the top, and points in the bottom right corner
+        let lines = [vec![(0, 10), (100, 10)], vec![(90, 90)], vec![(80, 92)]];
+        assert_eq!(get_free_corner(&plotting_area, size, margin, &lines), (left, bottom));
+        // the corner hiding the fewest data if all of them hide some
+        let everywhere = [vec![(90, 10)], vec![(90, 12)], vec![(10, 10)], vec![(90, 90)], vec![(10, 90)], vec![(12, 90)]];
+        assert_eq!(get_free_corner(&plotting_area, size, margin, &everywhere), (left, top));
+    }
+}
@@ -8,21 +8,89 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 #[cfg(not(feature = "parrallelize"))]
 use std::collections::HashMap;
 
+use std::collections::BTreeMap;
+
 use super::linspace::Linspace;
 
 
 const SERIE_DIVISION : usize = 500;
 
+/// by default, the series with fewer points are drawn without compression
+const COMPRESSION_MIN_NB_POINTS : usize = SERIE_DIVISION;
+
+
+/// how the points of a serie are reduced to accelerate the plotting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressionMethod {
+    /// all the points are drawn
+    None,
+    /// the average of the points in each cell of a grid with the given number of divisions on each axis
+    /// NOTE : the points are moved to the averages, even the isolated ones
+    Grid(usize),
+    /// Largest-Triangle-Three-Buckets downsampling to the given number of points (line plots)
+    /// NOTE : the points kept are points of the serie, the first and the last points and the peaks are kept
+    Lttb(usize),
+}
+
+/// the compression of the series of a plot
+/// NOTE : the series with at most min_nb_points points are kept as they are, the compressed series are sorted by x
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Compression {
+    pub method : CompressionMethod,
+    pub min_nb_points : usize,
+}
+
+impl Compression {
+    pub fn new(method : CompressionMethod) -> Self {
+        Compression {
+            method,
+            min_nb_points : 0,
+        }
+    }
+
+    /// keep all the points
+    pub fn none() -> Self {
+        Self::new(CompressionMethod::None)
+    }
+
+    /// only compress the series with more than min_nb_points points
+    pub fn with_min_nb_points(mut self, min_nb_points : usize) -> Self {
+        self.min_nb_points = min_nb_points;
+        self
+    }
+
+    /// compress the serie over the given ranges, the result is sorted by x (then by y)
+    pub fn apply(&self, to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
+        let mut compressed = match self.method {
+            _ if to_compress.len() <= self.min_nb_points => to_compress,
+            CompressionMethod::None => to_compress,
+            CompressionMethod::Grid(nb_divisions) => compress_data_serie(to_compress, range_x, range_y, nb_divisions.max(1)),
+            CompressionMethod::Lttb(nb_points) => {
+                let mut to_compress = to_compress;
+                to_compress.sort_by(|a, b| a.0.total_cmp(&b.0));
+                lttb(&to_compress, nb_points)
+            },
+        };
+        compressed.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.total_cmp(&b.1)));
+        compressed
+    }
+}
+
+impl Default for Compression {
+    /// the grid of SERIE_DIVISION divisions, on the series with more than SERIE_DIVISION points
+    fn default() -> Self {
+        Compression::new(CompressionMethod::Grid(SERIE_DIVISION)).with_min_nb_points(COMPRESSION_MIN_NB_POINTS)
+    }
+}
+
+
 /// The goal of the function is to compress a dataserie to accelerate the plotting
-/// it will cut the graph into piece en make the average of each piece
-/// NOTE : The function take the borow of the data to avoid the memory allocation
+/// it will cut the graph into nb_divisions pieces on each axis and make the average of each piece
+/// NOTE : The function take the ownership of the data to avoid the memory allocation
 #[cfg(not(feature = "parrallelize"))]
-pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
-    // cut the range into SERIE_DIVISION pieces
-
-    
-    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, SERIE_DIVISION);
-    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, SERIE_DIVISION);
+pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>, nb_divisions : usize) -> Vec<(f32, f32)> {
+    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, nb_divisions);
+    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, nb_divisions);
 
     // create the hashmap to store the data
     // the key is the index of the piece in the x and y linspace
@@ -48,23 +116,16 @@ pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Rang
     result
 }
 
-
-
 /// The goal of the function is to compress a dataserie to accelerate the plotting
-/// it will cut the graph into piece en make the average of each piece
-/// NOTE : The function take the borow of the data to avoid a double memory allocation
+/// it will cut the graph into nb_divisions pieces on each axis and make the average of each piece
+/// NOTE : The function take the ownership of the data to avoid a double memory allocation
 #[cfg(feature = "parrallelize")]
-pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
-    // cut the range into SERIE_DIVISION pieces
-
-   
+pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>, nb_divisions : usize) -> Vec<(f32, f32)> {
+    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, nb_divisions);
+    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, nb_divisions);
 
-    
-    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, SERIE_DIVISION);
-    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, SERIE_DIVISION);
-
-    let mut discrete_points : Vec<Mutex<(f64, f64, u64)>> = Vec::with_capacity(SERIE_DIVISION*SERIE_DIVISION);
-    for _ in 0..SERIE_DIVISION*SERIE_DIVISION {
+    let mut discrete_points : Vec<Mutex<(f64, f64, u64)>> = Vec::with_capacity(nb_divisions*nb_divisions);
+    for _ in 0..nb_divisions*nb_divisions {
         discrete_points.push(Mutex::new((0.0, 0.0, 0)));
     }
 
@@ -72,7 +133,7 @@ pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Rang
     to_compress.into_par_iter().map(|(x, y)| {
         let x_index = x_linspace.index_of(&(x as f64)).unwrap();
         let y_index = y_linspace.index_of(&(y as f64)).unwrap();
-        let index = x_index * SERIE_DIVISION + y_index;
+        let index = x_index * nb_divisions + y_index;
         let mut value = discrete_points[index].lock().unwrap(); // block until the lock is available (in scope)
         value.2 += 1;
         value.0 += (x as f64 - value.0) / value.2 as f64;
@@ -88,6 +149,160 @@ pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Rang
     }).collect()
 }
 
+/// Largest-Triangle-Three-Buckets : keep nb_points points of a serie sorted by x
+/// the first and the last points are kept, then one point per bucket : the one making the largest triangle with the point kept in the previous bucket and the average of the next bucket
+/// NOTE : the serie is returned as it is if it has at most nb_points points (or nb_points < 3)
+pub(crate) fn lttb(serie : &[(f32, f32)], nb_points : usize) -> Vec<(f32, f32)> {
+    if nb_points < 3 || serie.len() <= nb_points {
+        return serie.to_vec();
+    }
+    // the points between the first and the last one are cut into nb_points - 2 buckets
+    let bucket_size = (serie.len() - 2) as f64 / (nb_points - 2) as f64;
+    let bucket = |index : usize| {
+        let start = (index as f64 * bucket_size) as usize + 1;
+        let end = (((index + 1) as f64 * bucket_size) as usize + 1).min(serie.len() - 1);
+        &serie[start..end.max(start + 1)]
+    };
+
+    let mut sampled = Vec::with_capacity(nb_points);
+    sampled.push(serie[0]);
+    let mut previous = serie[0];
+    for index in 0..nb_points - 2 {
+        // the average of the next bucket, the last point after the last bucket
+        let next = if index + 1 < nb_points - 2 {
+            let next_bucket = bucket(index + 1);
+            let sum = next_bucket.iter().fold((0.0, 0.0), |sum, (x, y)| (sum.0 + *x as f64, sum.1 + *y as f64));
+            (sum.0 / next_bucket.len() as f64, sum.1 / next_bucket.len() as f64)
+        } else {
+            let last = serie[serie.len() - 1];
+            (last.0 as f64, last.1 as f64)
+        };
+        let (px, py) = (previous.0 as f64, previous.1 as f64);
+        let area = |(x, y) : &(f32, f32)| ((px - next.0) * (*y as f64 - py) - (px - *x as f64) * (next.1 - py)).abs();
+        let selected = bucket(index).iter()
+            .max_by(|a, b| area(a).total_cmp(&area(b)))
+            .copied()
+            .unwrap_or(previous);
+        sampled.push(selected);
+        previous = selected;
+    }
+    sampled.push(serie[serie.len() - 1]);
+    sampled
+}
+
+
+
+/// the shape of the bins used to count the points of a density
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DensityBins {
+    /// a grid with the given number of bins on each axis
+    Rectangular(usize),
+    /// hexagons, with the given number of hexagons on the x axis
+    Hexagonal(usize),
+}
+
+/// a bin of a density
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityBin {
+    pub center : (f32, f32),
+    pub count : usize,
+}
+
+impl DensityBins {
+    /// count the points in each bin over the ranges, the points outside the ranges are ignored
+    /// NOTE : the empty bins are not returned, the bins are sorted by x then by y
+    pub fn bin_data_serie(&self, points : &[(f32, f32)], range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<DensityBin> {
+        let mut counts : BTreeMap<(i64, i64), usize> = BTreeMap::new();
+        let (width, height) = (range_x.end - range_x.start, range_y.end - range_y.start);
+
+        match self {
+            DensityBins::Rectangular(nb_bins) => {
+                let nb_bins = (*nb_bins).max(1);
+                // the same cut of the range than the compression, with one more bound to get nb_bins bins
+                let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, nb_bins + 1);
+                let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, nb_bins + 1);
+                for (x, y) in points.iter() {
+                    let (Some(x_index), Some(y_index)) = (x_linspace.index_of(&(*x as f64)), y_linspace.index_of(&(*y as f64))) else {
+                        continue;
+                    };
+                    // the end of the range is in the last bin
+                    *counts.entry((x_index.min(nb_bins - 1) as i64, y_index.min(nb_bins - 1) as i64)).or_insert(0) += 1;
+                }
+                counts.into_iter().map(|((i, j), count)| DensityBin {
+                    center : (
+                        range_x.start + (i as f32 + 0.5) * width / nb_bins as f32,
+                        range_y.start + (j as f32 + 0.5) * height / nb_bins as f32,
+                    ),
+                    count,
+                }).collect()
+            },
+            DensityBins::Hexagonal(_) => {
+                // two shifted lattices in the normalized space, the nearest center give the hexagon (like matplotlib hexbin)
+                let (sx, sy) = self.get_hexagon_steps();
+                for (x, y) in points.iter() {
+                    if *x < range_x.start || *x > range_x.end || *y < range_y.start || *y > range_y.end {
+                        continue;
+                    }
+                    let u = ((x - range_x.start) / width) as f64 / sx;
+                    let v = ((y - range_y.start) / height) as f64 / sy;
+                    let (i1, j1) = (u.round(), v.round());
+                    let (i2, j2) = (u.floor(), v.floor());
+                    let d1 = (u - i1).powi(2) + 3.0 * (v - j1).powi(2);
+                    let d2 = (u - i2 - 0.5).powi(2) + 3.0 * (v - j2 - 0.5).powi(2);
+                    // the first lattice has even indexes, the second one odd indexes
+                    let key = if d1 <= d2 { (2 * i1 as i64, 2 * j1 as i64) } else { (2 * i2 as i64 + 1, 2 * j2 as i64 + 1) };
+                    *counts.entry(key).or_insert(0) += 1;
+                }
+                counts.into_iter().map(|((i, j), count)| DensityBin {
+                    center : (
+                        range_x.start + (i as f64 / 2.0 * sx) as f32 * width,
+                        range_y.start + (j as f64 / 2.0 * sy) as f32 * height,
+                    ),
+                    count,
+                }).collect()
+            },
+        }
+    }
+
+    /// the steps of the hexagonal lattice in the normalized space (between 0 and 1)
+    fn get_hexagon_steps(&self) -> (f64, f64) {
+        let nb_bins = match self {
+            DensityBins::Rectangular(nb_bins) | DensityBins::Hexagonal(nb_bins) => (*nb_bins).max(1),
+        };
+        let sx = 1.0 / nb_bins as f64;
+        (sx, sx * 3.0_f64.sqrt())
+    }
+
+    /// get the vertices of the bin with the given center
+    pub fn get_bin_vertices(&self, center : (f32, f32), range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
+        let (width, height) = (range_x.end - range_x.start, range_y.end - range_y.start);
+        match self {
+            DensityBins::Rectangular(nb_bins) => {
+                let half_x = width / (*nb_bins).max(1) as f32 / 2.0;
+                let half_y = height / (*nb_bins).max(1) as f32 / 2.0;
+                vec![
+                    (center.0 - half_x, center.1 - half_y),
+                    (center.0 + half_x, center.1 - half_y),
+                    (center.0 + half_x, center.1 + half_y),
+                    (center.0 - half_x, center.1 + half_y),
+                ]
+            },
+            DensityBins::Hexagonal(_) => {
+                // pointy-top hexagon, the circumradius is sx / sqrt(3) in the normalized space
+                let (sx, _) = self.get_hexagon_steps();
+                let radius = sx / 3.0_f64.sqrt();
+                (0..6).map(|k| {
+                    let angle = std::f64::consts::PI / 6.0 + k as f64 * std::f64::consts::PI / 3.0;
+                    (
+                        center.0 + (radius * angle.cos()) as f32 * width,
+                        center.1 + (radius * angle.sin()) as f32 * height,
+                    )
+                }).collect()
+            },
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -99,7 +314,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert_eq!(compressed.len(), 0);
     }
@@ -110,7 +325,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert_eq!(compressed.len(), 1);
         assert_eq!(compressed[0], (5.0, 5.0));
@@ -122,7 +337,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() == 2);
     }
@@ -133,7 +348,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() == 2);
     }
@@ -145,9 +360,83 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() <= SERIE_DIVISION * SERIE_DIVISION);
         // Further checks can assess distribution and averaging correctness
     }
+
+    #[test]
+    fn test_sorted_compression() {
+        let to_compress = (0..2000).rev().map(|i| (i as f32 / 100.0, (i % 7) as f32)).collect::<Vec<(f32, f32)>>();
+        let range_x = Range { start: 0.0, end: 20.0 };
+        let range_y = Range { start: 0.0, end: 6.0 };
+
+        let compressed = Compression::new(CompressionMethod::Grid(10)).apply(to_compress.clone(), &range_x, &range_y);
+        assert!(compressed.len() <= 100);
+        assert!(compressed.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // the small series are kept, sorted
+        let small = vec![(3.0, 1.0), (1.0, 2.0), (2.0, 3.0)];
+        assert_eq!(Compression::default().apply(small, &range_x, &range_y), vec![(1.0, 2.0), (2.0, 3.0), (3.0, 1.0)]);
+        assert_eq!(Compression::none().apply(to_compress, &range_x, &range_y).len(), 2000);
+    }
+
+    #[test]
+    fn test_lttb() {
+        // a flat serie with a peak : the peak, the first and the last points are kept
+        let mut serie = (0..100).map(|i| (i as f32, 0.0)).collect::<Vec<(f32, f32)>>();
+        serie[42].1 = 10.0;
+        let sampled = lttb(&serie, 10);
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled[0], (0.0, 0.0));
+        assert_eq!(sampled[9], (99.0, 0.0));
+        assert!(sampled.contains(&(42.0, 10.0)));
+        assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+        // the points are points of the serie
+        assert!(sampled.iter().all(|point| serie.contains(point)));
+
+        assert_eq!(lttb(&serie[..5], 10), serie[..5].to_vec());
+
+        let range = Range { start: 0.0, end: 100.0 };
+        let compressed = Compression::new(CompressionMethod::Lttb(10)).apply(serie.into_iter().rev().collect(), &range, &range);
+        assert_eq!(compressed.len(), 10);
+        assert!(compressed.contains(&(42.0, 10.0)));
+    }
+
+    #[test]
+    fn test_rectangular_density() {
+        let points = vec![(0.0, 0.0), (1.0, 1.0), (4.0, 4.0), (10.0, 10.0), (11.0, 0.0)];
+        let range = Range { start: 0.0, end: 10.0 };
+
+        let bins = DensityBins::Rectangular(2).bin_data_serie(&points, &range, &range);
+        assert_eq!(bins, vec![
+            DensityBin { center : (2.5, 2.5), count : 3 },
+            DensityBin { center : (7.5, 7.5), count : 1 },
+        ]);
+        assert_eq!(
+            DensityBins::Rectangular(2).get_bin_vertices((2.5, 2.5), &range, &range),
+            vec![(0.0, 0.0), (5.0, 0.0), (5.0, 5.0), (0.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn test_hexagonal_density() {
+        let range = Range { start: 0.0, end: 1.0 };
+        // (0.5 * sx, 0.5 * sy) is the center of the first hexagon of the second lattice, with 2 hexagons on x
+        let center = (0.25, (3.0_f64.sqrt() / 4.0) as f32);
+        let points = vec![(0.0, 0.0), (0.01, 0.01), center, (center.0 + 0.05, center.1 - 0.05)];
+
+        let bins = DensityBins::Hexagonal(2).bin_data_serie(&points, &range, &range);
+        assert_eq!(bins.len(), 2);
+        assert_eq!(bins[0], DensityBin { center : (0.0, 0.0), count : 2 });
+        assert_eq!(bins[1].count, 2);
+        assert!((bins[1].center.0 - center.0).abs() < 1e-6 && (bins[1].center.1 - center.1).abs() < 1e-6);
+
+        let total : usize = DensityBins::Hexagonal(10).bin_data_serie(
+            &(0..100).map(|i| ((i % 10) as f32 / 10.0, (i / 10) as f32 / 10.0)).collect::<Vec<(f32, f32)>>(), &range, &range
+        ).iter().map(|b| b.count).sum();
+        assert_eq!(total, 100);
+        assert_eq!(DensityBins::Hexagonal(2).get_bin_vertices((0.0, 0.0), &range, &range).len(), 6);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::fmt::{Display, Formatter};
+
+use crate::plotter::utils::format_number;
+use crate::static_html::presentation_data::Array;
+
+use super::hypothesis::rank_with_ties;
+
+
+/// the correlation coefficients between two series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CorrelationMethod {
+    /// linear correlation
+    Pearson,
+    /// linear correlation of the ranks (monotonic correlation)
+    Spearman,
+}
+
+impl Display for CorrelationMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorrelationMethod::Pearson => write!(f, "pearson"),
+            CorrelationMethod::Spearman => write!(f, "spearman"),
+        }
+    }
+}
+
+
+/// get the correlation coefficient between x and y (between -1 and 1)
+/// NOTE : the pairs with a NaN value are ignored
+/// return NaN if there is less than 2 pairs or if one of the series is constant
+pub fn correlation(x : &[f32], y : &[f32], method : CorrelationMethod) -> f64 {
+    let (xs, ys) : (Vec<f64>, Vec<f64>) = x.iter().zip(y.iter())
+        .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+        .map(|(a, b)| (*a as f64, *b as f64))
+        .unzip();
+    if xs.len() < 2 {
+        return f64::NAN;
+    }
+
+    match method {
+        CorrelationMethod::Pearson => pearson(&xs, &ys),
+        CorrelationMethod::Spearman => pearson(&rank_with_ties(&xs).0, &rank_with_ties(&ys).0),
+    }
+}
+
+fn pearson(xs : &[f64], ys : &[f64]) -> f64 {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        covariance += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return f64::NAN;
+    }
+    (covariance / (var_x * var_y).sqrt()).clamp(-1.0, 1.0)
+}
+
+
+/// the correlation of each pair of series
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationMatrix {
+    method : CorrelationMethod,
+    labels : Vec<String>,
+    /// values[i][j] is the correlation between the serie i and the serie j
+    values : Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    /// compute the correlation matrix of the series, the labels are the names of the series
+    pub fn new(labels : Vec<String>, series : &[Vec<f32>], method : CorrelationMethod) -> Self {
+        if labels.len() != series.len() {
+            panic!("The number of labels ({}) is not equal to the number of series ({})", labels.len(), series.len());
+        }
+        let mut values = vec![vec![f64::NAN; series.len()]; series.len()];
+        for i in 0..series.len() {
+            for j in i..series.len() {
+                let value = correlation(&series[i], &series[j], method);
+                values[i][j] = value;
+                values[j][i] = value;
+            }
+        }
+
+        CorrelationMatrix {
+            method,
+            labels,
+            values,
+        }
+    }
+
+    pub fn get_method(&self) -> CorrelationMethod {
+        self.method
+    }
+
+    pub fn get_labels(&self) -> &Vec<String> {
+        &self.labels
+    }
+
+    pub fn get_values(&self) -> &Vec<Vec<f64>> {
+        &self.values
+    }
+
+    /// get the correlation between the series with the given labels
+    pub fn get(&self, label_1 : &str, label_2 : &str) -> Option<f64> {
+        let i = self.labels.iter().position(|l| l == label_1)?;
+        let j = self.labels.iter().position(|l| l == label_2)?;
+        Some(self.values[i][j])
+    }
+
+    /// Create the correlation table, to include it in a static html presentation
+    pub fn to_array(&self) -> Array {
+        let mut header = vec![self.method.to_string()];
+        header.extend(self.labels.iter().cloned());
+
+        let data = self.labels.iter().zip(self.values.iter()).map(|(label, row)| {
+            let mut line = vec![label.clone()];
+            line.extend(row.iter().map(|v| format_number(*v)));
+            line
+        }).collect();
+
+        Array::new(header, data)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_correlation() {
+        let x = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let y = vec![2.0, 4.0, 6.0, 8.0, 10.0];
+        let z = vec![1.0, 8.0, 27.0, 64.0, 125.0];
+        assert_relative_eq!(correlation(&x, &y, CorrelationMethod::Pearson), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(correlation(&x, &z, CorrelationMethod::Spearman), 1.0, epsilon = 1e-12);
+        assert!(correlation(&x, &z, CorrelationMethod::Pearson) < 0.99);
+
+        let reversed = vec![5.0, 4.0, 3.0, 2.0, 1.0];
+        assert_relative_eq!(correlation(&x, &reversed, CorrelationMethod::Pearson), -1.0, epsilon = 1e-12);
+
+        // x = [1, 2, 3, 4], y = [1, 3, 2, 4] -> 0.8
+        assert_relative_eq!(correlation(&[1.0, 2.0, 3.0, 4.0], &[1.0, 3.0, 2.0, 4.0], CorrelationMethod::Spearman), 0.8, epsilon = 1e-12);
+
+        // NaN values are ignored, constant series have no correlation
+        assert_relative_eq!(correlation(&[1.0, f32::NAN, 2.0, 3.0], &[1.0, 5.0, 2.0, 3.0], CorrelationMethod::Pearson), 1.0, epsilon = 1e-12);
+        assert!(correlation(&x, &[1.0; 5], CorrelationMethod::Pearson).is_nan());
+        assert!(correlation(&[1.0], &[1.0], CorrelationMethod::Pearson).is_nan());
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        let labels = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let series = vec![vec![1.0, 2.0, 3.0], vec![3.0, 2.0, 1.0], vec![1.0, 3.0, 2.0]];
+        let matrix = CorrelationMatrix::new(labels, &series, CorrelationMethod::Pearson);
+
+        assert_relative_eq!(matrix.get("a", "a").unwrap(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(matrix.get("a", "b").unwrap(), -1.0, epsilon = 1e-12);
+        assert_relative_eq!(matrix.get("c", "a").unwrap(), 0.5, epsilon = 1e-12);
+        assert_eq!(matrix.get("a", "d"), None);
+
+        let array = matrix.to_array();
+        assert_eq!(array.get_header(), &vec!["pearson".to_string(), "a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(array.get_data()[1], vec!["b".to_string(), "-1".to_string(), "1".to_string(), "-0.5".to_string()]);
+    }
+}
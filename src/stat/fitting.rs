@@ -0,0 +1,294 @@
+use std::fmt::{Display, Formatter};
+
+use crate::plotter::utils::format_number;
+use crate::static_html::presentation_data::Array;
+
+
+/// the models that can be fitted with the least squares method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FitModel {
+    /// y = a + b * x
+    Linear,
+    /// y = c0 + c1 * x + ... + cn * x^n (n is the degree)
+    Polynomial(usize),
+    /// y = a * x^b (only the points with x > 0 and y > 0 are used)
+    PowerLaw,
+    /// y = a * exp(b * x) (only the points with y > 0 are used)
+    Exponential,
+    /// y = a + b * ln(x) (only the points with x > 0 are used)
+    Logarithmic,
+}
+
+impl Display for FitModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FitModel::Linear => write!(f, "linear"),
+            FitModel::Polynomial(degree) => write!(f, "polynomial (degree {})", degree),
+            FitModel::PowerLaw => write!(f, "power law"),
+            FitModel::Exponential => write!(f, "exponential"),
+            FitModel::Logarithmic => write!(f, "logarithmic"),
+        }
+    }
+}
+
+
+/// the result of a fit
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitResult {
+    pub model : FitModel,
+    /// the coefficients of the model, in the order of the formula of the model (a, b) or (c0, ..., cn)
+    pub coefficients : Vec<f64>,
+    /// the coefficient of determination, computed on the original values
+    pub r_squared : f64,
+}
+
+impl FitResult {
+    /// Fit the model to the points with the least squares method
+    /// NOTE : the power law, exponential and logarithmic models are fitted linearly on the log of the values
+    /// return None if there isn't enough valid points to fit the model
+    pub fn new(model : FitModel, points : &[(f32, f32)]) -> Option<Self> {
+        let (xs, ys) : (Vec<f64>, Vec<f64>) = points.iter()
+            .map(|(x, y)| (*x as f64, *y as f64))
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .filter(|(x, y)| match model {
+                FitModel::Linear | FitModel::Polynomial(_) => true,
+                FitModel::PowerLaw => *x > 0.0 && *y > 0.0,
+                FitModel::Exponential => *y > 0.0,
+                FitModel::Logarithmic => *x > 0.0,
+            })
+            .unzip();
+
+        let coefficients = match model {
+            FitModel::Linear => polynomial_least_squares(&xs, &ys, 1)?,
+            FitModel::Polynomial(degree) => polynomial_least_squares(&xs, &ys, degree)?,
+            FitModel::PowerLaw => {
+                let log_xs = xs.iter().map(|x| x.ln()).collect::<Vec<f64>>();
+                let log_ys = ys.iter().map(|y| y.ln()).collect::<Vec<f64>>();
+                let line = polynomial_least_squares(&log_xs, &log_ys, 1)?;
+                vec![line[0].exp(), line[1]]
+            },
+            FitModel::Exponential => {
+                let log_ys = ys.iter().map(|y| y.ln()).collect::<Vec<f64>>();
+                let line = polynomial_least_squares(&xs, &log_ys, 1)?;
+                vec![line[0].exp(), line[1]]
+            },
+            FitModel::Logarithmic => {
+                let log_xs = xs.iter().map(|x| x.ln()).collect::<Vec<f64>>();
+                polynomial_least_squares(&log_xs, &ys, 1)?
+            },
+        };
+
+        let mut result = FitResult {
+            model,
+            coefficients,
+            r_squared : f64::NAN,
+        };
+        result.r_squared = result.compute_r_squared(&xs, &ys);
+        Some(result)
+    }
+
+    /// get the value of the fitted model at x
+    pub fn predict(&self, x : f64) -> f64 {
+        let c = &self.coefficients;
+        match self.model {
+            FitModel::Linear | FitModel::Polynomial(_) => c.iter().rev().fold(0.0, |acc, coefficient| acc * x + coefficient),
+            FitModel::PowerLaw => c[0] * x.powf(c[1]),
+            FitModel::Exponential => c[0] * (c[1] * x).exp(),
+            FitModel::Logarithmic => c[0] + c[1] * x.ln(),
+        }
+    }
+
+    /// get the formula of the fitted model, e.g. "y = 1.2 + 3.4x"
+    pub fn get_formula(&self) -> String {
+        let c = self.coefficients.iter().map(|c| format_number(*c)).collect::<Vec<String>>();
+        match self.model {
+            FitModel::Linear | FitModel::Polynomial(_) => {
+                let terms = c.iter().enumerate().map(|(power, coefficient)| match power {
+                    0 => coefficient.clone(),
+                    1 => format!("{}x", coefficient),
+                    _ => format!("{}x^{}", coefficient, power),
+                }).collect::<Vec<String>>();
+                format!("y = {}", terms.join(" + "))
+            },
+            FitModel::PowerLaw => format!("y = {}x^{}", c[0], c[1]),
+            FitModel::Exponential => format!("y = {}exp({}x)", c[0], c[1]),
+            FitModel::Logarithmic => format!("y = {} + {}ln(x)", c[0], c[1]),
+        }
+    }
+
+    fn compute_r_squared(&self, xs : &[f64], ys : &[f64]) -> f64 {
+        let mean = ys.iter().sum::<f64>() / ys.len() as f64;
+        let total = ys.iter().map(|y| (y - mean).powi(2)).sum::<f64>();
+        let residual = xs.iter().zip(ys.iter()).map(|(x, y)| (y - self.predict(*x)).powi(2)).sum::<f64>();
+        if total == 0.0 {
+            return if residual == 0.0 { 1.0 } else { f64::NAN };
+        }
+        1.0 - residual / total
+    }
+
+    /// Create a table of the fits, one row per legend (sorted by legend)
+    pub fn to_array<'a, It>(fits : It) -> Array
+    where
+        It : IntoIterator<Item = (&'a String, &'a FitResult)>,
+    {
+        let header = vec![
+            "legend".to_string(),
+            "model".to_string(),
+            "formula".to_string(),
+            "R²".to_string(),
+        ];
+
+        let mut data = fits.into_iter().map(|(legend, fit)| vec![
+            legend.clone(),
+            fit.model.to_string(),
+            fit.get_formula(),
+            format_number(fit.r_squared),
+        ]).collect::<Vec<Vec<String>>>();
+        data.sort();
+
+        Array::new(header, data)
+    }
+}
+
+impl Display for FitResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (R² = {})", self.get_formula(), format_number(self.r_squared))
+    }
+}
+
+
+/// get the coefficients (c0, ..., cn) of the polynomial minimizing the squared error
+/// return None if there isn't enough distinct points
+fn polynomial_least_squares(xs : &[f64], ys : &[f64], degree : usize) -> Option<Vec<f64>> {
+    let size = degree + 1;
+    if xs.len() < size {
+        return None;
+    }
+
+    // center and scale x to keep the normal equations well conditioned
+    let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+    let scale = xs.iter().map(|x| (x - mean).abs()).fold(0.0, f64::max);
+    if scale == 0.0 {
+        return None;
+    }
+
+    // normal equations (A^T A) c = A^T y
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        let u = (x - mean) / scale;
+        let powers = (0..size).map(|i| u.powi(i as i32)).collect::<Vec<f64>>();
+        for row in 0..size {
+            for col in 0..size {
+                matrix[row][col] += powers[row] * powers[col];
+            }
+            matrix[row][size] += powers[row] * y;
+        }
+    }
+    let scaled_coefficients = solve_linear_system(matrix)?;
+
+    // expand the polynomial in u = (x - mean) / scale to a polynomial in x
+    let mut coefficients = vec![0.0; size];
+    // (x - mean)^k, coefficients in x
+    let mut shifted_power = vec![1.0];
+    for (k, scaled_coefficient) in scaled_coefficients.iter().enumerate() {
+        let factor = scaled_coefficient / scale.powi(k as i32);
+        for (power, value) in shifted_power.iter().enumerate() {
+            coefficients[power] += factor * value;
+        }
+        let mut next = vec![0.0; shifted_power.len() + 1];
+        for (power, value) in shifted_power.iter().enumerate() {
+            next[power + 1] += value;
+            next[power] -= mean * value;
+        }
+        shifted_power = next;
+    }
+
+    Some(coefficients)
+}
+
+/// solve the linear system given as an augmented matrix, with the Gaussian elimination (partial pivoting)
+fn solve_linear_system(mut matrix : Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let size = matrix.len();
+    for col in 0..size {
+        let pivot = (col..size).max_by(|a, b| matrix[*a][col].abs().partial_cmp(&matrix[*b][col].abs()).unwrap())?;
+        if matrix[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        let (upper_rows, lower_rows) = matrix.split_at_mut(col + 1);
+        let pivot_row = &upper_rows[col];
+        for row in lower_rows.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row[col..].iter_mut().zip(pivot_row[col..].iter()) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev() {
+        let sum = ((row + 1)..size).map(|k| matrix[row][k] * solution[k]).sum::<f64>();
+        solution[row] = (matrix[row][size] - sum) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_linear_and_polynomial_fit() {
+        let points = (0..10).map(|x| (x as f32, 2.0 + 3.0 * x as f32)).collect::<Vec<(f32, f32)>>();
+        let fit = FitResult::new(FitModel::Linear, &points).unwrap();
+        assert_relative_eq!(fit.coefficients[0], 2.0, epsilon = 1e-6);
+        assert_relative_eq!(fit.coefficients[1], 3.0, epsilon = 1e-6);
+        assert_relative_eq!(fit.r_squared, 1.0, epsilon = 1e-9);
+        assert_eq!(fit.get_formula(), "y = 2 + 3x");
+
+        let points = (100..110).map(|x| (x as f32, 1.0 - 2.0 * x as f32 + 0.5 * (x * x) as f32)).collect::<Vec<(f32, f32)>>();
+        let fit = FitResult::new(FitModel::Polynomial(2), &points).unwrap();
+        assert_relative_eq!(fit.predict(120.0), 1.0 - 240.0 + 0.5 * 14400.0, epsilon = 1e-2);
+        assert_relative_eq!(fit.coefficients[2], 0.5, epsilon = 1e-6);
+
+        // not enough points
+        assert!(FitResult::new(FitModel::Polynomial(3), &points[..3]).is_none());
+        assert!(FitResult::new(FitModel::Linear, &[(1.0, 1.0), (1.0, 2.0)]).is_none());
+    }
+
+    #[test]
+    fn test_log_fits() {
+        let points = (1..10).map(|x| (x as f32, 3.0 * (x as f32).powf(1.5))).collect::<Vec<(f32, f32)>>();
+        let fit = FitResult::new(FitModel::PowerLaw, &points).unwrap();
+        assert_relative_eq!(fit.coefficients[0], 3.0, epsilon = 1e-4);
+        assert_relative_eq!(fit.coefficients[1], 1.5, epsilon = 1e-4);
+
+        let points = (0..10).map(|x| (x as f32, 2.0 * (0.3 * x as f32).exp())).collect::<Vec<(f32, f32)>>();
+        let fit = FitResult::new(FitModel::Exponential, &points).unwrap();
+        assert_relative_eq!(fit.coefficients[0], 2.0, epsilon = 1e-4);
+        assert_relative_eq!(fit.coefficients[1], 0.3, epsilon = 1e-4);
+
+        // x = 0 is ignored
+        let points = (0..10).map(|x| (x as f32, 1.0 + 2.0 * (x as f32).ln())).collect::<Vec<(f32, f32)>>();
+        let fit = FitResult::new(FitModel::Logarithmic, &points).unwrap();
+        assert_relative_eq!(fit.coefficients[0], 1.0, epsilon = 1e-4);
+        assert_relative_eq!(fit.coefficients[1], 2.0, epsilon = 1e-4);
+        assert_relative_eq!(fit.r_squared, 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_r_squared_and_array() {
+        let points = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 1.0)];
+        let fit = FitResult::new(FitModel::Linear, &points).unwrap();
+        // y = 0.2 + 0.2x
+        assert_relative_eq!(fit.r_squared, 0.2, epsilon = 1e-9);
+
+        let fits = [("b".to_string(), fit.clone()), ("a".to_string(), fit)];
+        let array = FitResult::to_array(fits.iter().map(|(legend, fit)| (legend, fit)));
+        assert_eq!(array.get_data()[0][0], "a");
+        assert_eq!(array.get_data()[0][2], "y = 0.2 + 0.2x");
+    }
+}
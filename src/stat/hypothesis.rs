@@ -0,0 +1,663 @@
+use std::fmt::{Display, Formatter};
+
+use crate::plotter::utils::format_number;
+use crate::static_html::presentation_data::Array;
+
+use super::stats_serie::{MetricName, StatsSerie};
+
+
+/// number of resamples used by default for the bootstrap
+pub const BOOTSTRAP_NB_RESAMPLES : usize = 2000;
+/// seed used by default for the bootstrap (to get reproducible intervals)
+pub const BOOTSTRAP_SEED : u64 = 42;
+/// above this number of pairs (n1 * n2), the Kolmogorov–Smirnov p-value is approximated
+const KOLMOGOROV_SMIRNOV_EXACT_MAX_PAIRS : usize = 10_000_000;
+
+
+// NOTE : the NaN values of the samples are ignored by the tests, the effect sizes and the bootstrap intervals
+
+
+/// represent the result of a statistical test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic : f64,
+    pub p_value : f64,
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "statistic: {:.4} p-value: {:.4}", self.statistic, self.p_value)
+    }
+}
+
+impl TestResult {
+    /// if the null hypothesis is rejected with the given significance level
+    pub fn is_significant(&self, alpha : f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+
+/// Two-sided Mann–Whitney U test (same method as scipy.stats.mannwhitneyu with the default parameters).
+/// The statistic is the U of the first sample. The p-value is exact when a sample has at most 8 values
+/// and there is no tie, otherwise it use the normal approximation with tie and continuity corrections.
+/// return NaN values if one of the samples is empty
+pub fn mann_whitney_u(x : &[f32], y : &[f32]) -> TestResult {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    let n1 = x.len();
+    let n2 = y.len();
+    if n1 == 0 || n2 == 0 {
+        return TestResult { statistic : f64::NAN, p_value : f64::NAN };
+    }
+
+    let all = x.iter().chain(y.iter()).map(|v| *v as f64).collect::<Vec<f64>>();
+    let (ranks, tie_term) = rank_with_ties(&all);
+
+    let rank_sum_x = ranks[..n1].iter().sum::<f64>();
+    let u1 = rank_sum_x - (n1 * (n1 + 1)) as f64 / 2.0;
+    let u2 = (n1 * n2) as f64 - u1;
+    let u = u1.max(u2);
+
+    let p_value = if !(n1 > 8 && n2 > 8) && tie_term == 0.0 {
+        2.0 * mann_whitney_exact_sf(u.round() as usize, n1, n2)
+    } else {
+        let n = (n1 + n2) as f64;
+        let mu = (n1 * n2) as f64 / 2.0;
+        let s = ((n1 * n2) as f64 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)))).sqrt();
+        if s == 0.0 {
+            1.0
+        } else {
+            2.0 * normal_sf((u - mu - 0.5) / s)
+        }
+    };
+
+    TestResult {
+        statistic : u1,
+        p_value : p_value.min(1.0),
+    }
+}
+
+/// Welch's t-test, two-sided (same as scipy.stats.ttest_ind with equal_var=False).
+/// return NaN values if one of the samples has less than 2 values
+pub fn welch_t_test(x : &[f32], y : &[f32]) -> TestResult {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    let n1 = x.len() as f64;
+    let n2 = y.len() as f64;
+    if x.len() < 2 || y.len() < 2 {
+        return TestResult { statistic : f64::NAN, p_value : f64::NAN };
+    }
+
+    let (mean_x, var_x) = mean_and_variance(x);
+    let (mean_y, var_y) = mean_and_variance(y);
+    let a = var_x / n1;
+    let b = var_y / n2;
+
+    let t = (mean_x - mean_y) / (a + b).sqrt();
+    let df = (a + b).powi(2) / (a * a / (n1 - 1.0) + b * b / (n2 - 1.0));
+    let p_value = regularized_incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+
+    TestResult {
+        statistic : t,
+        p_value,
+    }
+}
+
+/// Two-sample Kolmogorov–Smirnov test, two-sided. The statistic is the maximal distance between the empirical CDF.
+/// The p-value is exact (same as scipy.stats.ks_2samp with method="exact"), except for very large samples
+/// where the asymptotic Kolmogorov distribution is used.
+/// return NaN values if one of the samples is empty
+pub fn kolmogorov_smirnov(x : &[f32], y : &[f32]) -> TestResult {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    let n1 = x.len();
+    let n2 = y.len();
+    if n1 == 0 || n2 == 0 {
+        return TestResult { statistic : f64::NAN, p_value : f64::NAN };
+    }
+
+    let sorted_x = sorted_f64(x);
+    let sorted_y = sorted_f64(y);
+    let mut d : f64 = 0.0;
+    for value in sorted_x.iter().chain(sorted_y.iter()) {
+        let cdf_x = sorted_x.partition_point(|v| v <= value) as f64 / n1 as f64;
+        let cdf_y = sorted_y.partition_point(|v| v <= value) as f64 / n2 as f64;
+        d = d.max((cdf_x - cdf_y).abs());
+    }
+
+    let p_value = if n1 * n2 <= KOLMOGOROV_SMIRNOV_EXACT_MAX_PAIRS {
+        kolmogorov_smirnov_exact_sf(d, n1, n2)
+    } else {
+        let en = ((n1 * n2) as f64 / (n1 + n2) as f64).sqrt();
+        kolmogorov_sf((en + 0.12 + 0.11 / en) * d)
+    };
+
+    TestResult {
+        statistic : d,
+        p_value : p_value.clamp(0.0, 1.0),
+    }
+}
+
+/// P(D >= d) for the exact distribution of the two-sample statistic, by counting the lattice paths staying inside the band
+fn kolmogorov_smirnov_exact_sf(d : f64, n1 : usize, n2 : usize) -> f64 {
+    let g = gcd(n1, n2);
+    let (n1g, n2g) = ((n1 / g) as i64, (n2 / g) as i64);
+    let lcm = (n1 / g * n2) as f64;
+    // work with integers to avoid the rounding errors on the band
+    let h = (d * lcm).round() as i64;
+    if h == 0 {
+        return 1.0;
+    }
+
+    // inside[j] : probability that a random path to (i, j) stay strictly inside the band (|i/n1 - j/n2| < d)
+    // the paths to (i, j) come from (i-1, j) with a proportion i/(i+j) and from (i, j-1) with a proportion j/(i+j)
+    let mut inside = vec![0.0; n2 + 1];
+    for i in 0..=n1 {
+        for j in 0..=n2 {
+            if (i as i64 * n2g - j as i64 * n1g).abs() >= h {
+                inside[j] = 0.0;
+            } else if i == 0 && j == 0 {
+                inside[j] = 1.0;
+            } else {
+                let from_x = if i > 0 { inside[j] * i as f64 } else { 0.0 };
+                let from_y = if j > 0 { inside[j - 1] * j as f64 } else { 0.0 };
+                inside[j] = (from_x + from_y) / (i + j) as f64;
+            }
+        }
+    }
+
+    1.0 - inside[n2]
+}
+
+/// survival function of the Kolmogorov distribution
+fn kolmogorov_sf(lambda : f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    for k in 1..=100 {
+        let k = k as f64;
+        let term = (-2.0 * k * k * lambda * lambda).exp();
+        sum += if k as usize % 2 == 1 { term } else { -term };
+        if term < 1e-16 {
+            break;
+        }
+    }
+    2.0 * sum
+}
+
+
+/// Cohen's d : the difference of the means divided by the pooled standard deviation
+/// return NaN if the samples have less than 3 values in total
+pub fn cohen_d(x : &[f32], y : &[f32]) -> f64 {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    let n1 = x.len() as f64;
+    let n2 = y.len() as f64;
+    if x.is_empty() || y.is_empty() || x.len() + y.len() < 3 {
+        return f64::NAN;
+    }
+    let (mean_x, var_x) = mean_and_variance(x);
+    let (mean_y, var_y) = mean_and_variance(y);
+    // the variance of a sample with one value don't contribute
+    let var_x = if x.len() < 2 { 0.0 } else { var_x };
+    let var_y = if y.len() < 2 { 0.0 } else { var_y };
+    let pooled_var = ((n1 - 1.0) * var_x + (n2 - 1.0) * var_y) / (n1 + n2 - 2.0);
+
+    (mean_x - mean_y) / pooled_var.sqrt()
+}
+
+/// Cliff's delta : P(x > y) - P(x < y) for a random pair, between -1 and 1
+/// return NaN if one of the samples is empty
+pub fn cliff_delta(x : &[f32], y : &[f32]) -> f64 {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    if x.is_empty() || y.is_empty() {
+        return f64::NAN;
+    }
+    let sorted_y = sorted_f64(y);
+    let mut dominance = 0i64;
+    for value in x.iter().map(|v| *v as f64) {
+        let lower = sorted_y.partition_point(|v| *v < value) as i64;
+        let greater = (sorted_y.len() - sorted_y.partition_point(|v| *v <= value)) as i64;
+        dominance += lower - greater;
+    }
+
+    dominance as f64 / (x.len() * y.len()) as f64
+}
+
+
+/// Percentile bootstrap confidence interval of a metric of the values
+/// return (NaN, NaN) if the values are empty
+/// - args: confidence: the confidence level (e.g. 0.95)
+/// - args: seed: the seed of the resampling, the same seed give the same interval
+pub fn bootstrap_confidence_interval(values : &[f32], metric : MetricName, confidence : f64, nb_resamples : usize, seed : u64) -> (f64, f64) {
+    let values = &without_nan(values)[..];
+    if values.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    let mut rng = SplitMix64::new(seed);
+    let estimates = (0..nb_resamples)
+        .map(|_| StatsSerie::new(&rng.resample(values)).get_stats(metric).value)
+        .collect::<Vec<f64>>();
+    percentile_interval(estimates, confidence)
+}
+
+/// Percentile bootstrap confidence interval of the difference of a metric between x and y (metric(x) - metric(y))
+/// return (NaN, NaN) if one of the samples is empty
+/// - args: confidence: the confidence level (e.g. 0.95)
+/// - args: seed: the seed of the resampling, the same seed give the same interval
+pub fn bootstrap_difference_confidence_interval(x : &[f32], y : &[f32], metric : MetricName, confidence : f64, nb_resamples : usize, seed : u64) -> (f64, f64) {
+    let (x, y) = (&without_nan(x)[..], &without_nan(y)[..]);
+    if x.is_empty() || y.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    let mut rng = SplitMix64::new(seed);
+    let estimates = (0..nb_resamples).map(|_| {
+        let metric_x = StatsSerie::new(&rng.resample(x)).get_stats(metric).value;
+        let metric_y = StatsSerie::new(&rng.resample(y)).get_stats(metric).value;
+        metric_x - metric_y
+    }).collect::<Vec<f64>>();
+    percentile_interval(estimates, confidence)
+}
+
+/// get the interval containing the given proportion of the estimates
+fn percentile_interval(mut estimates : Vec<f64>, confidence : f64) -> (f64, f64) {
+    estimates.retain(|v| !v.is_nan());
+    if estimates.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    estimates.sort_by(|a, b| a.total_cmp(b));
+    let alpha = (1.0 - confidence) / 2.0;
+    let last = (estimates.len() - 1) as f64;
+    let lower = estimates[(alpha * last).floor() as usize];
+    let upper = estimates[((1.0 - alpha) * last).ceil() as usize];
+    (lower, upper)
+}
+
+
+/// A small deterministic random generator, used to resample the values in the bootstrap
+struct SplitMix64 {
+    state : u64,
+}
+
+impl SplitMix64 {
+    fn new(seed : u64) -> Self {
+        SplitMix64 { state : seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// draw len values with replacement
+    fn resample(&mut self, values : &[f32]) -> Vec<f32> {
+        (0..values.len()).map(|_| values[(self.next_u64() % values.len() as u64) as usize]).collect()
+    }
+}
+
+
+// -----------------------------------------------------------------------------
+
+/// The comparison of two series with all the tests and effect sizes
+/// NOTE : To show it in a html table, use the methods get_headers and get_values (or to_array)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SerieComparison {
+    pub welch : TestResult,
+    pub mann_whitney : TestResult,
+    pub kolmogorov_smirnov : TestResult,
+    pub cohen_d : f64,
+    pub cliff_delta : f64,
+    /// 95% bootstrap confidence interval of the difference of the medians
+    pub median_difference_ci : (f64, f64),
+}
+
+impl SerieComparison {
+    pub fn new(x : &StatsSerie, y : &StatsSerie) -> Self {
+        SerieComparison {
+            welch : welch_t_test(&x.serie, &y.serie),
+            mann_whitney : mann_whitney_u(&x.serie, &y.serie),
+            kolmogorov_smirnov : kolmogorov_smirnov(&x.serie, &y.serie),
+            cohen_d : cohen_d(&x.serie, &y.serie),
+            cliff_delta : cliff_delta(&x.serie, &y.serie),
+            median_difference_ci : bootstrap_difference_confidence_interval(
+                &x.serie, &y.serie, MetricName::Median, 0.95, BOOTSTRAP_NB_RESAMPLES, BOOTSTRAP_SEED
+            ),
+        }
+    }
+
+    pub fn get_headers() -> &'static [&'static str ; 6] {
+        &[
+            "welch p-value",
+            "mann-whitney p-value",
+            "kolmogorov-smirnov p-value",
+            "cohen d",
+            "cliff delta",
+            "median difference (95% CI)",
+        ]
+    }
+
+    pub fn get_values(&self) -> [String ; 6] {
+        [
+            format_number(self.welch.p_value),
+            format_number(self.mann_whitney.p_value),
+            format_number(self.kolmogorov_smirnov.p_value),
+            format_number(self.cohen_d),
+            format_number(self.cliff_delta),
+            format!("[{}, {}]", format_number(self.median_difference_ci.0), format_number(self.median_difference_ci.1)),
+        ]
+    }
+
+    /// Create a table with one row per named comparison
+    pub fn to_array(comparisons : &[(String, SerieComparison)]) -> Array {
+        let mut header = vec!["comparison".to_string()];
+        header.extend(Self::get_headers().iter().map(|h| h.to_string()));
+
+        let data = comparisons.iter().map(|(name, comparison)| {
+            let mut row = vec![name.clone()];
+            row.extend(comparison.get_values());
+            row
+        }).collect();
+
+        Array::new(header, data)
+    }
+}
+
+
+// -----------------------------------------------------------------------------
+
+/// P(U >= u) for the exact distribution of U, without ties
+fn mann_whitney_exact_sf(u : usize, n1 : usize, n2 : usize) -> f64 {
+    // frequencies[i][u] : number of arrangements of i values of x and j values of y with a statistic u,
+    // computed column by column for j from 0 to n2
+    let max_u = n1 * n2;
+    let mut previous : Vec<Vec<f64>> = (0..=n1).map(|_| {
+        let mut frequencies = vec![0.0; max_u + 1];
+        frequencies[0] = 1.0;
+        frequencies
+    }).collect();
+
+    for j in 1..=n2 {
+        let mut current : Vec<Vec<f64>> = Vec::with_capacity(n1 + 1);
+        let mut first = vec![0.0; max_u + 1];
+        first[0] = 1.0;
+        current.push(first);
+        for i in 1..=n1 {
+            // the largest value is either from y (u unchanged) or from x (u increase by j)
+            let mut frequencies = previous[i].clone();
+            for v in j..=max_u {
+                frequencies[v] += current[i - 1][v - j];
+            }
+            current.push(frequencies);
+        }
+        previous = current;
+    }
+
+    let frequencies = &previous[n1];
+    let total = frequencies.iter().sum::<f64>();
+    frequencies[u.min(max_u)..].iter().sum::<f64>() / total
+}
+
+
+/// compute the ranks (starting at 1, ties get the average rank) of the values,
+/// and the tie term (sum of t^3 - t for each group of t tied values)
+pub(crate) fn rank_with_ties(values : &[f64]) -> (Vec<f64>, f64) {
+    let mut indexes = (0..values.len()).collect::<Vec<usize>>();
+    indexes.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < indexes.len() {
+        let mut end = start + 1;
+        while end < indexes.len() && values[indexes[end]] == values[indexes[start]] {
+            end += 1;
+        }
+        // positions start..end (0 based) share the ranks start+1..=end
+        let average_rank = (start + 1 + end) as f64 / 2.0;
+        for index in indexes[start..end].iter() {
+            ranks[*index] = average_rank;
+        }
+        let t = (end - start) as f64;
+        tie_term += t * t * t - t;
+        start = end;
+    }
+
+    (ranks, tie_term)
+}
+
+/// get the values without the NaN ones, ignored by the tests
+fn without_nan(values : &[f32]) -> Vec<f32> {
+    values.iter().copied().filter(|v| !v.is_nan()).collect()
+}
+
+/// mean and unbiased variance (ddof = 1) of the values
+fn mean_and_variance(values : &[f32]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let variance = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+fn sorted_f64(values : &[f32]) -> Vec<f64> {
+    let mut sorted = values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+fn gcd(a : usize, b : usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// logarithm of the gamma function (Lanczos approximation, g = 7)
+fn ln_gamma(x : f64) -> f64 {
+    const COEFFICIENTS : [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = COEFFICIENTS[0];
+    let t = x + 7.5;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        a += coefficient / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// regularized incomplete beta function I_x(a, b)
+fn regularized_incomplete_beta(a : f64, b : f64, x : f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the continued fraction converge quickly only on this side
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// continued fraction of the incomplete beta function (modified Lentz's method)
+fn beta_continued_fraction(a : f64, b : f64, x : f64) -> f64 {
+    const MAX_ITERATIONS : usize = 10_000;
+    const EPSILON : f64 = 1e-15;
+    const FP_MIN : f64 = 1e-300;
+
+    let clamp = |v : f64| if v.abs() < FP_MIN { FP_MIN } else { v };
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - qab * x / qap);
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        // even step
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        h *= d * c;
+        // odd step
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// survival function of the standard normal distribution
+pub(crate) fn normal_sf(z : f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// complementary error function (fractional error below 1.2e-7, Numerical Recipes)
+fn erfc(x : f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_rank_with_ties() {
+        let (ranks, tie_term) = rank_with_ties(&[3.0, 1.0, 3.0, 2.0]);
+        assert_eq!(ranks, vec![3.5, 1.0, 3.5, 2.0]);
+        assert_eq!(tie_term, 6.0);
+
+        // the NaN values are ranked last instead of panicking
+        assert_eq!(rank_with_ties(&[2.0, f64::NAN, 1.0]).0, vec![2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn test_normal_sf() {
+        assert_relative_eq!(normal_sf(0.0), 0.5, epsilon = 1e-7);
+        assert_relative_eq!(normal_sf(1.959963984540054), 0.025, epsilon = 1e-7);
+        assert_relative_eq!(normal_sf(-1.0), 0.8413447460685429, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn test_mann_whitney_u_exact() {
+        // expected (U, p-value) of [1, 2, 3, 4], [5, 6, 7, 8] with the method of scipy.stats.mannwhitneyu : (0.0, 0.02857142857142857)
+        let result = mann_whitney_u(&[1.0, 2.0, 3.0, 4.0], &[5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(result.statistic, 0.0);
+        assert_relative_eq!(result.p_value, 0.02857142857142857, epsilon = 1e-9);
+
+        // expected (U, p-value) of [1, 4, 6], [2, 3, 5, 7] with the method of scipy.stats.mannwhitneyu : (5.0, 0.8571428571428571)
+        let result = mann_whitney_u(&[1.0, 4.0, 6.0], &[2.0, 3.0, 5.0, 7.0]);
+        assert_eq!(result.statistic, 5.0);
+        assert_relative_eq!(result.p_value, 0.8571428571428571, epsilon = 1e-9);
+
+        // exact as long as one of the samples has at most 8 values : (14.0, 0.14465534465534466)
+        let result = mann_whitney_u(&[-44.0, 27.0, -27.0, -26.0, -58.0, -47.0], &[87.0, 30.0, 22.0, -66.0, 46.0, -67.0, -24.0, 98.0, 28.0]);
+        assert_eq!(result.statistic, 14.0);
+        assert_relative_eq!(result.p_value, 0.14465534465534466, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_mann_whitney_u_asymptotic() {
+        // expected (U, p-value) of [1, 2, 2, 3, 4], [3, 4, 5, 5, 6] with the method of scipy.stats.mannwhitneyu : (2.0, 0.03445363703947131)
+        let result = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0, 4.0], &[3.0, 4.0, 5.0, 5.0, 6.0]);
+        assert_eq!(result.statistic, 2.0);
+        assert_relative_eq!(result.p_value, 0.03445363703947131, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_regularized_incomplete_beta() {
+        assert_relative_eq!(regularized_incomplete_beta(1.0, 1.0, 0.3), 0.3, epsilon = 1e-12);
+        assert_relative_eq!(regularized_incomplete_beta(2.0, 3.0, 0.4), 0.5248, epsilon = 1e-12);
+        assert_relative_eq!(ln_gamma(5.0), 24.0_f64.ln(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_effect_sizes() {
+        assert_relative_eq!(cliff_delta(&[1.0, 2.0], &[2.0, 3.0]), -0.75, epsilon = 1e-12);
+        assert_relative_eq!(cliff_delta(&[5.0], &[1.0, 2.0]), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(cohen_d(&[1.0, 2.0, 3.0], &[2.0, 3.0, 4.0]), -1.0, epsilon = 1e-12);
+        assert!(cohen_d(&[], &[1.0]).is_nan());
+    }
+
+    #[test]
+    fn test_bootstrap_confidence_interval() {
+        let values = (0..50).map(|v| v as f32).collect::<Vec<f32>>();
+        let (lower, upper) = bootstrap_confidence_interval(&values, MetricName::Mean, 0.95, 500, 1);
+        assert!(lower < 24.5 && 24.5 < upper);
+        assert_eq!((lower, upper), bootstrap_confidence_interval(&values, MetricName::Mean, 0.95, 500, 1));
+
+        let constant = vec![3.0; 10];
+        assert_eq!(bootstrap_confidence_interval(&constant, MetricName::Median, 0.95, 100, 1), (3.0, 3.0));
+
+        let (lower, upper) = bootstrap_difference_confidence_interval(&values, &constant, MetricName::Median, 0.9, 500, 1);
+        assert!(lower < 21.5 && 21.5 < upper);
+        assert!(bootstrap_confidence_interval(&[], MetricName::Mean, 0.95, 100, 1).0.is_nan());
+    }
+
+    #[test]
+    fn test_serie_comparison_to_array() {
+        let x = StatsSerie::new(&vec![1.0, 2.0, 3.0, 4.0]);
+        let y = StatsSerie::new(&vec![5.0, 6.0, 7.0, 8.0]);
+        let comparison = SerieComparison::new(&x, &y);
+        assert_relative_eq!(comparison.mann_whitney.p_value, 0.02857142857142857, epsilon = 1e-9);
+
+        let array = SerieComparison::to_array(&[("x vs y".to_string(), comparison)]);
+        assert_eq!(array.get_header().len(), 7);
+        assert_eq!(array.get_data()[0][0], "x vs y");
+        assert_eq!(array.get_data()[0][5], "-1");
+    }
+
+    #[test]
+    fn test_mann_whitney_u_empty() {
+        let result = mann_whitney_u(&[], &[1.0]);
+        assert!(result.statistic.is_nan());
+        assert!(result.p_value.is_nan());
+    }
+
+    #[test]
+    fn test_nan_values_are_ignored() {
+        let x = [1.0, 2.0, f32::NAN, 3.0, 4.0];
+        let y = [2.5, f32::NAN, 3.5, 4.5, 5.5];
+        let (x_clean, y_clean) = ([1.0, 2.0, 3.0, 4.0], [2.5, 3.5, 4.5, 5.5]);
+        assert_eq!(mann_whitney_u(&x, &y), mann_whitney_u(&x_clean, &y_clean));
+        assert_eq!(welch_t_test(&x, &y), welch_t_test(&x_clean, &y_clean));
+        assert_eq!(kolmogorov_smirnov(&x, &y), kolmogorov_smirnov(&x_clean, &y_clean));
+        assert_eq!(cliff_delta(&x, &y), cliff_delta(&x_clean, &y_clean));
+        assert_eq!(cohen_d(&x, &y), cohen_d(&x_clean, &y_clean));
+        assert_eq!(
+            bootstrap_confidence_interval(&x, MetricName::Mean, 0.95, 100, 1),
+            bootstrap_confidence_interval(&x_clean, MetricName::Mean, 0.95, 100, 1)
+        );
+        assert!(bootstrap_difference_confidence_interval(&[f32::NAN], &y, MetricName::Mean, 0.95, 100, 1).0.is_nan());
+
+        // the intervals don't panic on NaN
+        assert_eq!(percentile_interval(vec![f64::NAN, 1.0, 2.0], 0.5), (1.0, 2.0));
+    }
+}
@@ -38,6 +38,11 @@ impl Linspace {
     pub fn get_range(&self) -> Range<f64> {
         self.start..self.end
     }
+
+    /// iterate over the values of the linspace array
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.len).map(move |i| self.start + i as f64 * self.step)
+    }
 }
 
 
@@ -55,6 +60,12 @@ mod tests {
         assert_eq!(linspace.len, 11);
     }
 
+    #[test]
+    fn test_linspace_iter() {
+        let linspace = Linspace::new(1.0, 3.0, 5);
+        assert_eq!(linspace.iter().collect::<Vec<f64>>(), vec![1.0, 1.5, 2.0, 2.5, 3.0]);
+    }
+
     #[test]
     fn test_linspace_index_of() {
         let linspace = Linspace::new(0.0, 10.0, 11);
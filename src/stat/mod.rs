@@ -2,6 +2,11 @@
 pub mod stats_serie;
 pub mod compression;
 pub mod linspace;
+pub mod hypothesis;
+pub mod outlier;
+pub mod fitting;
+pub mod smoothing;
+pub mod correlation;
 
 /// get the outliers of the given data (Mask)
 /// return the vector of bool, true if the corresponding data is an outlier
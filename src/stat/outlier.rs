@@ -0,0 +1,242 @@
+use super::calculate_q1_q3;
+use super::stats_serie::{MetricName, StatsSerie};
+
+
+/// the constant of the modified z-score (0.6745 is the 0.75 quantile of the standard normal distribution)
+const MODIFIED_Z_SCORE_CONSTANT : f64 = 0.6745;
+/// the constant of the modified z-score with the mean absolute deviation, used when the MAD is 0 (sqrt(pi / 2))
+const MEAN_AD_CONSTANT : f64 = 1.253314;
+
+/// a (x, y) point
+type Point = (f32, f32);
+
+/// define how the outliers of a serie are detected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierStrategy {
+    /// no outlier
+    None,
+    /// Tukey fence : outside [q1 - k * iqr, q3 + k * iqr] (k = 1.5 usually)
+    Iqr(f32),
+    /// outside mean +- threshold * std (threshold = 3 usually)
+    ZScore(f32),
+    /// modified z-score of Iglewicz and Hoaglin : 0.6745 * |x - median| / MAD > threshold (threshold = 3.5 usually)
+    /// NOTE : if the MAD is 0 (more than half of the values are equal), |x - median| / (1.253314 * MeanAD) > threshold is used instead
+    ModifiedZScore(f32),
+    /// outside the given percentiles (between 0 and 100), e.g. Percentile(1.0, 99.0)
+    Percentile(f32, f32),
+}
+
+impl Default for OutlierStrategy {
+    fn default() -> Self {
+        OutlierStrategy::Iqr(1.5)
+    }
+}
+
+impl OutlierStrategy {
+    /// get the bounds of the values that are not outliers (inclusive)
+    /// NOTE : the NaN values are ignored
+    /// return None if there is no bound (no strategy or no value)
+    pub fn get_bounds(&self, values : &[f32]) -> Option<(f32, f32)> {
+        let values = &values.iter().copied().filter(|v| !v.is_nan()).collect::<Vec<f32>>()[..];
+        if values.is_empty() {
+            return None;
+        }
+
+        match self {
+            OutlierStrategy::None => None,
+            OutlierStrategy::Iqr(k) => {
+                let (q1, q3) = calculate_q1_q3(&mut values.iter().map(|v| ((), *v)).collect::<Vec<((), f32)>>());
+                let iqr = q3 - q1;
+                Some((q1 - k * iqr, q3 + k * iqr))
+            },
+            OutlierStrategy::ZScore(threshold) => {
+                let n = values.len() as f64;
+                let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+                let std = (values.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / n).sqrt();
+                let delta = *threshold as f64 * std;
+                Some(((mean - delta) as f32, (mean + delta) as f32))
+            },
+            OutlierStrategy::ModifiedZScore(threshold) => {
+                let median = StatsSerie::new(&values.to_vec()).get_stats(MetricName::Median).value;
+                let deviations = values.iter().map(|v| (*v as f64 - median).abs() as f32).collect::<Vec<f32>>();
+                let mad = StatsSerie::new(&deviations).get_stats(MetricName::Median).value;
+                let delta = if mad > 0.0 {
+                    *threshold as f64 * mad / MODIFIED_Z_SCORE_CONSTANT
+                } else {
+                    // the mean absolute deviation is 0 only if all the values are equal : no outlier
+                    let mean_ad = deviations.iter().map(|d| *d as f64).sum::<f64>() / deviations.len() as f64;
+                    *threshold as f64 * MEAN_AD_CONSTANT * mean_ad
+                };
+                Some(((median - delta) as f32, (median + delta) as f32))
+            },
+            OutlierStrategy::Percentile(lower, upper) => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                Some((get_percentile(&sorted, *lower), get_percentile(&sorted, *upper)))
+            },
+        }
+    }
+
+    /// get the outliers of the given values (Mask)
+    /// return the vector of bool, true if the corresponding value is an outlier
+    pub fn get_outliers_mask(&self, values : &[f32]) -> Vec<bool> {
+        match self.get_bounds(values) {
+            Some((lower, upper)) => values.iter().map(|v| *v < lower || *v > upper).collect(),
+            None => vec![false; values.len()],
+        }
+    }
+}
+
+/// get the percentile of sorted values, with a linear interpolation
+fn get_percentile(sorted : &[f32], percentile : f32) -> f32 {
+    let position = (percentile.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32;
+    let lower_index = position.floor() as usize;
+    let upper_index = position.ceil() as usize;
+    let fraction = position - lower_index as f32;
+    sorted[lower_index] + (sorted[upper_index] - sorted[lower_index]) * fraction
+}
+
+
+/// define how the outliers of a subplot are handled, for each axis
+/// NOTE : a point is an outlier if it's an outlier on x or on y
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OutlierConfig {
+    pub x : OutlierStrategy,
+    pub y : OutlierStrategy,
+    /// if true, the outliers are plotted in a muted style instead of being removed
+    pub show_removed : bool,
+}
+
+impl OutlierConfig {
+    /// no outlier on both axes
+    pub fn none() -> Self {
+        OutlierConfig {
+            x : OutlierStrategy::None,
+            y : OutlierStrategy::None,
+            show_removed : false,
+        }
+    }
+
+    /// the given strategy only on the y axis
+    pub fn new_y(strategy : OutlierStrategy) -> Self {
+        OutlierConfig {
+            x : OutlierStrategy::None,
+            y : strategy,
+            show_removed : false,
+        }
+    }
+
+    pub fn new(x : OutlierStrategy, y : OutlierStrategy, show_removed : bool) -> Self {
+        OutlierConfig {
+            x,
+            y,
+            show_removed,
+        }
+    }
+
+    pub fn with_show_removed(mut self, show_removed : bool) -> Self {
+        self.show_removed = show_removed;
+        self
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.x == OutlierStrategy::None && self.y == OutlierStrategy::None
+    }
+
+    /// split the points in (kept points, outliers)
+    pub fn split_outliers(&self, points : Vec<Point>) -> (Vec<Point>, Vec<Point>) {
+        if self.is_none() {
+            return (points, Vec::new());
+        }
+        let x_mask = self.x.get_outliers_mask(&points.iter().map(|p| p.0).collect::<Vec<f32>>());
+        let y_mask = self.y.get_outliers_mask(&points.iter().map(|p| p.1).collect::<Vec<f32>>());
+
+        let mut kept = Vec::new();
+        let mut outliers = Vec::new();
+        for ((point, x_outlier), y_outlier) in points.into_iter().zip(x_mask).zip(y_mask) {
+            if x_outlier || y_outlier {
+                outliers.push(point);
+            } else {
+                kept.push(point);
+            }
+        }
+        (kept, outliers)
+    }
+}
+
+/// true to remove the outliers on y with a 1.5 IQR, like the previous versions
+impl From<bool> for OutlierConfig {
+    fn from(remove_outlier : bool) -> Self {
+        if remove_outlier {
+            OutlierConfig::new_y(OutlierStrategy::default())
+        } else {
+            OutlierConfig::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outlier_strategies() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 100.0];
+
+        assert_eq!(OutlierStrategy::None.get_outliers_mask(&values), vec![false; 9]);
+        assert_eq!(OutlierStrategy::Iqr(1.5).get_bounds(&values), Some((-3.0, 13.0)));
+        assert!(OutlierStrategy::Iqr(1.5).get_outliers_mask(&values)[8]);
+        assert!(!OutlierStrategy::Iqr(100.0).get_outliers_mask(&values)[8]);
+
+        assert_eq!(OutlierStrategy::ZScore(2.0).get_outliers_mask(&values).iter().filter(|o| **o).count(), 1);
+        assert_eq!(OutlierStrategy::ZScore(3.0).get_outliers_mask(&values).iter().filter(|o| **o).count(), 0);
+
+        // median = 5, MAD = 2
+        let (lower, upper) = OutlierStrategy::ModifiedZScore(3.5).get_bounds(&values).unwrap();
+        assert!((lower - (5.0 - 3.5 * 2.0 / 0.6745)).abs() < 1e-4);
+        assert!((upper - (5.0 + 3.5 * 2.0 / 0.6745)).abs() < 1e-4);
+
+        assert_eq!(OutlierStrategy::Percentile(0.0, 50.0).get_bounds(&values), Some((1.0, 5.0)));
+        assert_eq!(OutlierStrategy::Percentile(10.0, 90.0).get_bounds(&[0.0, 10.0]), Some((1.0, 9.0)));
+        assert_eq!(OutlierStrategy::ZScore(1.0).get_bounds(&[]), None);
+    }
+
+    #[test]
+    fn test_modified_z_score_without_mad() {
+        // more than half of the values are equal : MAD = 0, median = 1, MeanAD = 11 / 7
+        let values = vec![1.0, 1.0, 1.0, 1.0, 2.0, 0.0, 10.0];
+        let (lower, upper) = OutlierStrategy::ModifiedZScore(3.5).get_bounds(&values).unwrap();
+        let delta = 3.5 * 1.253314 * 11.0 / 7.0;
+        assert!((lower - (1.0 - delta)).abs() < 1e-4);
+        assert!((upper - (1.0 + delta)).abs() < 1e-4);
+        assert_eq!(OutlierStrategy::ModifiedZScore(3.5).get_outliers_mask(&values), vec![false, false, false, false, false, false, true]);
+
+        // all the values are equal
+        assert_eq!(OutlierStrategy::ModifiedZScore(3.5).get_outliers_mask(&[2.0; 5]), vec![false; 5]);
+    }
+
+    #[test]
+    fn test_nan_values_are_ignored() {
+        let values = vec![1.0, 2.0, f32::NAN, 3.0, 4.0, 5.0];
+        assert_eq!(OutlierStrategy::Percentile(0.0, 100.0).get_bounds(&values), Some((1.0, 5.0)));
+        assert_eq!(OutlierStrategy::Iqr(1.5).get_bounds(&values), OutlierStrategy::Iqr(1.5).get_bounds(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+        assert!(!OutlierStrategy::ModifiedZScore(3.5).get_outliers_mask(&values)[2]);
+        assert_eq!(OutlierStrategy::ZScore(1.0).get_bounds(&[f32::NAN]), None);
+    }
+
+    #[test]
+    fn test_outlier_config() {
+        let points = vec![(1.0, 1.0), (2.0, 2.0), (3.0, 3.0), (4.0, 100.0), (100.0, 4.0), (5.0, 2.0), (6.0, 3.0), (7.0, 1.0)];
+
+        let (kept, outliers) = OutlierConfig::from(true).split_outliers(points.clone());
+        assert_eq!(outliers, vec![(4.0, 100.0)]);
+        assert_eq!(kept.len(), 7);
+
+        let config = OutlierConfig::new(OutlierStrategy::Iqr(1.5), OutlierStrategy::Iqr(1.5), true);
+        let (kept, outliers) = config.split_outliers(points.clone());
+        assert_eq!(outliers, vec![(4.0, 100.0), (100.0, 4.0)]);
+        assert_eq!(kept.len(), 6);
+
+        assert_eq!(OutlierConfig::from(false).split_outliers(points.clone()), (points, Vec::new()));
+    }
+}
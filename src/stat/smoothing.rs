@@ -0,0 +1,199 @@
+use super::stats_serie::{MetricName, StatsSerie};
+
+
+/// a (x, y) point
+type Point = (f32, f32);
+
+/// the smoothing transforms of a serie, applied on the points sorted by x
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// mean of the centered window of the given number of points
+    MovingAverage(usize),
+    /// median of the centered window of the given number of points
+    RollingMedian(usize),
+    /// s_i = alpha * y_i + (1 - alpha) * s_(i-1), with alpha between 0 and 1 (a lower alpha smooth more)
+    Exponential(f32),
+    /// local linear regression on the given fraction (between 0 and 1) of the nearest points, with tricube weights
+    Loess(f32),
+}
+
+impl Smoothing {
+    /// smooth the serie, the x values are kept
+    /// NOTE : the returned serie is sorted by x, without the points with a non finite value (e.g. NaN read from a NULL column)
+    pub fn apply(&self, serie : &[Point]) -> Vec<Point> {
+        let mut sorted = serie.iter().filter(|(x, y)| x.is_finite() && y.is_finite()).cloned().collect::<Vec<Point>>();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if sorted.is_empty() {
+            return sorted;
+        }
+
+        match self {
+            Smoothing::MovingAverage(window) => Self::apply_window(&sorted, *window, MetricName::Mean),
+            Smoothing::RollingMedian(window) => Self::apply_window(&sorted, *window, MetricName::Median),
+            Smoothing::Exponential(alpha) => {
+                let alpha = alpha.clamp(0.0, 1.0);
+                let mut smoothed = Vec::with_capacity(sorted.len());
+                let mut current = sorted[0].1;
+                for (x, y) in sorted.into_iter() {
+                    current = alpha * y + (1.0 - alpha) * current;
+                    smoothed.push((x, current));
+                }
+                smoothed
+            },
+            Smoothing::Loess(fraction) => Self::apply_loess(&sorted, *fraction),
+        }
+    }
+
+    /// apply the metric on the centered window around each point (truncated on the edges)
+    fn apply_window(sorted : &[Point], window : usize, metric : MetricName) -> Vec<Point> {
+        let half_before = window.max(1).saturating_sub(1) / 2;
+        let half_after = window.max(1) / 2;
+        (0..sorted.len()).map(|i| {
+            let start = i.saturating_sub(half_before);
+            let end = (i + half_after + 1).min(sorted.len());
+            let values = sorted[start..end].iter().map(|p| p.1).collect::<Vec<f32>>();
+            (sorted[i].0, StatsSerie::new(&values).get_stats(metric).value as f32)
+        }).collect()
+    }
+
+    /// local linear regression around each point, on the window of its nearest neighbours
+    /// NOTE : on the sorted points, the nearest neighbours are a contiguous window sliding with the point, so the smoothing is O(n * k) with k neighbours
+    fn apply_loess(sorted : &[Point], fraction : f32) -> Vec<Point> {
+        let nb_neighbours = ((fraction.clamp(0.0, 1.0) * sorted.len() as f32).ceil() as usize).clamp(2.min(sorted.len()), sorted.len());
+
+        let mut start = 0;
+        sorted.iter().map(|(x0, y0)| {
+            let x0 = *x0 as f64;
+            // slide the window while the next point after it is nearer than its first point
+            while start + nb_neighbours < sorted.len()
+                && sorted[start + nb_neighbours].0 as f64 - x0 < x0 - sorted[start].0 as f64
+            {
+                start += 1;
+            }
+            let window = &sorted[start..start + nb_neighbours];
+            let max_distance = (x0 - window[0].0 as f64).max(window[nb_neighbours - 1].0 as f64 - x0);
+            if max_distance == 0.0 {
+                return (x0 as f32, *y0);
+            }
+
+            // weighted least squares of y = a + b * (x - x0)
+            // the points outside the window are at least at max_distance, so their weight is 0
+            let (mut sw, mut swx, mut swy, mut swxx, mut swxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for (x, y) in window.iter() {
+                let dx = *x as f64 - x0;
+                let u = dx.abs() / max_distance;
+                if u >= 1.0 {
+                    continue;
+                }
+                let w = (1.0 - u.powi(3)).powi(3);
+                sw += w;
+                swx += w * dx;
+                swy += w * *y as f64;
+                swxx += w * dx * dx;
+                swxy += w * dx * *y as f64;
+            }
+            let denominator = sw * swxx - swx * swx;
+            let value = if denominator.abs() < 1e-12 {
+                swy / sw
+            } else {
+                (swy * swxx - swx * swxy) / denominator
+            };
+            (x0 as f32, value as f32)
+        }).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_smoothing() {
+        let serie = vec![(2.0, 10.0), (0.0, 0.0), (1.0, 2.0), (3.0, 4.0), (4.0, 6.0)];
+        assert_eq!(
+            Smoothing::MovingAverage(3).apply(&serie),
+            vec![(0.0, 1.0), (1.0, 4.0), (2.0, 16.0 / 3.0), (3.0, 20.0 / 3.0), (4.0, 5.0)]
+        );
+        assert_eq!(
+            Smoothing::RollingMedian(3).apply(&serie),
+            vec![(0.0, 1.0), (1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (4.0, 5.0)]
+        );
+        // a window of one point doesn't change the serie
+        assert_eq!(Smoothing::MovingAverage(1).apply(&serie)[2], (2.0, 10.0));
+        assert!(Smoothing::MovingAverage(3).apply(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_non_finite_values_are_ignored() {
+        let serie = vec![(2.0, 10.0), (f32::NAN, 5.0), (0.0, 0.0), (1.0, f32::NAN), (f32::INFINITY, 1.0)];
+        for smoothing in [Smoothing::MovingAverage(3), Smoothing::RollingMedian(3), Smoothing::Exponential(0.5), Smoothing::Loess(0.5)] {
+            let smoothed = smoothing.apply(&serie);
+            assert_eq!(smoothed.iter().map(|(x, _)| *x).collect::<Vec<f32>>(), vec![0.0, 2.0]);
+            assert!(smoothed.iter().all(|(_, y)| y.is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_exponential_smoothing() {
+        let serie = vec![(0.0, 0.0), (1.0, 10.0), (2.0, 10.0)];
+        assert_eq!(Smoothing::Exponential(0.5).apply(&serie), vec![(0.0, 0.0), (1.0, 5.0), (2.0, 7.5)]);
+        assert_eq!(Smoothing::Exponential(1.0).apply(&serie), serie);
+    }
+
+    #[test]
+    fn test_loess() {
+        // a line is kept by the local linear regression
+        let line = (0..20).map(|x| (x as f32, 3.0 * x as f32 + 1.0)).collect::<Vec<Point>>();
+        for ((_, smoothed), (_, expected)) in Smoothing::Loess(0.3).apply(&line).iter().zip(line.iter()) {
+            assert!((smoothed - expected).abs() < 1e-3);
+        }
+
+        // the noise is reduced
+        let noisy = (0..40).map(|x| (x as f32, if x % 2 == 0 { 1.0 } else { -1.0 })).collect::<Vec<Point>>();
+        let smoothed = Smoothing::Loess(0.5).apply(&noisy);
+        assert!(smoothed[5..35].iter().all(|(_, y)| y.abs() < 0.2));
+    }
+
+    #[test]
+    fn test_loess_window_match_nearest_neighbours() {
+        // the smoothing with the sorted distances to all the points, to check the sliding window
+        fn loess_reference(sorted : &[Point], nb_neighbours : usize) -> Vec<f32> {
+            sorted.iter().map(|(x0, y0)| {
+                let x0 = *x0 as f64;
+                let mut distances = sorted.iter().map(|(x, _)| (*x as f64 - x0).abs()).collect::<Vec<f64>>();
+                distances.sort_by(|a, b| a.total_cmp(b));
+                let max_distance = distances[nb_neighbours - 1];
+                if max_distance == 0.0 {
+                    return *y0;
+                }
+                let (mut sw, mut swx, mut swy, mut swxx, mut swxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+                for (x, y) in sorted.iter() {
+                    let dx = *x as f64 - x0;
+                    let u = dx.abs() / max_distance;
+                    if u < 1.0 {
+                        let w = (1.0 - u.powi(3)).powi(3);
+                        sw += w;
+                        swx += w * dx;
+                        swy += w * *y as f64;
+                        swxx += w * dx * dx;
+                        swxy += w * dx * *y as f64;
+                    }
+                }
+                let denominator = sw * swxx - swx * swx;
+                (if denominator.abs() < 1e-12 { swy / sw } else { (swy * swxx - swx * swxy) / denominator }) as f32
+            }).collect()
+        }
+
+        // irregular x values, with duplicates
+        let serie = (0..60)
+            .map(|i| (((i * i) % 17) as f32 + (i / 20) as f32 * 0.5, ((i * 7) % 11) as f32))
+            .collect::<Vec<Point>>();
+        let smoothed = Smoothing::Loess(0.2).apply(&serie);
+        let mut sorted = serie.clone();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for ((_, value), expected) in smoothed.iter().zip(loess_reference(&sorted, 12)) {
+            assert!((value - expected).abs() < 1e-4, "{} != {}", value, expected);
+        }
+    }
+}
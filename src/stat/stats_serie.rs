@@ -8,6 +8,8 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 #[derive(Debug, Clone)]
 pub struct StatsSerie {
     pub serie : Vec<f32>,
+    /// the values of the serie sorted in increasing order
+    pub sorted_serie : Vec<f32>,
     pub stats : HashMap<MetricName, MetricValue>,
 }
 
@@ -23,6 +25,7 @@ impl StatsSerie {
 
             return Self {
                 serie : serie.clone(),
+                sorted_serie : Vec::new(),
                 stats,
             }
         }
@@ -56,6 +59,7 @@ impl StatsSerie {
 
         Self {
             serie : serie.clone(),
+            sorted_serie,
             stats,
         }
     }
@@ -63,6 +67,33 @@ impl StatsSerie {
     pub fn get_stats(&self, metric : MetricName) -> MetricValue {
         self.stats.get(&metric).unwrap().clone()
     }
+
+    /// get the quantile q (between 0 and 1) of the serie, with a linear interpolation between the sorted values
+    /// return NaN if the serie is empty
+    pub fn get_quantile(&self, q : f64) -> f64 {
+        if self.sorted_serie.is_empty() {
+            return f64::NAN;
+        }
+        let position = q.clamp(0.0, 1.0) * (self.sorted_serie.len() - 1) as f64;
+        let lower = self.sorted_serie[position.floor() as usize] as f64;
+        let upper = self.sorted_serie[position.ceil() as usize] as f64;
+        lower + (upper - lower) * position.fract()
+    }
+
+    /// get the empirical cumulative distribution function of the serie
+    /// return the points (x, fraction of the values <= x) for each distinct value x, sorted by x
+    pub fn get_ecdf(&self) -> Vec<(f32, f32)> {
+        let nb_values = self.sorted_serie.len() as f32;
+        let mut ecdf : Vec<(f32, f32)> = Vec::new();
+        for (i, value) in self.sorted_serie.iter().enumerate() {
+            let fraction = (i + 1) as f32 / nb_values;
+            match ecdf.last_mut() {
+                Some(last) if last.0 == *value => last.1 = fraction,
+                _ => ecdf.push((*value, fraction)),
+            }
+        }
+        ecdf
+    }
 }
 
 impl Display for StatsSerie {
@@ -146,4 +177,28 @@ impl MetricName {
             MetricName::NbValues => "nb_values".to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantile() {
+        let serie = StatsSerie::new(&vec![4.0, 1.0, 3.0, 2.0, 5.0]);
+        assert_eq!(serie.sorted_serie, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(serie.get_quantile(0.0), 1.0);
+        assert_eq!(serie.get_quantile(0.5), 3.0);
+        assert_eq!(serie.get_quantile(0.375), 2.5);
+        assert_eq!(serie.get_quantile(1.0), 5.0);
+        assert!(StatsSerie::new(&vec![]).get_quantile(0.5).is_nan());
+    }
+
+    #[test]
+    fn test_ecdf() {
+        let serie = StatsSerie::new(&vec![3.0, 1.0, 3.0, 2.0]);
+        assert_eq!(serie.get_ecdf(), vec![(1.0, 0.25), (2.0, 0.5), (3.0, 1.0)]);
+        assert!(StatsSerie::new(&vec![]).get_ecdf().is_empty());
+    }
+}
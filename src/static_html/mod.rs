@@ -1,2 +1,3 @@
 pub mod presentation_data;
+pub mod report;
 pub mod to_html;
\ No newline at end of file
@@ -1,14 +1,18 @@
 
 
+use std::cmp::Ordering;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 
 use serde::Serialize as SerdeSerialize;
 use serde_derive::{Deserialize, Serialize};
 
-use crate::params::HTML_TEMPLATE;
+use crate::params::{CHART_SCRIPT, HTML_TEMPLATE};
+use crate::plotter::legend_style::{natural_cmp, LegendStyles};
+use crate::plotter::plot_data::PlotData;
 
-use super::to_html::{ToHtmlDepth, ToTableOfContent};
+use super::to_html::{HtmlOptions, ToHtmlDepth, ToTableOfContent};
 
 // ------------------------------------- Ir -------------------------------------
 
@@ -37,11 +41,19 @@ impl Default for Ir {
 impl Ir {
     /// convert the intermediate representation to html
     pub fn to_html<P : AsRef<Path>>(&self, output_path : P) -> Result<String, Box<dyn std::error::Error>> {
+        self.to_html_with_options(output_path, &HtmlOptions::default())
+    }
+
+    /// convert the intermediate representation to html with the given options
+    /// NOTE : with HtmlOptions::self_contained, the images and the files of the links are embedded : the html is a single portable file
+    /// return an error if a file to embed can't be read
+    pub fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         let mut html_template = HTML_TEMPLATE.to_string();
-        let html_content = self.elements.to_html(output_path, 1);
+        let html_content = self.elements.to_html_with_options(output_path, 1, options)?;
         let table_of_content = self.elements.get_table_of_content(1);
         html_template = html_template.replace("<!--table of contents-->", &table_of_content);
         html_template = html_template.replace("<!--contents-->", &html_content);
+        html_template = html_template.replace("<!--scripts-->", &format!("<script>{}</script>", CHART_SCRIPT));
 
         Ok(html_template)
     }
@@ -204,7 +216,8 @@ pub enum Content {
     Text(Text),
     Image(String),
     Array(Array),
-    Collapsable(Collapsable<Content>)
+    Collapsable(Collapsable<Content>),
+    Chart(Chart),
 }
 
 impl Content {
@@ -257,6 +270,12 @@ impl From<Collapsable<Content>> for Content {
     }
 }
 
+impl From<Chart> for Content {
+    fn from(chart : Chart) -> Content {
+        Content::Chart(chart)
+    }
+}
+
 // ------------------------------------- Content -------------------------------------
 
 // ************************ Collapsable
@@ -313,7 +332,7 @@ impl Collapsable<Content>{
 // ************************ Text
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Hash, Eq, PartialOrd, Ord)]
-pub struct Text(Vec<TextContent>);
+pub struct Text(pub(crate) Vec<TextContent>);
 
 impl From<Vec<TextContent>> for Text {
     fn from(content : Vec<TextContent>) -> Text {
@@ -475,3 +494,140 @@ impl Array {
     }
 }
 
+// ************************ Chart
+
+/// how the points of the series of a chart are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Hash, Eq, PartialOrd, Ord)]
+pub enum ChartKind {
+    /// the points of each serie are joined (sorted by x)
+    Line,
+    Scatter,
+}
+
+/// the points of a legend in a chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSerie {
+    pub(crate) legend : String,
+    /// the css color of the serie
+    pub(crate) color : String,
+    pub(crate) points : Vec<(f32, f32)>,
+}
+
+impl ChartSerie {
+    pub fn get_legend(&self) -> &String {
+        &self.legend
+    }
+
+    pub fn get_color(&self) -> &String {
+        &self.color
+    }
+
+    pub fn get_points(&self) -> &Vec<(f32, f32)> {
+        &self.points
+    }
+}
+
+/// represent an interactive chart, serialized as json in the html and drawn by the chart script (tooltips, zoom and legend toggling)
+/// NOTE : the values are compared by their bits (like f32::total_cmp) to be used as a content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chart {
+    pub(crate) kind : ChartKind,
+    pub(crate) title : String,
+    pub(crate) x_label : String,
+    pub(crate) y_label : String,
+    pub(crate) x_range : (f32, f32),
+    pub(crate) y_range : (f32, f32),
+    /// the series sorted by legend, with the colors of the plots
+    pub(crate) series : Vec<ChartSerie>,
+}
+
+impl Chart {
+    /// create a chart from the data of a plot, the legends have the same colors than in the plots with the default styles
+    /// NOTE : the outliers removed from the data aren't drawn
+    pub fn new(kind : ChartKind, title : &str, x_label : &str, y_label : &str, plot_data : &PlotData) -> Chart {
+        Self::new_with_styles(kind, title, x_label, y_label, plot_data, &LegendStyles::default())
+    }
+
+    /// same as new, with the styles of the legends given to the plots
+    pub fn new_with_styles(kind : ChartKind, title : &str, x_label : &str, y_label : &str, plot_data : &PlotData, styles : &LegendStyles) -> Chart {
+        let mut styles = styles.clone();
+        let mut legends = plot_data.get_data().keys().collect::<Vec<&String>>();
+        styles.assign_sorted(legends.iter().copied());
+        legends.sort_by(|a, b| natural_cmp(a, b));
+
+        let series = legends.into_iter().map(|legend| {
+            let color = styles.get_style(legend).color;
+            let mut points = plot_data.get_data()[legend].clone();
+            if kind == ChartKind::Line {
+                points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            }
+            ChartSerie {
+                legend : legend.clone(),
+                color : format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2),
+                points,
+            }
+        }).collect();
+
+        let (x_range, y_range) = plot_data.get_range();
+        Chart {
+            kind,
+            title : title.to_string(),
+            x_label : x_label.to_string(),
+            y_label : y_label.to_string(),
+            x_range : (x_range.start, x_range.end),
+            y_range : (y_range.start, y_range.end),
+            series,
+        }
+    }
+
+    pub fn get_kind(&self) -> ChartKind {
+        self.kind
+    }
+
+    pub fn get_title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn get_series(&self) -> &Vec<ChartSerie> {
+        &self.series
+    }
+
+    /// the fields of the chart with the values as bits, to compare and hash the charts
+    #[allow(clippy::type_complexity)]
+    fn get_key(&self) -> (ChartKind, [&String; 3], [u32; 4], Vec<(&String, &String, Vec<(u32, u32)>)>) {
+        (
+            self.kind,
+            [&self.title, &self.x_label, &self.y_label],
+            [self.x_range.0.to_bits(), self.x_range.1.to_bits(), self.y_range.0.to_bits(), self.y_range.1.to_bits()],
+            self.series.iter()
+                .map(|serie| (&serie.legend, &serie.color, serie.points.iter().map(|(x, y)| (x.to_bits(), y.to_bits())).collect()))
+                .collect(),
+        )
+    }
+}
+
+impl PartialEq for Chart {
+    fn eq(&self, other : &Self) -> bool {
+        self.get_key() == other.get_key()
+    }
+}
+
+impl Eq for Chart {}
+
+impl Hash for Chart {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        self.get_key().hash(state);
+    }
+}
+
+impl PartialOrd for Chart {
+    fn partial_cmp(&self, other : &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Chart {
+    fn cmp(&self, other : &Self) -> Ordering {
+        self.get_key().cmp(&other.get_key())
+    }
+}
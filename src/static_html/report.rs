@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize as SerdeSerialize;
+
+use super::presentation_data::{Collapsable, Content, ContentElement, Element, Ir, ListElement, Text, TextContent};
+use super::to_html::HtmlOptions;
+
+
+/// the name of the html file of a report
+pub const REPORT_INDEX : &str = "index.html";
+/// the directory of the images and the files of a report, next to the html file
+pub const REPORT_ASSETS_DIR : &str = "assets";
+
+
+/// a reference to a file used by the report, that can be rewritten
+enum AssetRef<'a> {
+    /// the absolute path of an image
+    Image(&'a mut String),
+    /// the file name of a link
+    Link(&'a mut String),
+}
+
+/// walk through the references to the files used by the report
+trait VisitAssets {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef));
+}
+
+impl VisitAssets for ListElement {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        self.elements.iter_mut().for_each(|e| e.visit_assets(visitor));
+    }
+}
+
+impl VisitAssets for Element {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        self.content.iter_mut().for_each(|c| c.visit_assets(visitor));
+    }
+}
+
+impl VisitAssets for ContentElement {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        match self {
+            ContentElement::Content(c) => c.visit_assets(visitor),
+            ContentElement::Element(e) => e.visit_assets(visitor),
+        }
+    }
+}
+
+impl VisitAssets for Content {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        match self {
+            Content::Text(t) => t.visit_assets(visitor),
+            Content::Image(path) => visitor(AssetRef::Image(path)),
+            Content::Collapsable(c) => c.visit_assets(visitor),
+            Content::Array(_) | Content::Chart(_) => (),
+        }
+    }
+}
+
+impl VisitAssets for Text {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        self.0.iter_mut().for_each(|t| t.visit_assets(visitor));
+    }
+}
+
+impl VisitAssets for TextContent {
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        match self {
+            TextContent::Raw(_) => (),
+            TextContent::Link(l) => visitor(AssetRef::Link(&mut l.file_name)),
+            TextContent::Collapsable(c) => c.visit_assets(visitor),
+        }
+    }
+}
+
+impl<T> VisitAssets for Collapsable<T>
+where T : VisitAssets + SerdeSerialize
+{
+    fn visit_assets(&mut self, visitor : &mut dyn FnMut(AssetRef)) {
+        self.content.iter_mut().for_each(|c| c.visit_assets(visitor));
+    }
+}
+
+
+/// the files copied in the assets directory, by source path
+#[derive(Debug, Default)]
+struct Assets {
+    names : HashMap<PathBuf, String>,
+    used_names : HashSet<String>,
+}
+
+impl Assets {
+    /// the sources already in the assets directory keep their name, and the other files can't take it
+    fn new<'a>(assets_dir : &Path, sources : impl Iterator<Item = &'a PathBuf>) -> Self {
+        let mut assets = Assets::default();
+        for source in sources.filter(|source| source.parent() == Some(assets_dir)) {
+            let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            assets.used_names.insert(name.clone());
+            assets.names.insert(source.clone(), name);
+        }
+        assets
+    }
+
+    /// get the name of the source in the assets directory
+    /// NOTE : the files with the same name in different directories are renamed "name_1.ext", "name_2.ext", ...
+    fn get_name(&mut self, source : &Path) -> String {
+        if let Some(name) = self.names.get(source) {
+            return name.clone();
+        }
+        let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("asset");
+        let extension = source.extension().and_then(|e| e.to_str()).map(|e| format!(".{}", e)).unwrap_or_default();
+        let mut name = format!("{}{}", stem, extension);
+        let mut index = 1;
+        while self.used_names.contains(&name) {
+            name = format!("{}_{}{}", stem, index, extension);
+            index += 1;
+        }
+        self.used_names.insert(name.clone());
+        self.names.insert(source.to_path_buf(), name.clone());
+        name
+    }
+}
+
+
+/// get the source of an image, canonicalized as the sources of the links
+fn get_image_source(path : &str) -> Option<PathBuf> {
+    Path::new(path).canonicalize().ok().filter(|source| source.is_file())
+}
+
+/// get the source of the file of a link : the files directory of the report (as in the html without report), or the path itself (absolute or relative to the current directory)
+/// NOTE : the source is canonicalized, to copy a file only once whatever the path used to reference it
+fn get_link_source(out_dir : &Path, file_name : &str) -> Option<PathBuf> {
+    [out_dir.join(HtmlOptions::default().files_dir).join(file_name), PathBuf::from(file_name)]
+        .into_iter()
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+}
+
+
+impl Ir {
+    /// write the report in the output directory : the html file "index.html" and the images and files of the links in the "assets" directory
+    /// NOTE : the files of the links are searched inside the files directory of the output directory ("files" as in HtmlOptions), then as a path (absolute or relative to the current directory)
+    /// NOTE : the files already in the assets directory (e.g. the images plotted in it) are used in place
+    /// return the path of the html file, or an error listing the missing files (nothing is written in this case)
+    pub fn write_report<P : AsRef<Path>>(&self, out_dir : P) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        // the absolute path of the output directory, created only once the files are checked
+        let out_dir = match out_dir.as_ref().canonicalize() {
+            Ok(out_dir) => out_dir,
+            Err(_) => std::env::current_dir()?.join(out_dir),
+        };
+        let assets_dir = out_dir.join(REPORT_ASSETS_DIR);
+        let assets_dir = assets_dir.canonicalize().unwrap_or(assets_dir);
+
+        // the sources of the files, in the order of the references (the same file can be referenced with different paths)
+        let mut report = self.clone();
+        let mut sources = Vec::new();
+        report.elements.visit_assets(&mut |asset| sources.push(match asset {
+            AssetRef::Image(path) => get_image_source(path).ok_or(path.clone()),
+            AssetRef::Link(file_name) => get_link_source(&out_dir, file_name).ok_or(file_name.clone()),
+        }));
+        let missing = sources.iter().filter_map(|source| source.as_ref().err().cloned()).collect::<Vec<String>>();
+        if !missing.is_empty() {
+            return Err(format!("the report can't be written, missing files : {}", missing.join(", ")).into());
+        }
+        let sources = sources.into_iter().flatten().collect::<Vec<PathBuf>>();
+
+        // rewrite the references to the files in the copy of the report
+        let mut assets = Assets::new(&assets_dir, sources.iter());
+        let mut sources = sources.into_iter();
+        report.elements.visit_assets(&mut |asset| {
+            let source = sources.next().unwrap();
+            match asset {
+                AssetRef::Image(path) => *path = assets_dir.join(assets.get_name(&source)).to_string_lossy().to_string(),
+                AssetRef::Link(file_name) => *file_name = assets.get_name(&source),
+            }
+        });
+
+        fs::create_dir_all(&assets_dir)?;
+        for (source, name) in assets.names.iter() {
+            // a source already in the assets directory would be truncated by a copy onto itself
+            let destination = assets_dir.join(name);
+            if *source == destination {
+                continue;
+            }
+            fs::copy(source, destination).map_err(|e| format!("can't copy the file {} : {}", source.display(), e))?;
+        }
+
+        let index_path = out_dir.join(REPORT_INDEX);
+        let html = report.to_html_with_options(&index_path, &HtmlOptions::new().with_files_dir(REPORT_ASSETS_DIR))?;
+        fs::write(&index_path, html)?;
+        Ok(index_path)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_names() {
+        let mut assets = Assets::default();
+        assert_eq!(assets.get_name(Path::new("/a/img.png")), "img.png");
+        assert_eq!(assets.get_name(Path::new("/b/img.png")), "img_1.png");
+        assert_eq!(assets.get_name(Path::new("/a/img.png")), "img.png");
+        assert_eq!(assets.get_name(Path::new("/c/img.png")), "img_2.png");
+        assert_eq!(assets.get_name(Path::new("/a/notes")), "notes");
+
+        // the sources in the assets directory keep their name
+        let sources = [PathBuf::from("/out/assets/img.png"), PathBuf::from("/a/img.png")];
+        let mut assets = Assets::new(Path::new("/out/assets"), sources.iter());
+        assert_eq!(assets.get_name(Path::new("/a/img.png")), "img_1.png");
+        assert_eq!(assets.get_name(Path::new("/out/assets/img.png")), "img.png");
+    }
+}
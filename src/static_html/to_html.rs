@@ -1,10 +1,11 @@
 use std::collections::hash_map::DefaultHasher;
+use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
 
 use serde::Serialize;
 
-use super::presentation_data::{Array, Collapsable, Content, ContentElement, Element, ListElement, Text, TextContent, TextLink};
+use super::presentation_data::{Array, Chart, Collapsable, Content, ContentElement, Element, ListElement, Text, TextContent, TextLink};
 
 
 fn calculate_hash<T: Hash>(t: &T) -> u64 {
@@ -17,17 +18,96 @@ fn format_title(title : &str) -> String {
     title.replace("_", " ")
 }
 
+const BASE64_ALPHABET : &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// encode the bytes in base64 (with padding)
+fn encode_base64(bytes : &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk.iter().enumerate().fold(0u32, |triple, (index, byte)| triple | (*byte as u32) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                result.push(BASE64_ALPHABET[(triple >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// get the content of the file as a base64 data URI, the media type is guessed from the extension
+fn get_data_uri(path : &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("can't embed the file {} : {}", path.display(), e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    let media_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "csv" => "text/csv",
+        "txt" | "log" => "text/plain",
+        "json" => "application/json",
+        "html" => "text/html",
+        _ => "application/octet-stream",
+    };
+    Ok(format!("data:{};base64,{}", media_type, encode_base64(&bytes)))
+}
+
+
+/// the options of the conversion to html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// embed the images and the files of the links as base64 data URIs, to get a single portable html file
+    /// NOTE : the files of the links are searched inside the files directory next to the html file
+    pub self_contained : bool,
+    /// the directory of the files of the links, relative to the html file ("files" by default)
+    pub files_dir : String,
+}
+
+impl HtmlOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_files_dir(mut self, files_dir : &str) -> Self {
+        self.files_dir = files_dir.to_string();
+        self
+    }
+
+    pub fn with_self_contained(mut self, self_contained : bool) -> Self {
+        self.self_contained = self_contained;
+        self
+    }
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            self_contained : false,
+            files_dir : "files".to_string(),
+        }
+    }
+}
+
 
 /// This trait is used to convert the data to html
 pub trait ToHtmlDepth {
-    /// convert the data to html
+    /// convert the data to html with the given options
     /// WARN : the output path is used to get the relative path of the images (must be absolute, representing the file html)
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String;
+    /// return an error if a file to embed can't be read
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>>;
+
+    /// convert the data to html, the images and the files are referenced by their paths
+    /// NOTE : the default options don't read any file, so they never fail
+    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+        self.to_html_with_options(output_path, depth, &HtmlOptions::default()).unwrap()
+    }
 }
 
 impl ToHtmlDepth for String {
-    fn to_html<P : AsRef<Path>>(&self, _output_path : P, _depth : usize) -> String {
-        self.clone()
+    fn to_html_with_options<P : AsRef<Path>>(&self, _output_path : P, _depth : usize, _options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self.clone())
     }
 }
 
@@ -37,9 +117,12 @@ pub trait ToTableOfContent {
 }
 
 impl ToHtmlDepth for ListElement {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
-        let result = self.elements.iter().map(|e| e.to_html(output_path.as_ref(), depth)).collect::<Vec<String>>().join("\n");
-        result
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
+        let result = self.elements.iter()
+            .map(|e| e.to_html_with_options(output_path.as_ref(), depth, options))
+            .collect::<Result<Vec<String>, _>>()?
+            .join("\n");
+        Ok(result)
     }
 }
 
@@ -62,14 +145,14 @@ impl ToTableOfContent for ListElement {
 }
 
 impl ToHtmlDepth for Element {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         let hash = calculate_hash(&self).to_string();
         let mut result = String::new();
         result.push_str(&format!("<h{} id=\"{}\">{}</h{}>", depth, hash, format_title(&self.title), depth));
         for e in self.content.iter() {
-            result.push_str(&e.to_html(output_path.as_ref(), depth + 1));
+            result.push_str(&e.to_html_with_options(output_path.as_ref(), depth + 1, options)?);
         }
-        result
+        Ok(result)
     }
 }
 
@@ -94,10 +177,10 @@ impl ToTableOfContent for Element {
 }
 
 impl ToHtmlDepth for ContentElement {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         match self {
-            ContentElement::Content(c) => c.to_html(output_path, depth),
-            ContentElement::Element(e) => e.to_html(output_path, depth),
+            ContentElement::Content(c) => c.to_html_with_options(output_path, depth, options),
+            ContentElement::Element(e) => e.to_html_with_options(output_path, depth, options),
         }
     }
 }
@@ -112,66 +195,79 @@ impl ToTableOfContent for ContentElement {
 }
 
 impl ToHtmlDepth for Content {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         match self {
-            Content::Text(t) => t.to_html(output_path, depth),
+            Content::Text(t) => t.to_html_with_options(output_path, depth, options),
             Content::Image(s) => {
-                let parent = output_path.as_ref().parent().unwrap();
-                let relative_path = Path::new(s).strip_prefix(&parent).unwrap();
-                format!("<img src=\"{}\"/>", relative_path.to_str().unwrap()).to_html(output_path, depth)
+                let src = if options.self_contained {
+                    get_data_uri(Path::new(s))?
+                } else {
+                    // the images outside the directory of the html are referenced by their absolute path
+                    let parent = output_path.as_ref().parent().unwrap_or(Path::new(""));
+                    let path = Path::new(s).strip_prefix(parent).unwrap_or(Path::new(s));
+                    path.to_string_lossy().to_string()
+                };
+                format!("<img src=\"{}\"/>", src).to_html_with_options(output_path, depth, options)
             },
-            Content::Array(a) => a.to_html(output_path, depth),
-            Content::Collapsable(e) => e.to_html(output_path, depth),
+            Content::Array(a) => a.to_html_with_options(output_path, depth, options),
+            Content::Collapsable(e) => e.to_html_with_options(output_path, depth, options),
+            Content::Chart(c) => c.to_html_with_options(output_path, depth, options),
         }
     }
 }
 
 impl<T> ToHtmlDepth for Collapsable<T> 
 where T: ToHtmlDepth + Serialize {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         let mut result = String::new();
         result.push_str(&format!("<details><summary>{}</summary>", &self.summary));
         for e in self.content.iter() {
-            result.push_str(&e.to_html(output_path.as_ref(), depth));
+            result.push_str(&e.to_html_with_options(output_path.as_ref(), depth, options)?);
         }
         result.push_str("</details>");
-        result
+        Ok(result)
     }
 }
 
 impl ToHtmlDepth for Text {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         let mut result = String::new();
         for e in self.get_content().iter() {
-            result.push_str(&e.to_html(output_path.as_ref(), depth));
+            result.push_str(&e.to_html_with_options(output_path.as_ref(), depth, options)?);
         }
         result = result.replace("\n", "<br>");
-        result
+        Ok(result)
     }
 }
 
 impl ToHtmlDepth for TextContent {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         match self {
-            TextContent::Raw(s) => s.to_html(output_path, depth),
-            TextContent::Link(l) => l.to_html(output_path, depth),
-            TextContent::Collapsable(c) => c.to_html(output_path, depth),
+            TextContent::Raw(s) => s.to_html_with_options(output_path, depth, options),
+            TextContent::Link(l) => l.to_html_with_options(output_path, depth, options),
+            TextContent::Collapsable(c) => c.to_html_with_options(output_path, depth, options),
         }
     }
 }
 
 
 impl ToHtmlDepth for TextLink {
-    fn to_html<P : AsRef<Path>>(&self, output_path : P, depth : usize) -> String {
-        let file_path = Path::new("files").join(&self.file_name);
+    fn to_html_with_options<P : AsRef<Path>>(&self, output_path : P, depth : usize, options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
+        let file_path = Path::new(&options.files_dir).join(&self.file_name);
 
-        format!("<a href=\"{}\">{}</a>", file_path.as_path().to_str().unwrap(), &self.text).to_html(output_path, depth)
+        if options.self_contained {
+            // the file is downloaded with its name
+            let parent = output_path.as_ref().parent().unwrap_or(Path::new(""));
+            let data_uri = get_data_uri(&parent.join(&file_path))?;
+            return format!("<a href=\"{}\" download=\"{}\">{}</a>", data_uri, &self.file_name, &self.text).to_html_with_options(output_path, depth, options);
+        }
+        format!("<a href=\"{}\">{}</a>", file_path.as_path().to_str().unwrap(), &self.text).to_html_with_options(output_path, depth, options)
     }
 }
 
 
 impl ToHtmlDepth for Array {
-    fn to_html<P : AsRef<Path>>(&self, _output_path : P, _depth : usize) -> String {
+    fn to_html_with_options<P : AsRef<Path>>(&self, _output_path : P, _depth : usize, _options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
         let mut result = String::new();
         result.push_str("<table class=\"custom-table\">");
         result.push_str("<thead>");
@@ -191,6 +287,32 @@ impl ToHtmlDepth for Array {
         }
         result.push_str("</tbody>");
         result.push_str("</table>");
-        result
+        Ok(result)
+    }
+}
+
+
+impl ToHtmlDepth for Chart {
+    /// the chart is drawn by the chart script of the html template, from the json in the div
+    fn to_html_with_options<P : AsRef<Path>>(&self, _output_path : P, _depth : usize, _options : &HtmlOptions) -> Result<String, Box<dyn std::error::Error>> {
+        // the json can't close the script tag
+        let json = serde_json::to_string(self)?.replace("</", "<\\/");
+        Ok(format!("<div class=\"chart\"><script type=\"application/json\">{}</script></div>", json))
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(&[0xff, 0xfe, 0x00]), "//4A");
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+use plot_helper::data::plottable::Plottable;
+use plot_helper::data::sample::key::SerieKey;
+use plot_helper::data::sample::Sample;
+use plot_helper::data::sample_serie::memory_sample_serie::MemorySampleSerie;
+use plot_helper::generate_plot_key;
+use plot_helper::history::benchmark_run::BenchmarkRun;
+use plot_helper::history::history_store::HistoryStore;
+use plot_helper::history::regression::{RegressionConfig, RegressionReport, RegressionStatus};
+use serde_derive::{Deserialize, Serialize};
+
+
+generate_plot_key!(BenchKey[
+    Time { "time (s)", Numeric },
+    Memory { "memory (Mb)", Numeric },
+    Parser { "parser", String }
+]);
+
+#[derive(Debug, Clone)]
+struct BenchSample {
+    time : f32,
+    memory : f32,
+    parser : String,
+}
+
+impl Sample<BenchKey> for BenchSample {
+    fn get_numeric_value(&self, key : &BenchKey) -> f32 {
+        match key {
+            BenchKey::Time => self.time,
+            BenchKey::Memory => self.memory,
+            _ => panic!("not a numeric key"),
+        }
+    }
+
+    fn get_string_value(&self, key : &BenchKey) -> String {
+        match key {
+            BenchKey::Parser => self.parser.clone(),
+            _ => panic!("not a string key"),
+        }
+    }
+}
+
+/// create a run where the time of the parser "slow" is multiplied by the factor
+fn get_run(run_id : &str, timestamp : u64, slow_factor : f32) -> BenchmarkRun {
+    let mut samples = Vec::new();
+    for i in 0..20 {
+        let noise = (i % 5) as f32 * 0.01;
+        samples.push(BenchSample { time : 1.0 + noise, memory : 10.0 + noise, parser : "fast".to_string() });
+        samples.push(BenchSample { time : (2.0 + noise) * slow_factor, memory : 20.0 + noise, parser : "slow".to_string() });
+    }
+    let serie = MemorySampleSerie::new(samples);
+    let stats = serie.collect_stats_sorted_by_unique_values(&vec![BenchKey::Time, BenchKey::Memory], &BenchKey::Parser);
+    BenchmarkRun::new_with_timestamp(run_id, timestamp, &BenchKey::Parser, &stats)
+}
+
+
+#[test]
+fn history_store_test() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("plot_helper_history_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let store = HistoryStore::open(dir.to_str().unwrap())?;
+
+    let run_1 = get_run("nightly/1", 100, 1.0);
+    let run_2 = get_run("nightly/2", 200, 1.0);
+    store.save_run(&run_2)?;
+    store.save_run(&run_1)?;
+
+    assert_eq!(store.load_run("nightly/1")?, Some(run_1.clone()));
+    assert_eq!(store.load_run("missing")?, None);
+    assert_eq!(store.load_runs()?, vec![run_1.clone(), run_2.clone()]);
+    assert_eq!(store.load_previous_run(&run_2)?, Some(run_1.clone()));
+    assert_eq!(store.load_previous_run(&run_1)?, None);
+
+    // the ids are not merged when they are encoded as file names
+    let run_3 = get_run("nightly_1", 300, 1.0);
+    store.save_run(&run_3)?;
+    assert_eq!(store.load_run("nightly/1")?, Some(run_1.clone()));
+    assert_eq!(store.load_run("nightly_1")?, Some(run_3.clone()));
+    assert!(store.remove_run("nightly_1")?);
+
+    assert!(store.remove_run("nightly/1")?);
+    assert_eq!(store.load_runs()?, vec![run_2]);
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn regression_report_test() {
+    let baseline = get_run("baseline", 100, 1.0);
+    let current = get_run("current", 200, 1.5);
+
+    let report = RegressionReport::new(&baseline, &current, RegressionConfig::default());
+    assert_eq!(report.get_entries().len(), 4);
+    assert!(report.has_regression());
+
+    let regressions = report.get_regressions();
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].group, "slow");
+    assert_eq!(regressions[0].key, BenchKey::Time.get_display_name());
+    assert!((regressions[0].relative_change - 0.5).abs() < 1e-6);
+
+    // identical values are unchanged
+    for entry in report.get_entries().iter().filter(|e| e.group == "fast") {
+        assert_eq!(entry.status, RegressionStatus::Unchanged);
+    }
+
+    // an improvement when the run is compared the other way
+    let report = RegressionReport::new(&current, &baseline, RegressionConfig::default());
+    assert!(!report.has_regression());
+    assert!(report.get_entries().iter().any(|e| e.status == RegressionStatus::Improvement));
+
+    let array = RegressionReport::new(&baseline, &current, RegressionConfig::default()).to_array();
+    assert_eq!(array.get_header().len(), 7);
+    assert_eq!(array.get_data().len(), 4);
+    assert!(array.get_data().iter().any(|row| row[4] == "+50.0%" && row[6] == "regression"));
+}
+
+#[test]
+fn regression_from_zero_baseline_test() {
+    // the time of the parser "slow" is 0 in the baseline
+    let baseline = get_run("baseline", 100, 0.0);
+    let current = get_run("current", 200, 1.0);
+
+    let report = RegressionReport::new(&baseline, &current, RegressionConfig::default());
+    let regressions = report.get_regressions();
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].group, "slow");
+    assert_eq!(regressions[0].relative_change, f64::INFINITY);
+
+    // no change from 0
+    let report = RegressionReport::new(&baseline, &baseline, RegressionConfig::default());
+    assert!(report.get_entries().iter().all(|e| e.relative_change == 0.0 && e.status == RegressionStatus::Unchanged));
+}
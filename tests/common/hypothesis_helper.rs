@@ -0,0 +1,81 @@
+// ------------------------------ hypothesis helper ------------------------------
+
+use std::fmt::{Display, Formatter};
+
+use plot_helper::stat::hypothesis::{cliff_delta, cohen_d, kolmogorov_smirnov, mann_whitney_u, welch_t_test};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::common::ABSOLUTE_ERROR;
+
+
+
+/// represent a comparison of two series, with the expected results of the tests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestHypothesis {
+    pub name: String,
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub welch_t: f64,
+    pub welch_p: f64,
+    pub mann_whitney_u: f64,
+    pub mann_whitney_p: f64,
+    pub ks_d: f64,
+    pub ks_p: f64,
+    pub cohen_d: f64,
+    pub cliff_delta: f64,
+}
+
+impl TestHypothesis {
+    pub fn new(file_path : &str) -> Self {
+        let data = std::fs::read_to_string(file_path).unwrap();
+        serde_json::from_str(data.as_str()).unwrap()
+    }
+
+    pub fn new_from_dir(dir_path : &str) -> Vec<Self> {
+        let mut res = Vec::new();
+        for entry in std::fs::read_dir(dir_path).unwrap() {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            if path.is_file() {
+                res.push(Self::new(path.to_str().unwrap()));
+            }
+        }
+        res
+    }
+}
+
+impl Display for TestHypothesis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+
+fn assert_value(name : &str, instance_name : &str, obtained : f64, expected : f64) {
+    let result = relative_eq!(obtained, expected, epsilon = ABSOLUTE_ERROR);
+    assert!(result, "The {} is not correct for the comparison {}, with an obtained value of {} and an expected value of {}", name, instance_name, obtained, expected);
+}
+
+/// test the hypothesis tests and the effect sizes of the given instance
+pub fn test_hypothesis(instance : &TestHypothesis, verbose : bool) {
+    let instance_name = if !verbose {
+        format!("{} ", instance.name)
+    }else{
+        format!("{} ", instance)
+    };
+
+    let welch = welch_t_test(&instance.x, &instance.y);
+    assert_value("welch t", &instance_name, welch.statistic, instance.welch_t);
+    assert_value("welch p-value", &instance_name, welch.p_value, instance.welch_p);
+
+    let mann_whitney = mann_whitney_u(&instance.x, &instance.y);
+    assert_value("mann-whitney U", &instance_name, mann_whitney.statistic, instance.mann_whitney_u);
+    assert_value("mann-whitney p-value", &instance_name, mann_whitney.p_value, instance.mann_whitney_p);
+
+    let ks = kolmogorov_smirnov(&instance.x, &instance.y);
+    assert_value("kolmogorov-smirnov D", &instance_name, ks.statistic, instance.ks_d);
+    assert_value("kolmogorov-smirnov p-value", &instance_name, ks.p_value, instance.ks_p);
+
+    assert_value("cohen d", &instance_name, cohen_d(&instance.x, &instance.y), instance.cohen_d);
+    assert_value("cliff delta", &instance_name, cliff_delta(&instance.x, &instance.y), instance.cliff_delta);
+}
@@ -1,8 +1,11 @@
 pub mod stats_helper;
+pub mod hypothesis_helper;
 
 // ------------- tests params -------------
 pub const STATS_RESSOURCES_DIR_PATH : &'static str = "tests/ressources/stats_series/data/";
 
+pub const HYPOTHESIS_RESSOURCES_DIR_PATH : &'static str = "tests/ressources/hypothesis/data/";
+
 pub const ABSOLUTE_ERROR : f64 = 0.0001;
 
 
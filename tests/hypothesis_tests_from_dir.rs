@@ -0,0 +1,14 @@
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+
+
+#[test]
+fn test_hypothesis_from_dir() {
+
+    for instance in common::hypothesis_helper::TestHypothesis::new_from_dir(common::HYPOTHESIS_RESSOURCES_DIR_PATH){
+        common::hypothesis_helper::test_hypothesis(&instance, false);
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs;
 
-use plot_helper::static_html::presentation_data::{Array, Content, Element, Ir, ListElement};
+use plot_helper::plotter::plot_data::PlotData;
+use plot_helper::static_html::presentation_data::{Array, Chart, ChartKind, Content, Element, Ir, ListElement, Text, TextContent, TextLink};
+use plot_helper::static_html::to_html::HtmlOptions;
 
 
 
@@ -65,4 +68,142 @@ fn ir_build_valid_test(){
 fn ir_build_invalid_test(){
     let ir = Ir::new_from_file_system(IR_INVALID_PATH);
     assert!(ir.is_err());
-}
\ No newline at end of file
+}
+
+#[test]
+fn chart_to_html_test() -> Result<(), Box<dyn std::error::Error>> {
+    let plot_data = PlotData::from(HashMap::from([
+        ("b".to_string(), vec![(2.0, 4.0), (1.0, 1.0)]),
+        ("a".to_string(), vec![(1.0, 2.0), (2.0, 3.0)]),
+    ]));
+    let chart = Chart::new(ChartKind::Line, "time </script> per size", "size", "time", &plot_data);
+    let legends = chart.get_series().iter().map(|serie| serie.get_legend().as_str()).collect::<Vec<&str>>();
+    assert_eq!(legends, vec!["a", "b"]);
+    // the points of a line are sorted by x
+    assert_eq!(chart.get_series()[1].get_points(), &vec![(1.0, 1.0), (2.0, 4.0)]);
+
+    // the chart is saved like the other contents
+    let content : Content = chart.into();
+    let saved : Content = serde_json::from_str(&serde_json::to_string(&content)?)?;
+    assert_eq!(saved, content);
+
+    let ir : Ir = ListElement::from(vec![Element::new("chart".to_string(), vec![content.into()])]).into();
+    let html = ir.to_html(get_cannonical_path(IR_VALID_PATH))?;
+    assert!(html.contains("<div class=\"chart\">"));
+    // the script is inlined, the title can't close the json
+    assert!(html.contains("function setupChart"));
+    assert!(html.contains("time <\\/script> per size"));
+    Ok(())
+}
+
+#[test]
+fn self_contained_html_test() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("plot_helper_html_{}", std::process::id()));
+    fs::create_dir_all(dir.join("files"))?;
+    fs::write(dir.join("files").join("notes.txt"), "foo")?;
+    let html_path = dir.join("index.html");
+
+    let mut ir = get_ref_ir();
+    ir.add_element(Element::new(
+        "links".to_string(),
+        vec![Content::Text(Text::from(TextContent::Link(TextLink::new("notes.txt".to_string(), "notes".to_string())))).into()],
+    ));
+
+    // the images outside the directory of the html are referenced by their absolute path
+    let html = ir.to_html(&html_path)?;
+    assert!(html.contains(&format!("<img src=\"{}/test2/img2.png\"/>", get_cannonical_path(IR_VALID_PATH))));
+    assert!(html.contains("<a href=\"files/notes.txt\">notes</a>"));
+
+    // the images and the files are embedded
+    let html = ir.to_html_with_options(&html_path, &HtmlOptions::new().with_self_contained(true))?;
+    assert!(!html.contains(&get_cannonical_path(IR_VALID_PATH)));
+    assert_eq!(html.matches("<img src=\"data:image/png;base64,").count(), 3);
+    assert!(html.contains("<a href=\"data:text/plain;base64,Zm9v\" download=\"notes.txt\">notes</a>"));
+
+    // a missing file is an error
+    fs::remove_file(dir.join("files").join("notes.txt"))?;
+    assert!(ir.to_html_with_options(&html_path, &HtmlOptions::new().with_self_contained(true)).is_err());
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn write_report_test() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("plot_helper_report_{}", std::process::id()));
+    fs::create_dir_all(dir.join("files"))?;
+    fs::write(dir.join("files").join("notes.txt"), "foo")?;
+
+    let mut ir = get_ref_ir();
+    ir.add_element(Element::new(
+        "links".to_string(),
+        vec![Content::Text(Text::from(TextContent::Link(TextLink::new("notes.txt".to_string(), "notes".to_string())))).into()],
+    ));
+
+    // the images and the files are copied in the assets directory, the paths are relative to the html file
+    let index_path = ir.write_report(&dir)?;
+    assert_eq!(index_path, dir.canonicalize()?.join("index.html"));
+    let html = fs::read_to_string(&index_path)?;
+    assert!(!html.contains(&get_cannonical_path(IR_VALID_PATH)));
+    for name in ["img1.png", "img2.png", "img3.png"] {
+        assert!(html.contains(&format!("<img src=\"assets/{}\"/>", name)));
+        assert!(dir.join("assets").join(name).is_file());
+    }
+    assert!(html.contains("<a href=\"assets/notes.txt\">notes</a>"));
+    assert_eq!(fs::read_to_string(dir.join("assets").join("notes.txt"))?, "foo");
+
+    // a missing file is an error, not a panic
+    let mut ir = get_ref_ir();
+    ir.add_element(Element::new(
+        "missing".to_string(),
+        vec![
+            Content::Image(dir.join("missing.png").to_str().unwrap().to_string()).into(),
+            Content::Text(Text::from(TextContent::Link(TextLink::new("missing.txt".to_string(), "missing".to_string())))).into(),
+        ],
+    ));
+    let error = ir.write_report(&dir).unwrap_err().to_string();
+    assert!(error.contains("missing.png") && error.contains("missing.txt"));
+    // nothing is written on error
+    let new_dir = dir.join("new_report");
+    assert!(ir.write_report(&new_dir).is_err());
+    assert!(!new_dir.exists());
+
+    // a file referenced with different paths is copied once
+    fs::write(dir.join("notes.txt"), "bar")?;
+    let image = format!("{}/test2/img2.png", get_cannonical_path(IR_VALID_PATH));
+    let mut ir = Ir::default();
+    ir.add_element(Element::new(
+        "duplicates".to_string(),
+        vec![
+            Content::Image(image.clone()).into(),
+            Content::Image(image.replace("/test2/", "/test2/../test2/")).into(),
+            Content::Text(Text::from(TextContent::Link(TextLink::new(dir.join("notes.txt").to_str().unwrap().to_string(), "notes".to_string())))).into(),
+            Content::Text(Text::from(TextContent::Link(TextLink::new(dir.join(".").join("notes.txt").to_str().unwrap().to_string(), "notes".to_string())))).into(),
+        ],
+    ));
+    let index_path = ir.write_report(&new_dir)?;
+    let html = fs::read_to_string(&index_path)?;
+    assert_eq!(html.matches("<img src=\"assets/img2.png\"/>").count(), 2);
+    assert_eq!(html.matches("<a href=\"assets/notes.txt\">notes</a>").count(), 2);
+    assert_eq!(fs::read_dir(new_dir.join("assets"))?.count(), 2);
+
+    // an image already in the assets directory isn't truncated, nor overwritten by another file with the same name
+    fs::create_dir_all(dir.join("other"))?;
+    fs::write(new_dir.join("assets").join("plot.png"), "plot")?;
+    fs::write(dir.join("other").join("plot.png"), "other plot")?;
+    let mut ir = Ir::default();
+    ir.add_element(Element::new(
+        "assets".to_string(),
+        vec![
+            Content::Image(dir.join("other").join("plot.png").to_str().unwrap().to_string()).into(),
+            Content::Image(new_dir.join("assets").join("plot.png").to_str().unwrap().to_string()).into(),
+        ],
+    ));
+    let html = fs::read_to_string(ir.write_report(&new_dir)?)?;
+    assert!(html.contains("<img src=\"assets/plot.png\"/>") && html.contains("<img src=\"assets/plot_1.png\"/>"));
+    assert_eq!(fs::read_to_string(new_dir.join("assets").join("plot.png"))?, "plot");
+    assert_eq!(fs::read_to_string(new_dir.join("assets").join("plot_1.png"))?, "other plot");
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
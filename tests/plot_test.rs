@@ -0,0 +1,759 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+
+use plot_helper::data::filtering::{Filter, Filters};
+use plot_helper::data::plottable::Plottable;
+use plot_helper::data::sample::key::SerieKey;
+use plot_helper::data::sample::Sample;
+use plot_helper::data::sample_serie::memory_sample_serie::MemorySampleSerie;
+use plot_helper::generate_plot_key;
+use plot_helper::plotter::annotation::Annotation;
+use plot_helper::plotter::axis_range::AxisRange;
+use plot_helper::plotter::bar_plot::{bar_plot, BarMode, CategoryOrder};
+use plot_helper::plotter::color_scale::ColorScale;
+use plot_helper::plotter::figure_style::FigureStyle;
+use plot_helper::plotter::distribution_plot::{ecdf_plot, qq_plot};
+use plot_helper::plotter::heatmap_plot::{heatmap_plot, HeatmapGrid};
+use plot_helper::plotter::layout::{Layout, SubplotCell};
+use plot_helper::plotter::plot_data::PlotData;
+use plot_helper::plotter::legend_style::{LegendOrder, LegendPalette, LegendStyle, LegendStyles, MarkerShape};
+use plot_helper::plotter::line_plot::{line_plot, LineSerie};
+use plot_helper::plotter::scatter_plot::scatter_plot;
+use plot_helper::plotter::subplot_options::{LegendOptions, SubplotOptions};
+use plot_helper::stat::compression::{Compression, CompressionMethod, DensityBins};
+use plot_helper::stat::correlation::CorrelationMethod;
+use plot_helper::stat::fitting::{FitModel, FitResult};
+use plot_helper::stat::outlier::{OutlierConfig, OutlierStrategy};
+use plot_helper::stat::smoothing::Smoothing;
+use plot_helper::stat::stats_serie::MetricName;
+use plotters::style::RGBColor;
+use serde_derive::{Deserialize, Serialize};
+
+
+generate_plot_key!(ParseKey[
+    Size { "size (Kb)", Numeric, Kilobytes },
+    Time { "time (s)", Numeric, Seconds },
+    Parser { "parser", String }
+]);
+
+#[derive(Debug, Clone)]
+struct ParseSample {
+    size : f32,
+    time : f32,
+    parser : String,
+}
+
+impl Sample<ParseKey> for ParseSample {
+    fn get_numeric_value(&self, key : &ParseKey) -> f32 {
+        match key {
+            ParseKey::Size => self.size,
+            ParseKey::Time => self.time,
+            _ => panic!("not a numeric key"),
+        }
+    }
+
+    fn get_string_value(&self, key : &ParseKey) -> String {
+        match key {
+            ParseKey::Parser => self.parser.clone(),
+            _ => panic!("not a string key"),
+        }
+    }
+}
+
+/// the time of the "linear" parser is 2 * size, the time of the "quadratic" parser is size^2 / 10, with an outlier for each
+fn get_samples() -> MemorySampleSerie<ParseSample, ParseKey> {
+    let mut samples = Vec::new();
+    for i in 1..=50 {
+        let size = i as f32;
+        let noise = ((i * 7) % 5) as f32 * 0.1;
+        samples.push(ParseSample { size, time : 2.0 * size + noise, parser : "linear".to_string() });
+        samples.push(ParseSample { size, time : size * size / 10.0 + noise, parser : "quadratic".to_string() });
+    }
+    samples.push(ParseSample { size : 25.0, time : 10000.0, parser : "linear".to_string() });
+    samples.push(ParseSample { size : 25.0, time : 10000.0, parser : "quadratic".to_string() });
+    MemorySampleSerie::new(samples)
+}
+
+fn get_output_path(name : &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("plot_helper_plot_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+
+#[test]
+fn scatter_plot_with_fit_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("scatter.png");
+    let options = [
+        SubplotOptions::new()
+            .with_outliers(OutlierConfig::new_y(OutlierStrategy::ModifiedZScore(3.5)).with_show_removed(true))
+            .with_fit(FitModel::Polynomial(2))
+    ];
+
+    let fits = scatter_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &options,
+    )?;
+    assert!(path.is_file());
+
+    assert_eq!(fits.len(), 1);
+    let quadratic = &fits[0]["quadratic"];
+    assert!((quadratic.coefficients[2] - 0.1).abs() < 1e-3);
+    assert!(quadratic.r_squared > 0.99);
+    // the outliers are removed before the fit : the linear parser is a line
+    let linear = &fits[0]["linear"];
+    assert!(linear.coefficients[2].abs() < 1e-3);
+    assert!((linear.coefficients[1] - 2.0).abs() < 0.05);
+    assert!((linear.predict(40.0) - 80.0).abs() < 1.0);
+
+    let array = FitResult::to_array(fits[0].iter());
+    assert_eq!(array.get_data().len(), 2);
+    assert_eq!(array.get_data()[0][0], "linear");
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn scatter_plot_density_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("density.png");
+    let outliers = OutlierConfig::new_y(OutlierStrategy::ModifiedZScore(3.5));
+    let options = [
+        SubplotOptions::from(outliers).with_density(DensityBins::Hexagonal(15)),
+        SubplotOptions::from(outliers).with_density(DensityBins::Rectangular(15)),
+    ];
+
+    scatter_plot(
+        &data,
+        None,
+        path.to_str().unwrap(),
+        &Layout::new(1, 2),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &options,
+    )?;
+    assert!(path.is_file());
+
+    // no legend area in density mode : the width of the PNG (in its header) is the width of the subplots
+    let png = fs::read(&path)?;
+    let width = u32::from_be_bytes(png[16..20].try_into()?);
+    assert_eq!(width, Layout::new(1, 2).get_size(FigureStyle::default().get_subplot_size()).0);
+
+    // every point without the outliers is in a bin
+    let plot_data = PlotData::from_it_without_compression(data.into_iter_with_filter((ParseKey::Size, Some(ParseKey::Time)), None, None), None, outliers);
+    let points = plot_data.get_data().values().flatten().cloned().collect::<Vec<(f32, f32)>>();
+    assert!(points.len() < 102 && points.iter().all(|(_, y)| *y < 10000.0));
+    let (range_x, range_y) = plot_data.get_range();
+    for bins in [DensityBins::Hexagonal(15), DensityBins::Rectangular(15)] {
+        let density_bins = bins.bin_data_serie(&points, &range_x, &range_y);
+        assert_eq!(density_bins.iter().map(|bin| bin.count).sum::<usize>(), points.len());
+        assert!(density_bins.iter().all(|bin| bin.count > 0));
+    }
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn bar_plot_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("bars.png");
+    let options = [
+        SubplotOptions::new().with_bars(BarMode::Grouped, CategoryOrder::ValueDescending),
+        SubplotOptions::new().with_bars(BarMode::Stacked, CategoryOrder::Name),
+    ];
+
+    let bar_data = bar_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Parser, Some(ParseKey::Time), None), (ParseKey::Size, None, None)],
+        &options,
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    // the quadratic parser is the slowest
+    assert_eq!(bar_data[0].get_categories(), &vec!["quadratic".to_string(), "linear".to_string()]);
+    assert_eq!(bar_data[0].get("linear", "quadratic"), None);
+    // one sample per parser and per size
+    assert_eq!(bar_data[1].get_categories().len(), 50);
+    assert_eq!(bar_data[1].get("3", "linear"), Some(1.0));
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn distribution_plots_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("ecdf.png");
+    let curves = ecdf_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Time, None), (ParseKey::Time, None)],
+        false,
+    )?;
+    assert!(path.is_file());
+    fs::remove_file(path)?;
+    // a curve for each parser, the cdf increases from 0 to 1
+    assert_eq!(curves.len(), 2);
+    for subplot_curves in curves.iter() {
+        assert_eq!(subplot_curves.iter().map(|(legend, _)| legend.as_str()).collect::<Vec<&str>>(), vec!["linear", "quadratic"]);
+        for (_, steps) in subplot_curves.iter() {
+            assert!(steps.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+            assert!(steps.iter().all(|(_, y)| (0.0..=1.0).contains(y)));
+            assert_eq!(steps.last().unwrap().1, 1.0);
+        }
+    }
+
+    let path = get_output_path("ccdf.png");
+    let curves = ecdf_plot(&data, Some(ParseKey::Parser), path.to_str().unwrap(), &Layout::new(1, 1), &FigureStyle::default(), vec![(ParseKey::Time, None)], true)?;
+    assert!(path.is_file());
+    fs::remove_file(path)?;
+    // the complementary cdf levels are in (0, 1] (drawn in log10) and decrease
+    for (_, steps) in curves[0].iter() {
+        assert!(!steps.is_empty());
+        assert!(steps.iter().all(|(_, y)| y.is_finite() && *y <= 0.0));
+        assert!(steps.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    let path = get_output_path("qq.png");
+    let quantiles = qq_plot(&data, ParseKey::Parser, ("linear", "quadratic"), path.to_str().unwrap(), &Layout::new(1, 1), &FigureStyle::default(), vec![(ParseKey::Time, None)])?;
+    assert!(path.is_file());
+    // the quantiles of both legends increase together
+    assert!(!quantiles[0].is_empty());
+    assert!(quantiles[0].windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+    // an unknown legend is an error
+    assert!(qq_plot(&data, ParseKey::Parser, ("linear", "cubic"), path.to_str().unwrap(), &Layout::new(1, 1), &FigureStyle::default(), vec![(ParseKey::Time, None)]).is_err());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn scatter_plot_per_subplot_legend_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("per_subplot_legend.png");
+
+    // different legend keys : each subplot has its own legend in its chart
+    let legend = LegendOptions::from(vec![Some(ParseKey::Parser), None]);
+    assert_eq!(legend.keys.get_shared_key(), None);
+    assert_eq!(legend.keys.get_legend_key(2, 0), Some(ParseKey::Parser));
+    assert_eq!(legend.keys.get_legend_key(2, 1), None);
+
+    scatter_plot(
+        &data,
+        legend,
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &[OutlierConfig::new_y(OutlierStrategy::ModifiedZScore(3.5))],
+    )?;
+    assert!(path.is_file());
+
+    // no legend area on the right : the width of the PNG (in its header) is the width of the subplots
+    let png = fs::read(&path)?;
+    let width = u32::from_be_bytes(png[16..20].try_into()?);
+    assert_eq!(width, Layout::new(2, 1).get_size(FigureStyle::default().get_subplot_size()).0);
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn legend_styles_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("legend_styles.png");
+
+    // the same styles are given to both figures
+    let styles = LegendStyles::new(LegendPalette::ColorBlind)
+        .with_order(["quadratic", "linear"])
+        .with_style("linear", LegendStyle::new(RGBColor(0, 0, 0)).with_marker(MarkerShape::Triangle));
+    assert_eq!(styles.get("quadratic").unwrap().color, RGBColor(230, 159, 0));
+
+    // the legends of the data are already assigned : the figures draw them with the given styles, in the given order
+    let legends = data.collect_unique_values(&ParseKey::Parser, None);
+    let mut assigned = styles.clone();
+    assigned.assign_sorted(legends.iter());
+    assert_eq!(assigned, styles);
+    assert_eq!(assigned.get_legends(), &vec!["quadratic".to_string(), "linear".to_string()]);
+    let linear = assigned.get("linear").unwrap();
+    assert_eq!((linear.color, linear.marker), (RGBColor(0, 0, 0), MarkerShape::Triangle));
+
+    scatter_plot(
+        &data,
+        LegendOptions::new(Some(ParseKey::Parser)).with_styles(styles.clone()),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new()],
+    )?;
+    assert!(path.is_file());
+
+    line_plot(
+        &data,
+        LegendOptions::new(Some(ParseKey::Parser)).with_styles(styles),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new()],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn legend_overflow_test() -> Result<(), Box<dyn std::error::Error>> {
+    // more legends than the height of the figure, with long labels
+    let samples = (0..200).map(|i| ParseSample {
+        size : (i % 50) as f32,
+        time : (i % 50) as f32 * (i / 50 + 1) as f32,
+        parser : if i % 40 == 0 {
+            format!("parser {} with a very long name which doesn't fit", i % 40)
+        } else {
+            format!("parser {}", i % 40)
+        },
+    }).collect::<Vec<ParseSample>>();
+    let data = MemorySampleSerie::new(samples);
+    let path = get_output_path("legend_overflow.png");
+
+    // the entries of the legend in natural order ("parser 9" before "parser 10")
+    let mut entries = data.collect_unique_values(&ParseKey::Parser, None).into_iter().map(|legend| (legend, ())).collect::<Vec<(String, ())>>();
+    LegendOrder::Natural.sort(&mut entries);
+    assert_eq!(entries.len(), 40);
+    assert!(entries[0].0.starts_with("parser 0 with"));
+    assert_eq!((entries[9].0.as_str(), entries[10].0.as_str()), ("parser 9", "parser 10"));
+    // the styles are all different when the colors of the palette are exhausted
+    let mut styles = LegendStyles::default();
+    styles.assign_sorted(entries.iter().map(|(legend, _)| legend));
+    let all_styles = entries.iter().map(|(legend, _)| *styles.get(legend).unwrap()).collect::<Vec<LegendStyle>>();
+    assert!(all_styles.iter().enumerate().all(|(i, style)| all_styles[..i].iter().all(|other| other != style)));
+
+    scatter_plot(
+        &data,
+        LegendOptions::new(Some(ParseKey::Parser)).with_order(LegendOrder::Natural),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new()],
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn layout_with_spans_and_shared_axes_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("layout.png");
+    let linear = Filters::new(vec![Filter::new_str(ParseKey::Parser, |parser| parser == "linear")]);
+
+    // a subplot on the whole first row, the second row share the y axis
+    let layout = Layout::new(2, 2)
+        .with_cells(vec![SubplotCell::new(0, 0).with_span(1, 2), SubplotCell::new(1, 0), SubplotCell::new(1, 1)])
+        .with_shared_y(true);
+    scatter_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &layout,
+        &FigureStyle::default(),
+        vec![
+            (ParseKey::Size, Some(ParseKey::Time), None),
+            (ParseKey::Size, Some(ParseKey::Time), Some(&linear)),
+            (ParseKey::Size, Some(ParseKey::Time), None),
+        ],
+        &[OutlierConfig::new_y(OutlierStrategy::ModifiedZScore(3.5))],
+    )?;
+    assert!(path.is_file());
+
+    // 3 series on an automatic grid, with an empty cell
+    line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::auto(3).with_shared_x(true),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None); 3],
+        &[SubplotOptions::new()],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn line_plot_with_smoothing_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("line.png");
+
+    let fits = line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &[
+            SubplotOptions::new().with_outliers(true.into()).with_fit(FitModel::Linear),
+            SubplotOptions::new().with_outliers(true.into()).with_smoothing(Smoothing::Loess(0.2), true),
+        ],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    assert_eq!(fits.len(), 2);
+    let linear = &fits[0]["linear"];
+    assert!((linear.coefficients[1] - 2.0).abs() < 1e-2);
+    assert!(fits[1].is_empty());
+
+    // the smoothed data of the second subplot : the outliers are removed, the lines are kept by the local regression
+    let mut plot_data = PlotData::from_it_without_compression(
+        data.into_iter_with_filter((ParseKey::Size, Some(ParseKey::Time)), Some(ParseKey::Parser), None),
+        Some(MetricName::Median),
+        true.into(),
+    );
+    plot_data.smooth(Smoothing::Loess(0.2));
+    let smoothed = plot_data.get_data();
+    assert_eq!(smoothed["linear"].len(), 50);
+    assert!(smoothed["linear"].windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(smoothed["linear"].iter().all(|(x, y)| (y - 2.0 * x).abs() < 0.5));
+    assert!(smoothed["quadratic"].iter().all(|(x, y)| (y - x * x / 10.0).abs() < 1.0));
+    assert!(plot_data.get_range().1.end < 300.0);
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn dark_high_dpi_figure_style_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("dark_style.png");
+
+    let style = FigureStyle::dark().with_subplot_size(400, 300).with_scale(2.0);
+    line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &style,
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new()],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    // the width and the height of the PNG are in its header, the legend area is on the right
+    let png = fs::read(&path)?;
+    let width = u32::from_be_bytes(png[16..20].try_into()?);
+    let height = u32::from_be_bytes(png[20..24].try_into()?);
+    assert!(width > 1600);
+    assert_eq!(height, 600);
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn custom_texts_and_units_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("texts.png");
+
+    // the ticks of the axes are formatted with the units of the keys
+    assert_eq!(ParseKey::Time.get_unit().format(90.0), "1m 30s");
+    assert_eq!(ParseKey::Size.get_unit().format(2048.0), "2 MiB");
+
+    scatter_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1).with_title("parsing benchmark"),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &[
+            SubplotOptions::new().with_caption("all the files").with_x_label("file size").with_y_label("parsing time"),
+            SubplotOptions::new(),
+        ],
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn axis_ranges_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("axis_ranges.png");
+
+    // the ranges of the axes computed from the range of the data
+    let plot_data = PlotData::from_it_without_compression(
+        data.into_iter_with_filter((ParseKey::Size, Some(ParseKey::Time)), Some(ParseKey::Parser), None),
+        None,
+        OutlierConfig::default(),
+    );
+    let (range_x, range_y) = plot_data.get_range();
+    assert_eq!(range_x, 1.0..50.0);
+    // a fixed range clips the points outside
+    assert_eq!(AxisRange::fixed(10.0, 30.0).apply(&range_x), 10.0..30.0);
+    assert_eq!(AxisRange::fixed(0.0, 100.0).get_clip_range(), Some(0.0..100.0));
+    // with zero and a padding of 10% on each side
+    assert_eq!(AxisRange::new().with_include_zero().with_padding(0.1).apply(&range_x), -5.0..55.0);
+    // without clip, the fixed range is extended to the data
+    assert_eq!(AxisRange::fixed(0.0, 100.0).without_clip().get_clip_range(), None);
+    assert_eq!(AxisRange::fixed(0.0, 100.0).without_clip().apply(&range_y).end, range_y.end);
+
+    // a fixed range hiding the points above, and a range starting at zero with a padding
+    scatter_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None), (ParseKey::Size, Some(ParseKey::Time), None)],
+        &[
+            SubplotOptions::new().with_x_range(AxisRange::fixed(10.0, 30.0)).with_y_range(AxisRange::fixed(0.0, 100.0)),
+            SubplotOptions::new().with_x_range(AxisRange::new().with_include_zero().with_padding(0.1)),
+        ],
+    )?;
+    assert!(path.is_file());
+
+    line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new().with_y_range(AxisRange::fixed(0.0, 100.0).without_clip())],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn annotations_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("annotations.png");
+
+    let annotations = SubplotOptions::new()
+        .with_annotation(Annotation::horizontal_line(150.0).with_label("SLA").with_color(RGBColor(200, 0, 0)))
+        .with_annotation(Annotation::vertical_line(20.0).with_label("baseline"))
+        .with_annotation(Annotation::x_interval(30.0, 40.0).with_label("slow files").with_color(RGBColor(0, 100, 200)))
+        .with_annotation(Annotation::y_interval(0.0, 25.0))
+        .with_annotation(Annotation::text(10.0, 200.0, "text at (10, 200)"))
+        .with_annotation(Annotation::arrow((35.0, 220.0), (45.0, 200.0)).with_label("quadratic"))
+        // outside the ranges, not drawn
+        .with_annotation(Annotation::horizontal_line(1e6));
+    line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[annotations],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    bar_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Parser, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new().with_annotation(Annotation::horizontal_line(50.0).with_label("threshold"))],
+        MetricName::Mean,
+    )?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn compression_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("compression.png");
+
+    // a line downsampled with LTTB, and the points averaged on a coarse grid above 20 points
+    line_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(2, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None); 2],
+        &[
+            SubplotOptions::new().with_compression(Compression::new(CompressionMethod::Lttb(10))),
+            SubplotOptions::new().with_compression(Compression::new(CompressionMethod::Grid(10)).with_min_nb_points(20)),
+        ],
+        MetricName::Median,
+    )?;
+    assert!(path.is_file());
+
+    scatter_plot(
+        &data,
+        Some(ParseKey::Parser),
+        path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(ParseKey::Size, Some(ParseKey::Time), None)],
+        &[SubplotOptions::new().with_compression(Compression::none())],
+    )?;
+    assert!(path.is_file());
+
+    // the compressed series of the subplots
+    let get_plot_data = |aggregation_metric| PlotData::from_it_without_compression(
+        data.into_iter_with_filter((ParseKey::Size, Some(ParseKey::Time)), Some(ParseKey::Parser), None),
+        aggregation_metric,
+        OutlierConfig::none(),
+    );
+    let mut lttb = get_plot_data(Some(MetricName::Median));
+    lttb.compress_with(&Compression::new(CompressionMethod::Lttb(10)));
+    for serie in lttb.get_data().values() {
+        assert_eq!(serie.len(), 10);
+        assert_eq!((serie[0].0, serie[9].0), (1.0, 50.0));
+    }
+    let mut grid = get_plot_data(Some(MetricName::Median));
+    grid.compress_with(&Compression::new(CompressionMethod::Grid(10)).with_min_nb_points(20));
+    assert!(grid.get_data().values().all(|serie| serie.len() < 50));
+    // a serie with fewer points than the minimum is kept
+    let mut small = get_plot_data(Some(MetricName::Median));
+    small.compress_with(&Compression::new(CompressionMethod::Grid(10)).with_min_nb_points(50));
+    assert!(small.get_data().values().all(|serie| serie.len() == 50));
+    // without aggregation, all the points are kept (with the outlier)
+    let mut all_points = get_plot_data(None);
+    all_points.compress_with(&Compression::none());
+    assert!(all_points.get_data().values().all(|serie| serie.len() == 51));
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// the macro generates items at the top level, another key needs its own module
+mod server {
+    use super::*;
+
+    generate_plot_key!(ServerKey[
+        Load { "load (req/s)", Numeric },
+        Latency { "latency", Numeric, Milliseconds },
+        Memory { "memory", Numeric, Megabytes },
+        Host { "host", String }
+    ]);
+
+    #[derive(Debug, Clone)]
+    struct ServerSample {
+        load : f32,
+        latency : f32,
+        memory : f32,
+        host : String,
+    }
+
+    impl Sample<ServerKey> for ServerSample {
+        fn get_numeric_value(&self, key : &ServerKey) -> f32 {
+            match key {
+                ServerKey::Load => self.load,
+                ServerKey::Latency => self.latency,
+                ServerKey::Memory => self.memory,
+                _ => panic!("not a numeric key"),
+            }
+        }
+
+        fn get_string_value(&self, key : &ServerKey) -> String {
+            match key {
+                ServerKey::Host => self.host.clone(),
+                _ => panic!("not a string key"),
+            }
+        }
+    }
+
+    #[test]
+    fn secondary_y_axis_test() -> Result<(), Box<dyn std::error::Error>> {
+        // the latency is a few ms, the memory is hundreds of MiB : they need their own axis
+        let samples = (1..=40).flat_map(|i| ["a", "b"].into_iter().map(move |host| {
+            let load = (i * 10) as f32;
+            let factor = if host == "a" { 1.0 } else { 1.5 };
+            ServerSample { load, latency : factor * (1.0 + load / 100.0), memory : factor * (200.0 + load), host : host.to_string() }
+        })).collect::<Vec<ServerSample>>();
+        let data = MemorySampleSerie::new(samples);
+        let path = get_output_path("secondary_y_axis.png");
+
+        // a subplot with the memory on the right axis, and a subplot with the latency only : the legend names the metrics
+        line_plot(
+            &data,
+            Some(ServerKey::Host),
+            path.to_str().unwrap(),
+            &Layout::new(1, 2),
+            &FigureStyle::default(),
+            vec![
+                LineSerie::new(ServerKey::Load, Some(ServerKey::Latency), None).with_secondary_y(ServerKey::Memory),
+                LineSerie::new(ServerKey::Load, Some(ServerKey::Latency), None),
+            ],
+            &[SubplotOptions::new().with_secondary_y_label("resident memory").with_secondary_y_range(AxisRange::new().with_include_zero())],
+            MetricName::Mean,
+        )?;
+        assert!(path.is_file());
+
+        // the secondary y axis has the range of the memory, including zero
+        let memory = PlotData::from_it_without_compression(
+            data.into_iter_with_filter((ServerKey::Load, Some(ServerKey::Memory)), Some(ServerKey::Host), None),
+            Some(MetricName::Mean),
+            OutlierConfig::default(),
+        );
+        let (range_x, range_y) = memory.get_range();
+        assert_eq!(range_x, 10.0..400.0);
+        assert_eq!(AxisRange::new().with_include_zero().apply(&range_y), 0.0..900.0);
+        // the ticks of each axis are formatted with the unit of its key
+        assert_eq!(ServerKey::Latency.get_unit().format(2.0), "2ms");
+        assert_eq!(ServerKey::Memory.get_unit().format(900.0), "900 MiB");
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn correlation_heatmap_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_samples();
+    let path = get_output_path("correlation.png");
+
+    let matrix = data.collect_correlation_matrix(&[ParseKey::Size, ParseKey::Time], CorrelationMethod::Spearman, None);
+    assert_eq!(matrix.get_labels(), &vec![ParseKey::Size.get_display_name(), ParseKey::Time.get_display_name()]);
+    assert!(matrix.get("size (Kb)", "time (s)").unwrap() > 0.5);
+
+    heatmap_plot(&HeatmapGrid::from(&matrix), path.to_str().unwrap(), &FigureStyle::default(), "correlation", Some(ColorScale::new_correlation()), true)?;
+    assert!(path.is_file());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
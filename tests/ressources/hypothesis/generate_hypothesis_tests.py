@@ -0,0 +1,93 @@
+import argparse
+from dataclasses import dataclass, asdict
+import json
+import os
+import random
+import numpy as np
+from scipy import stats
+
+
+
+def generate_random_float_array(rng : random.Random, size: int, shift: float, right_digits: int) -> list[float]:
+    return [round(rng.uniform(-100, 100) + shift, right_digits) for _ in range(size)]
+
+
+def cohen_d(x : list[float], y : list[float]) -> float:
+    n1, n2 = len(x), len(y)
+    pooled_var = ((n1 - 1) * np.var(x, ddof=1) + (n2 - 1) * np.var(y, ddof=1)) / (n1 + n2 - 2)
+    return float((np.mean(x) - np.mean(y)) / np.sqrt(pooled_var))
+
+
+def cliff_delta(x : list[float], y : list[float]) -> float:
+    greater = sum(1 for a in x for b in y if a > b)
+    lower = sum(1 for a in x for b in y if a < b)
+    return (greater - lower) / (len(x) * len(y))
+
+
+@dataclass
+class HypothesisTest:
+    name: str
+    x: list[float]
+    y: list[float]
+    welch_t: float
+    welch_p: float
+    mann_whitney_u: float
+    mann_whitney_p: float
+    ks_d: float
+    ks_p: float
+    cohen_d: float
+    cliff_delta: float
+
+    @staticmethod
+    def generate_hypothesis_test(rng : random.Random, size_x: int, size_y: int, shift: float, right_digits: int) -> 'HypothesisTest':
+        name = f"{size_x}_{size_y}_shift_{shift}_digits_{right_digits}"
+        x = generate_random_float_array(rng, size_x, 0.0, right_digits)
+        y = generate_random_float_array(rng, size_y, shift, right_digits)
+
+        welch = stats.ttest_ind(x, y, equal_var=False)
+        mann_whitney = stats.mannwhitneyu(x, y)
+        ks = stats.ks_2samp(x, y, method="exact")
+        return HypothesisTest(
+            name, x, y,
+            float(welch.statistic), float(welch.pvalue),
+            float(mann_whitney.statistic), float(mann_whitney.pvalue),
+            float(ks.statistic), float(ks.pvalue),
+            cohen_d(x, y), cliff_delta(x, y),
+        )
+
+    def to_json(self) -> str:
+        return json.dumps(asdict(self), indent=4)
+
+    def save_to_json(self, folder: str) -> None:
+        file_path = os.path.join(folder, f"{self.name}.json")
+        with open(file_path, "w") as f:
+            f.write(self.to_json())
+
+
+OUTPUT_FOLDER = "plot_helper/tests/ressources/hypothesis/data/"
+# (size of x, size of y, shift of y, number of digits : 0 to get ties)
+TEST_CONFIGURATIONS = [
+    (3, 4, 0.0, 2),
+    (5, 5, 50.0, 2),
+    (8, 6, 20.0, 2),
+    (8, 8, 80.0, 2),
+    (6, 9, 0.0, 0),
+    (10, 12, 30.0, 2),
+    (15, 20, 0.0, 2),
+    (20, 15, 40.0, 0),
+    (30, 30, 20.0, 1),
+    (40, 25, -30.0, 2),
+]
+
+def get_argv_parser():
+    parser = argparse.ArgumentParser(description='Generate the hypothesis tests, and store them in a folder')
+    parser.add_argument('-s', '--seed', type=int, default=42, help="Seed of the random generator")
+    return parser
+
+if __name__ == "__main__":
+    parser = get_argv_parser()
+    args = parser.parse_args()
+    rng = random.Random(args.seed)
+    os.makedirs(OUTPUT_FOLDER, exist_ok=True)
+    for (size_x, size_y, shift, right_digits) in TEST_CONFIGURATIONS:
+        HypothesisTest.generate_hypothesis_test(rng, size_x, size_y, shift, right_digits).save_to_json(OUTPUT_FOLDER)